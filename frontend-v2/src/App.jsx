@@ -53,7 +53,8 @@ function App() {
             setTimeout(() => updateStep(3, 'completed'), 3000)
             setTimeout(() => {
                 updateStep(4, 'completed')
-                setResult(response.data)
+                // The Rust gateway wraps results in { status, data, request_id }
+                setResult(response.data.data ?? response.data)
                 setLoading(false)
             }, 4000)
 
@@ -1,7 +1,8 @@
-from fastapi import FastAPI, UploadFile, File, HTTPException
+from fastapi import FastAPI, UploadFile, File, Form, HTTPException
 import pytesseract
 from pdf2image import convert_from_bytes
 import io
+from typing import Optional
 from pydantic import BaseModel
 
 app = FastAPI()
@@ -11,7 +12,12 @@ class OCRResponse(BaseModel):
     page_count: int
 
 @app.post("/ocr/pdf", response_model=OCRResponse)
-async def ocr_pdf(file: UploadFile = File(...)):
+async def ocr_pdf(
+    file: UploadFile = File(...),
+    lang: str = Form("eng"),
+    first_page: Optional[int] = Form(None),
+    last_page: Optional[int] = Form(None),
+):
     if file.content_type != "application/pdf":
         raise HTTPException(status_code=400, detail="File must be a PDF")
     
@@ -20,11 +26,12 @@ async def ocr_pdf(file: UploadFile = File(...)):
         
         # Try to run OCR
         try:
-            images = convert_from_bytes(content)
+            images = convert_from_bytes(content, first_page=first_page, last_page=last_page)
+            page_offset = (first_page or 1) - 1
             full_text = []
             for i, image in enumerate(images):
-                text = pytesseract.image_to_string(image)
-                full_text.append(f"--- Page {i+1} ---\n{text}")
+                text = pytesseract.image_to_string(image, lang=lang)
+                full_text.append(f"--- Page {page_offset + i + 1} ---\n{text}")
             
             extracted_text = "\n\n".join(full_text)
             page_count = len(images)
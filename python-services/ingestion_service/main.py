@@ -3,7 +3,7 @@ Ingestion Service FastAPI Application
 Provides REST API endpoints for case law document ingestion
 """
 
-from fastapi import FastAPI, HTTPException, status, UploadFile, File, Form, Depends
+from fastapi import FastAPI, HTTPException, status, UploadFile, File, Form, Depends, Query
 from fastapi.middleware.cors import CORSMiddleware
 from pydantic import BaseModel, Field
 from typing import Optional, List
@@ -14,7 +14,8 @@ import os
 import tempfile
 
 from ingestion_service.service import get_ingestion_service, IngestionService
-from shared.models import IngestionResult
+from shared.models import CaseLawDocument, IngestionResult
+from shared.validators import citation_key
 from shared.security import verify_token, require_role, validate_path, validate_pdf_file
 from shared.middleware import setup_middleware
 from shared.rate_limiter import RateLimitMiddleware
@@ -98,17 +99,14 @@ async def startup_event():
         log_configuration()
         
         # Import dependencies
-        from embedding_service.service import get_embedding_service
         from vector_index.service import get_vector_index_service
         
-        # Initialize dependencies
-        embedding_service = get_embedding_service()
+        # Initialize dependencies (embeddings are requested over HTTP)
         vector_service = get_vector_index_service()
         
         # Initialize ingestion service
         ingestion_service = get_ingestion_service(
-            embedding_service=embedding_service,
-            vector_service=vector_service
+            vector_index_service=vector_service
         )
         
         logger.success("Ingestion Service started successfully")
@@ -126,7 +124,7 @@ async def health_check():
         ingestion_service_ready=ingestion_service is not None,
         ocr_service_available=True,  # Assume available
         embedding_service_available=ingestion_service.embedding_service is not None if ingestion_service else False,
-        vector_index_available=ingestion_service.vector_service is not None if ingestion_service else False
+        vector_index_available=ingestion_service.vector_index_service is not None if ingestion_service else False
     )
 
 
@@ -255,6 +253,64 @@ async def ingest_pdf(
         )
 
 
+@app.post("/ingest/document", response_model=IngestionResult)
+async def ingest_document(
+    document: CaseLawDocument,
+    user: dict = Depends(verify_token)
+):
+    """
+    Ingest a case law document that is already structured, skipping OCR
+    and section parsing.
+    
+    Requires authentication.
+    
+    Example:
+        POST /ingest/document
+        Authorization: Bearer <token>
+        {
+            "case_name": "Smith v. Jones",
+            "year": 2021,
+            "facts": "...",
+            "issue": "...",
+            "reasoning": "...",
+            "holding": "...",
+            "final_judgment": "Affirmed"
+        }
+    """
+    if ingestion_service is None:
+        raise HTTPException(
+            status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
+            detail="Ingestion service not initialized"
+        )
+    
+    try:
+        result = await ingestion_service.ingest_document(document)
+        
+        # Update statistics
+        ingestion_stats["total_documents"] += 1
+        ingestion_stats["total_time"] += result.processing_time_seconds
+        ingestion_stats["total_vectors"] += len(result.vector_ids)
+        
+        if result.status == "success":
+            ingestion_stats["successful"] += 1
+        else:
+            ingestion_stats["failed"] += 1
+        
+        logger.info(f"Ingested: {result.case_name} "
+                   f"(status: {result.status}, time: {result.processing_time_seconds:.2f}s)")
+        
+        return result
+    
+    except Exception as e:
+        ingestion_stats["total_documents"] += 1
+        ingestion_stats["failed"] += 1
+        logger.error(f"Error ingesting document: {e}")
+        raise HTTPException(
+            status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
+            detail=f"Ingestion failed: {str(e)}"
+        )
+
+
 @app.post("/ingest/batch")
 async def ingest_batch(
     request: BatchIngestionRequest,
@@ -369,6 +425,90 @@ async def ingest_batch(
         )
 
 
+# Declared before /documents/{document_id} so "by-citation" isn't taken as an id
+@app.get("/documents/by-citation", response_model=CaseLawDocument)
+async def get_document_by_citation(
+    citation: Optional[str] = Query(None, description="Citation as the client wrote it"),
+    key: Optional[str] = Query(None, alias="citation_key", description="Normalized citation"),
+    user: dict = Depends(verify_token)
+):
+    """
+    Get a stored case law document by its reporter citation.
+    
+    Requires authentication. Matching uses the normalized key, so
+    "478 A.2d 202" and "478 A. 2d 202" find the same case.
+    
+    Example:
+        GET /documents/by-citation?citation=478%20A.2d%20202&citation_key=478%20A2D%20202
+        Authorization: Bearer <token>
+    """
+    if ingestion_service is None:
+        raise HTTPException(
+            status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
+            detail="Ingestion service not initialized"
+        )
+    
+    key = key or (citation_key(citation) if citation else None)
+    if not key:
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="citation or citation_key is required"
+        )
+    
+    try:
+        document = ingestion_service.vector_index_service.get_document_by_citation(key)
+    except Exception as e:
+        logger.error(f"Error getting document by citation: {e}")
+        raise HTTPException(
+            status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
+            detail=f"Failed to get document: {str(e)}"
+        )
+    
+    if document is None:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND,
+            detail=f"No document with citation {citation or key}"
+        )
+    return document
+
+
+@app.get("/documents/{document_id}", response_model=CaseLawDocument)
+async def get_document(
+    document_id: str,
+    user: dict = Depends(verify_token)
+):
+    """
+    Get a stored case law document by its document_id.
+    
+    Requires authentication.
+    
+    Example:
+        GET /documents/abc-123-def-456
+        Authorization: Bearer <token>
+    """
+    if ingestion_service is None:
+        raise HTTPException(
+            status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
+            detail="Ingestion service not initialized"
+        )
+    
+    try:
+        document = ingestion_service.vector_index_service.get_document(document_id)
+    except Exception as e:
+        logger.error(f"Error getting document: {e}")
+        raise HTTPException(
+            status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
+            detail=f"Failed to get document: {str(e)}"
+        )
+    
+    if document is None:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND,
+            detail=f"Document {document_id} not found"
+        )
+    return document
+
+
 @app.delete("/documents/{document_id}")
 async def delete_document(
     document_id: str,
@@ -391,7 +531,7 @@ async def delete_document(
     
     try:
         # Delete from vector index
-        ingestion_service.vector_service.delete_document(document_id)
+        ingestion_service.vector_index_service.delete_document(document_id)
         
         logger.info(f"Deleted document: {document_id}")
         
@@ -11,7 +11,7 @@ from loguru import logger
 import time
 
 from shared.models import CaseLawDocument, IngestionResult, ValidationResult
-from shared.validators import validate_case_law_document
+from shared.validators import citation_key, validate_case_law_document
 
 
 class IngestionService:
@@ -88,43 +88,8 @@ class IngestionService:
             logger.info("Step 2: Parsing case law structure...")
             case_law_doc = self.parse_case_law(text)
             
-            # Step 3: Validate document
-            logger.info("Step 3: Validating document...")
-            validation_result = validate_case_law_document(case_law_doc)
-            
-            if not validation_result.is_valid:
-                logger.warning(f"Validation failed: {len(validation_result.errors)} errors")
-                processing_time = time.time() - start_time
-                return IngestionResult(
-                    document_id=case_law_doc.document_id,
-                    case_name=case_law_doc.case_name,
-                    status="failed",
-                    sections_extracted=[],
-                    validation_errors=validation_result.errors,
-                    processing_time_seconds=processing_time,
-                    vector_ids=[]
-                )
-            
-            # Step 4: Generate embeddings
-            logger.info("Step 4: Generating embeddings...")
-            section_embeddings = await self._generate_embeddings(case_law_doc)
-            
-            # Step 5: Store in vector index
-            logger.info("Step 5: Storing in vector index...")
-            vector_ids = await self._store_in_vector_index(case_law_doc, section_embeddings)
-            
-            processing_time = time.time() - start_time
-            logger.success(f"Successfully ingested document in {processing_time:.2f}s")
-            
-            return IngestionResult(
-                document_id=case_law_doc.document_id,
-                case_name=case_law_doc.case_name,
-                status="success",
-                sections_extracted=list(section_embeddings.keys()),
-                validation_errors=[],
-                processing_time_seconds=processing_time,
-                vector_ids=vector_ids
-            )
+            # Steps 3-5: Validate, embed and index
+            return await self.ingest_document(case_law_doc, start_time=start_time)
         
         except Exception as e:
             logger.error(f"Ingestion failed: {e}")
@@ -139,6 +104,62 @@ class IngestionService:
                 vector_ids=[]
             )
     
+    async def ingest_document(
+        self,
+        case_law_doc: CaseLawDocument,
+        start_time: Optional[float] = None
+    ) -> IngestionResult:
+        """
+        Validate, embed and index an already-structured case law document.
+        
+        Args:
+            case_law_doc: Parsed or client-supplied CaseLawDocument
+            start_time: When processing began, for the reported duration
+        
+        Returns:
+            IngestionResult with processing details
+        """
+        if start_time is None:
+            start_time = time.time()
+        
+        # Step 3: Validate document
+        logger.info("Step 3: Validating document...")
+        validation_result = validate_case_law_document(case_law_doc)
+        
+        if not validation_result.is_valid:
+            logger.warning(f"Validation failed: {len(validation_result.errors)} errors")
+            processing_time = time.time() - start_time
+            return IngestionResult(
+                document_id=case_law_doc.document_id,
+                case_name=case_law_doc.case_name,
+                status="failed",
+                sections_extracted=[],
+                validation_errors=validation_result.errors,
+                processing_time_seconds=processing_time,
+                vector_ids=[]
+            )
+        
+        # Step 4: Generate embeddings
+        logger.info("Step 4: Generating embeddings...")
+        section_embeddings = await self._generate_embeddings(case_law_doc)
+        
+        # Step 5: Store in vector index
+        logger.info("Step 5: Storing in vector index...")
+        vector_ids = await self._store_in_vector_index(case_law_doc, section_embeddings)
+        
+        processing_time = time.time() - start_time
+        logger.success(f"Successfully ingested document in {processing_time:.2f}s")
+        
+        return IngestionResult(
+            document_id=case_law_doc.document_id,
+            case_name=case_law_doc.case_name,
+            status="success",
+            sections_extracted=list(section_embeddings.keys()),
+            validation_errors=[],
+            processing_time_seconds=processing_time,
+            vector_ids=vector_ids
+        )
+    
     async def _extract_text_from_pdf(
         self,
         pdf_path: str = None,
@@ -379,7 +400,11 @@ class IngestionService:
             "year": doc.year,
             "court": doc.court,
             "opinion_type": doc.opinion_type,
-            "final_judgment": doc.final_judgment
+            "final_judgment": doc.final_judgment,
+            # Normalized, so GET /documents/by-citation matches any spelling
+            "citation_key": citation_key(doc.citation) if doc.citation else None,
+            # The whole document, so GET /documents/{id} can return it
+            "document": doc.model_dump(mode="json")
         }
         
         # Convert embeddings to numpy arrays
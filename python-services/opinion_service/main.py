@@ -5,11 +5,13 @@ Provides REST API endpoints for judicial opinion generation
 
 from fastapi import FastAPI, HTTPException, status, Depends
 from fastapi.middleware.cors import CORSMiddleware
+from fastapi.responses import StreamingResponse
 from pydantic import BaseModel, Field
 from typing import Optional, Dict
 from loguru import logger
 import sys
 import time
+import json
 
 from opinion_service.service import get_opinion_generator, OpinionGenerator
 from shared.models import OpinionRequest, GeneratedOpinion
@@ -233,6 +235,80 @@ async def generate_opinion(
         )
 
 
+@app.post("/generate/opinion/stream")
+async def generate_opinion_stream(
+    request: OpinionRequest,
+    user: dict = Depends(verify_token)
+):
+    """
+    Generate a judicial opinion, streamed as newline-delimited JSON.
+    
+    Requires authentication. Inputs are sanitized as for /generate/opinion.
+    
+    Emits {"token": "..."} lines as text is generated, then one
+    {"opinion": {...}} line with the structured GeneratedOpinion, or an
+    {"error": "..."} line if generation fails part-way.
+    
+    Example:
+        POST /generate/opinion/stream
+        Authorization: Bearer <token>
+        {
+            "case_context": {
+                "facts": "The landlord failed to repair the heating system...",
+                "issue": "Whether the landlord breached the warranty of habitability"
+            },
+            "opinion_type": "per_curiam"
+        }
+    """
+    if opinion_generator is None:
+        raise HTTPException(
+            status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
+            detail="Opinion service not initialized"
+        )
+    
+    # Checked before streaming starts, so a bad request is still a plain 400
+    if 'facts' not in request.case_context or 'issue' not in request.case_context:
+        raise HTTPException(
+            status_code=status.HTTP_400_BAD_REQUEST,
+            detail="case_context must include 'facts' and 'issue'"
+        )
+    
+    sanitized_context = {
+        key: sanitize_llm_input(value) if isinstance(value, str) else value
+        for key, value in request.case_context.items()
+    }
+    
+    async def lines():
+        start_time = time.time()
+        try:
+            async for item in opinion_generator.stream_opinion(
+                case_context=sanitized_context,
+                opinion_type=request.opinion_type,
+                max_precedents=request.max_precedents
+            ):
+                if "opinion" in item:
+                    opinion = item["opinion"]
+                    processing_time_ms = (time.time() - start_time) * 1000
+                    
+                    # Update statistics
+                    opinion_stats["total_opinions"] += 1
+                    opinion_stats["total_time_ms"] += processing_time_ms
+                    opinion_stats["total_precedents"] += opinion.generation_metadata.get('precedents_used', 0)
+                    
+                    opinion_type_key = request.opinion_type if request.opinion_type in opinion_stats["opinion_types"] else "other"
+                    opinion_stats["opinion_types"][opinion_type_key] += 1
+                    
+                    logger.info(f"Opinion streamed: {request.opinion_type} "
+                               f"(time: {processing_time_ms:.0f}ms)")
+                    item = {"opinion": opinion.model_dump(mode="json")}
+                yield json.dumps(item) + "\n"
+        except Exception as e:
+            logger.error(f"Error streaming opinion: {e}")
+            yield json.dumps({"error": f"Opinion generation failed: {str(e)}"}) + "\n"
+    
+    return StreamingResponse(lines(), media_type="application/x-ndjson")
+
+
 @app.post("/generate/section")
 async def generate_section(
     section_type: str = Field(..., description="Section type to generate"),
@@ -5,7 +5,9 @@ Generates Supreme Court-style opinions using retrieved precedents and LLM
 
 import os
 import re
-from typing import List, Dict, Optional
+import time
+import json
+from typing import AsyncIterator, List, Dict, Optional, Tuple
 from loguru import logger
 import httpx
 
@@ -128,36 +130,18 @@ class OpinionGenerator:
             )
             
             # Step 3: Generate opinion using LLM
-            generated_text = await self._call_llm(prompt)
+            started = time.perf_counter()
+            generated_text, mock = await self._call_llm(prompt)
+            generation_time_ms = (time.perf_counter() - started) * 1000
             
-            # Step 4: Parse and structure the opinion
-            sections = self._parse_opinion_sections(generated_text)
-            
-            # Step 5: Format citations
-            cited_precedents = self._extract_cited_cases(generated_text, precedents)
-            
-            # Step 6: Create final opinion with disclaimer
-            full_text = self._format_final_opinion(
+            # Steps 4-6: Structure, cite and add the disclaimer
+            opinion = self._build_opinion(
                 generated_text,
                 case_context,
-                opinion_type
-            )
-            
-            opinion = GeneratedOpinion(
-                full_text=full_text,
-                sections=sections,
-                cited_precedents=cited_precedents,
-                generation_metadata={
-                    "model": self.model,
-                    "temperature": self.temperature,
-                    "precedents_used": len(precedents),
-                    "opinion_type": opinion_type
-                },
-                disclaimer=(
-                    "This opinion is AI-generated for research and academic purposes only. "
-                    "It does not constitute legal advice and should not be relied upon for "
-                    "actual legal proceedings."
-                )
+                opinion_type,
+                precedents,
+                generation_time_ms,
+                mock
             )
             
             logger.success("Opinion generated successfully")
@@ -167,6 +151,94 @@ class OpinionGenerator:
             logger.error(f"Opinion generation failed: {e}")
             raise
     
+    async def stream_opinion(
+        self,
+        case_context: Dict,
+        opinion_type: str = "per_curiam",
+        max_precedents: int = None
+    ) -> AsyncIterator[Dict]:
+        """
+        Generate an opinion like generate_opinion, yielding it as it is written.
+        
+        Yields {"token": text} for each piece of generated text, then one
+        {"opinion": GeneratedOpinion} once the full text is structured.
+        Errors are raised to the caller, which reports them in-stream.
+        """
+        logger.info(f"Streaming {opinion_type} opinion")
+        
+        precedents = await self._retrieve_precedents(
+            case_context,
+            max_precedents or self.max_precedents
+        )
+        prompt = self._build_opinion_prompt(case_context, precedents, opinion_type)
+        
+        started = time.perf_counter()
+        pieces = []
+        mock = False
+        async for token, mock in self._stream_llm(prompt):
+            pieces.append(token)
+            yield {"token": token}
+        generation_time_ms = (time.perf_counter() - started) * 1000
+        
+        opinion = self._build_opinion(
+            "".join(pieces),
+            case_context,
+            opinion_type,
+            precedents,
+            generation_time_ms,
+            mock
+        )
+        logger.success("Opinion streamed successfully")
+        yield {"opinion": opinion}
+    
+    def _build_opinion(
+        self,
+        generated_text: str,
+        case_context: Dict,
+        opinion_type: str,
+        precedents: List[SearchResult],
+        generation_time_ms: float,
+        mock: bool
+    ) -> GeneratedOpinion:
+        """
+        Turn generated text into a GeneratedOpinion.
+        
+        Returns:
+            GeneratedOpinion with sections, citations and disclaimer
+        """
+        # Step 4: Parse and structure the opinion
+        sections = self._parse_opinion_sections(generated_text)
+        
+        # Step 5: Format citations
+        cited_precedents = self._extract_cited_cases(generated_text, precedents)
+        
+        # Step 6: Create final opinion with disclaimer
+        full_text = self._format_final_opinion(
+            generated_text,
+            case_context,
+            opinion_type
+        )
+        
+        return GeneratedOpinion(
+            full_text=full_text,
+            sections=sections,
+            cited_precedents=cited_precedents,
+            generation_metadata={
+                "model": self.model,
+                "temperature": self.temperature,
+                "precedents_used": len(precedents),
+                "opinion_type": opinion_type,
+                "generation_time_ms": round(generation_time_ms, 1),
+                # True when the LLM was unavailable and canned text was used
+                "mock": mock
+            },
+            disclaimer=(
+                "This opinion is AI-generated for research and academic purposes only. "
+                "It does not constitute legal advice and should not be relied upon for "
+                "actual legal proceedings."
+            )
+        )
+    
     async def _retrieve_precedents(
         self,
         case_context: Dict,
@@ -326,7 +398,80 @@ Generate the complete opinion now:"""
         
         return "\n".join(formatted)
     
-    async def _call_llm(self, prompt: str) -> str:
+    def _llm_payload(self, prompt: str) -> Dict:
+        """Chat completion request body for the opinion prompt"""
+        return {
+            "model": self.model,
+            "messages": [
+                {
+                    "role": "system",
+                    "content": "You are a Supreme Court justice writing formal judicial opinions."
+                },
+                {
+                    "role": "user",
+                    "content": prompt
+                }
+            ],
+            "temperature": self.temperature,
+            "max_tokens": self.max_tokens,
+            "top_p": 0.9,
+            "frequency_penalty": 0.3
+        }
+    
+    async def _stream_llm(self, prompt: str) -> AsyncIterator[Tuple[str, bool]]:
+        """
+        Call the LLM API with streaming on, yielding text as it arrives.
+        
+        Falls back to the mock opinion, split by line, when no API key is
+        configured or the API fails before sending any text.
+        
+        Yields:
+            (text, whether it is the mock opinion)
+        """
+        if not self.llm_api_key:
+            logger.warning("No LLM API key configured, streaming mock opinion")
+            for line in self._generate_mock_opinion().splitlines(keepends=True):
+                yield line, True
+            return
+        
+        headers = {
+            "Authorization": f"Bearer {self.llm_api_key}",
+            "Content-Type": "application/json"
+        }
+        payload = {**self._llm_payload(prompt), "stream": True}
+        
+        sent_any = False
+        try:
+            async with httpx.AsyncClient(timeout=self.timeout) as client:
+                async with client.stream(
+                    "POST",
+                    self.llm_api_url,
+                    headers=headers,
+                    json=payload
+                ) as response:
+                    response.raise_for_status()
+                    # Server-sent events: "data: {...}" lines, then "data: [DONE]"
+                    async for line in response.aiter_lines():
+                        if not line.startswith("data:"):
+                            continue
+                        data = line[len("data:"):].strip()
+                        if data == "[DONE]":
+                            break
+                        choices = json.loads(data).get("choices") or [{}]
+                        token = (choices[0].get("delta") or {}).get("content")
+                        if token:
+                            sent_any = True
+                            yield token, False
+        except httpx.HTTPError as e:
+            # Text already sent can't be taken back, so only fall back before it
+            if sent_any:
+                raise
+            logger.error(f"LLM API error: {e}")
+            logger.warning("Falling back to mock opinion")
+            for line in self._generate_mock_opinion().splitlines(keepends=True):
+                yield line, True
+    
+    async def _call_llm(self, prompt: str) -> Tuple[str, bool]:
         """
         Call LLM API to generate opinion text.
         
@@ -334,11 +479,11 @@ Generate the complete opinion now:"""
             prompt: Formatted prompt
         
         Returns:
-            Generated opinion text
+            Generated opinion text, and whether it is the mock opinion
         """
         if not self.llm_api_key:
             logger.warning("No LLM API key configured, returning mock opinion")
-            return self._generate_mock_opinion()
+            return self._generate_mock_opinion(), True
         
         async with httpx.AsyncClient(timeout=self.timeout) as client:
             try:
@@ -347,23 +492,7 @@ Generate the complete opinion now:"""
                     "Content-Type": "application/json"
                 }
                 
-                payload = {
-                    "model": self.model,
-                    "messages": [
-                        {
-                            "role": "system",
-                            "content": "You are a Supreme Court justice writing formal judicial opinions."
-                        },
-                        {
-                            "role": "user",
-                            "content": prompt
-                        }
-                    ],
-                    "temperature": self.temperature,
-                    "max_tokens": self.max_tokens,
-                    "top_p": 0.9,
-                    "frequency_penalty": 0.3
-                }
+                payload = self._llm_payload(prompt)
                 
                 response = await client.post(
                     self.llm_api_url,
@@ -376,12 +505,12 @@ Generate the complete opinion now:"""
                 generated_text = data['choices'][0]['message']['content']
                 
                 logger.debug(f"LLM generated {len(generated_text)} characters")
-                return generated_text
+                return generated_text, False
             
             except httpx.HTTPError as e:
                 logger.error(f"LLM API error: {e}")
                 logger.warning("Falling back to mock opinion")
-                return self._generate_mock_opinion()
+                return self._generate_mock_opinion(), True
     
     def _generate_mock_opinion(self) -> str:
         """
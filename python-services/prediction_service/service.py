@@ -8,7 +8,7 @@ from typing import List, Dict, Tuple
 from collections import Counter
 from loguru import logger
 
-from shared.models import OutcomePrediction, SearchResult
+from shared.models import OutcomePrediction, RationaleFactor, SearchResult
 
 
 class OutcomePredictor:
@@ -106,8 +106,12 @@ class OutcomePredictor:
             # Step 2: Extract outcomes and calculate weighted probabilities
             probabilities = self._calculate_outcome_probabilities(similar_cases)
             
-            # Step 3: Determine predicted outcome
-            predicted_outcome = max(probabilities, key=probabilities.get)
+            # Step 3: Determine predicted outcome; ties go to the alphabetically
+            # first label (the gateway then applies OUTCOME_TIE_PRIORITY)
+            top = max(probabilities.values())
+            predicted_outcome = min(
+                label for label, p in probabilities.items() if top - p <= 1e-9
+            )
             confidence = probabilities[predicted_outcome]
             
             # Step 4: Get supporting cases
@@ -135,7 +139,8 @@ class OutcomePredictor:
                 probabilities=probabilities,
                 confidence=confidence,
                 supporting_cases=supporting_cases,
-                explanation=explanation
+                explanation=explanation,
+                rationale=self._build_rationale(similar_cases)
             )
             
             logger.success(f"Predicted outcome: {predicted_outcome} "
@@ -235,6 +240,43 @@ class OutcomePredictor:
         
         return supporting
     
+    def _build_rationale(
+        self,
+        similar_cases: List[SearchResult],
+        max_factors: int = 5
+    ) -> List[RationaleFactor]:
+        """
+        Break the weighted vote down into its largest contributions.
+        
+        Args:
+            similar_cases: All similar cases
+            max_factors: Maximum number of factors to return
+        
+        Returns:
+            Factors ordered by weight, each weight being the case's share
+            of the total vote
+        """
+        voting = [
+            case for case in similar_cases
+            if case.metadata.get('final_judgment') in ('Affirmed', 'Reversed', 'Remanded')
+        ]
+        total_weight = sum(case.similarity_score for case in voting)
+        if total_weight <= 0:
+            return []
+        
+        voting.sort(key=lambda case: case.similarity_score, reverse=True)
+        return [
+            RationaleFactor(
+                factor=(
+                    f"Similar case ({case.similarity_score:.0%} match) "
+                    f"was {case.metadata['final_judgment'].lower()}"
+                ),
+                weight=case.similarity_score / total_weight,
+                cited_case=f"{case.case_name} ({case.year})"
+            )
+            for case in voting[:max_factors]
+        ]
+    
     def _generate_explanation(
         self,
         predicted_outcome: str,
@@ -54,6 +54,8 @@ class SearchResponse(BaseModel):
     total_results: int
     search_time_ms: float
     query: str
+    # Metric behind each result's `distance`, e.g. "cosine"
+    distance_metric: Optional[str] = None
 
 
 class SearchStatsResponse(BaseModel):
@@ -64,6 +66,11 @@ class SearchStatsResponse(BaseModel):
     cache_hit_rate: float
 
 
+class OutcomeCountsResponse(BaseModel):
+    """Response model for outcome counts"""
+    counts: Dict[str, int]
+
+
 class HealthResponse(BaseModel):
     """Response model for health check"""
     status: str
@@ -126,6 +133,34 @@ async def get_stats():
         )
 
 
+@app.get("/stats/outcomes", response_model=OutcomeCountsResponse)
+async def get_outcome_counts(
+    court: Optional[str] = None,
+    year_from: Optional[int] = None,
+    year_to: Optional[int] = None
+):
+    """Count indexed cases by final judgment, optionally filtered"""
+    if search_engine is None or search_engine.vector_index_service is None:
+        raise HTTPException(
+            status_code=status.HTTP_503_SERVICE_UNAVAILABLE,
+            detail="Search engine not initialized"
+        )
+    
+    try:
+        counts = search_engine.vector_index_service.count_outcomes(
+            court=court,
+            year_from=year_from,
+            year_to=year_to
+        )
+        return OutcomeCountsResponse(counts=counts)
+    except Exception as e:
+        logger.error(f"Error counting outcomes: {e}")
+        raise HTTPException(
+            status_code=status.HTTP_500_INTERNAL_SERVER_ERROR,
+            detail=f"Failed to count outcomes: {str(e)}"
+        )
+
+
 @app.post("/search", response_model=SearchResponse)
 async def search(
     request: SearchRequest,
@@ -168,7 +203,8 @@ async def search(
             results=results,
             total_results=len(results),
             search_time_ms=round(search_time_ms, 2),
-            query=request.query
+            query=request.query,
+            distance_metric=search_engine.distance_metric
         )
     
     except ValueError as e:
@@ -224,7 +260,8 @@ async def search_by_facts(
             results=results,
             total_results=len(results),
             search_time_ms=round(search_time_ms, 2),
-            query=request.query
+            query=request.query,
+            distance_metric=search_engine.distance_metric
         )
     
     except ValueError as e:
@@ -280,7 +317,8 @@ async def search_by_reasoning(
             results=results,
             total_results=len(results),
             search_time_ms=round(search_time_ms, 2),
-            query=request.query
+            query=request.query,
+            distance_metric=search_engine.distance_metric
         )
     
     except ValueError as e:
@@ -325,6 +325,7 @@ class SemanticSearchEngine:
             
             # Extract snippet (first 500 chars of text content)
             snippet = payload.get('text_content', '')[:500]
+            char_start, char_end = (0, len(snippet)) if snippet else (None, None)
             if not snippet:
                 # Fallback: create snippet from available data
                 snippet = f"Case: {payload.get('case_name', 'Unknown')}"
@@ -337,6 +338,9 @@ class SemanticSearchEngine:
                 section_type=payload.get('section_type', 'unknown'),
                 similarity_score=result.get('adjusted_score', result['score']),
                 snippet=snippet,
+                char_start=char_start,
+                char_end=char_end,
+                distance=self._raw_distance(result['score']),
                 metadata=payload
             )
             
@@ -344,6 +348,27 @@ class SemanticSearchEngine:
         
         return search_results
     
+    @property
+    def distance_metric(self) -> Optional[str]:
+        """The vector index's distance metric, lower-cased (e.g. "cosine")"""
+        if not self.vector_index_service:
+            return None
+        return self.vector_index_service.distance.lower()
+
+    def _raw_distance(self, score: float) -> Optional[float]:
+        """
+        Convert a Qdrant score back to a distance.
+
+        Qdrant reports cosine *similarity* and Euclidean *distance*; dot
+        product has no distance form, so it gets None.
+        """
+        metric = self.distance_metric
+        if metric == "cosine":
+            return 1.0 - score
+        if metric == "euclid":
+            return score
+        return None
+
     def get_search_stats(self) -> Dict:
         """
         Get search engine statistics.
@@ -31,6 +31,7 @@ class CaseLawDocument(BaseModel):
     respondent: Optional[str] = None
     lower_court: Optional[str] = None
     procedural_history: Optional[str] = None
+    citation: Optional[str] = None  # Reporter citation, e.g. "478 A.2d 202"
     
     # System metadata
     document_id: str = Field(default_factory=lambda: str(uuid.uuid4()))
@@ -90,6 +91,11 @@ class SearchResult(BaseModel):
     section_type: str
     similarity_score: float = Field(..., ge=0.0, le=1.0)
     snippet: str = Field(..., max_length=500)
+    # Snippet position within the section text (char_end exclusive)
+    char_start: Optional[int] = None
+    char_end: Optional[int] = None
+    # Raw vector distance before normalization and re-ranking, when known
+    distance: Optional[float] = None
     full_document: Optional[CaseLawDocument] = None
     metadata: Dict
     
@@ -99,6 +105,14 @@ class SearchResult(BaseModel):
         }
 
 
+class RationaleFactor(BaseModel):
+    """One weighted factor behind a prediction"""
+    
+    factor: str
+    weight: float = Field(..., ge=0.0, le=1.0)
+    cited_case: Optional[str] = None
+
+
 class OutcomePrediction(BaseModel):
     """Predicted judicial outcome"""
     
@@ -107,6 +121,8 @@ class OutcomePrediction(BaseModel):
     confidence: float = Field(..., ge=0.0, le=1.0)
     supporting_cases: List[str]
     explanation: str
+    # Structured breakdown of the explanation, when available
+    rationale: Optional[List[RationaleFactor]] = None
     
     @validator('probabilities')
     def validate_probabilities(cls, v):
@@ -2,10 +2,35 @@
 Custom validators and validation utilities for case law documents.
 """
 
-from typing import Dict, List, Tuple
+import re
+from typing import Dict, List, Optional, Tuple
 from .models import CaseLawDocument, ValidationResult
 
 
+# volume, then a reporter starting with a letter, then the first page
+_CITATION_PATTERN = re.compile(
+    r"\b(\d{1,5})\s+([A-Za-z][A-Za-z0-9.'&\s]*?[A-Za-z.])\s*(\d{1,6})\b"
+)
+
+
+def citation_key(citation: str) -> Optional[str]:
+    """
+    Comparison form of a reporter citation: "478 A2D 202" for
+    "478 A.2d 202", "478 A. 2d 202" and "478 a.2d 202" alike.
+    
+    Must match the gateway's Citation::key (rust-api/src/citation.rs),
+    which sends this form to GET /documents/by-citation.
+    
+    Returns None when the text holds no citation.
+    """
+    match = _CITATION_PATTERN.search(citation)
+    if match is None:
+        return None
+    volume, reporter, page = match.groups()
+    reporter = "".join(c for c in reporter if c.isascii() and c.isalnum())
+    return f"{int(volume)} {reporter.upper()} {int(page)}"
+
+
 def validate_case_law_document(doc: CaseLawDocument) -> ValidationResult:
     """
     Comprehensive validation of a CaseLawDocument.
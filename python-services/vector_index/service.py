@@ -373,6 +373,139 @@ class VectorIndexService:
             logger.error(f"Failed to get collection info: {e}")
             raise
     
+    def count_outcomes(
+        self,
+        court: Optional[str] = None,
+        year_from: Optional[int] = None,
+        year_to: Optional[int] = None
+    ) -> Dict[str, int]:
+        """
+        Count indexed documents by final_judgment.
+        
+        Each document is stored as one point per section, so points are
+        de-duplicated on document_id before counting.
+        
+        Args:
+            court: Only count documents from this court
+            year_from: Earliest year (inclusive)
+            year_to: Latest year (inclusive)
+        
+        Returns:
+            Dictionary mapping final_judgment to document count
+        """
+        must_conditions = []
+        if court:
+            must_conditions.append(
+                models.FieldCondition(
+                    key="court",
+                    match=models.MatchValue(value=court)
+                )
+            )
+        if year_from is not None or year_to is not None:
+            must_conditions.append(
+                models.FieldCondition(
+                    key="year",
+                    range=models.Range(gte=year_from, lte=year_to)
+                )
+            )
+        scroll_filter = models.Filter(must=must_conditions) if must_conditions else None
+        
+        outcomes: Dict[str, str] = {}
+        offset = None
+        try:
+            while True:
+                points, offset = self.client.scroll(
+                    collection_name=self.collection_name,
+                    scroll_filter=scroll_filter,
+                    limit=1000,
+                    offset=offset,
+                    with_payload=["document_id", "final_judgment"],
+                    with_vectors=False
+                )
+                for point in points:
+                    payload = point.payload or {}
+                    doc_id = payload.get("document_id")
+                    if doc_id is not None:
+                        outcomes[doc_id] = payload.get("final_judgment") or "Unknown"
+                if offset is None:
+                    break
+        except Exception as e:
+            logger.error(f"Failed to count outcomes: {e}")
+            raise
+        
+        counts: Dict[str, int] = {}
+        for outcome in outcomes.values():
+            counts[outcome] = counts.get(outcome, 0) + 1
+        return counts
+    
+    def get_document_by_citation(self, key: str) -> Optional[dict]:
+        """
+        Fetch the stored document whose citation normalizes to `key`.
+        
+        Args:
+            key: Normalized citation, e.g. "478 A2D 202"
+        
+        Returns:
+            The document as stored at ingestion, or None if none matches
+        """
+        try:
+            points, _ = self.client.scroll(
+                collection_name=self.collection_name,
+                scroll_filter=models.Filter(
+                    must=[
+                        models.FieldCondition(
+                            key="citation_key",
+                            match=models.MatchValue(value=key)
+                        )
+                    ]
+                ),
+                limit=1,
+                with_payload=["document"],
+                with_vectors=False
+            )
+        except Exception as e:
+            logger.error(f"Failed to get document by citation: {e}")
+            raise
+        
+        if not points:
+            return None
+        return (points[0].payload or {}).get("document")
+    
+    def get_document(self, doc_id: str) -> Optional[dict]:
+        """
+        Fetch the stored document for an indexed document_id.
+        
+        Every section point carries the same copy, so any one will do.
+        
+        Args:
+            doc_id: Document identifier
+        
+        Returns:
+            The document as stored at ingestion, or None if unknown
+        """
+        try:
+            points, _ = self.client.scroll(
+                collection_name=self.collection_name,
+                scroll_filter=models.Filter(
+                    must=[
+                        models.FieldCondition(
+                            key="document_id",
+                            match=models.MatchValue(value=doc_id)
+                        )
+                    ]
+                ),
+                limit=1,
+                with_payload=["document"],
+                with_vectors=False
+            )
+        except Exception as e:
+            logger.error(f"Failed to get document: {e}")
+            raise
+        
+        if not points:
+            return None
+        return (points[0].payload or {}).get("document")
+    
     def check_duplicate(self, case_name: str, year: int) -> Optional[str]:
         """
         Check if a document with the same case_name and year already exists.
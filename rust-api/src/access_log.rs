@@ -0,0 +1,64 @@
+//! One log line per request: method, path, status and duration
+//!
+//! Errors (4xx/5xx) are always logged; successes only for a random
+//! ACCESS_LOG_SAMPLE_RATE fraction of requests, to keep volume manageable.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+
+use crate::request_id::RequestId;
+use crate::state::AppState;
+
+pub async fn log_request(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .unwrap_or_default();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+    let status = response.status();
+    let elapsed_ms = started.elapsed().as_millis();
+
+    let level = if status.is_server_error() {
+        log::Level::Error
+    } else if status.is_client_error() {
+        log::Level::Warn
+    } else if sampled(state.config().access_log_sample_rate) {
+        log::Level::Info
+    } else {
+        return response;
+    };
+    log::log!(
+        level,
+        "{} {} {} {}ms request_id={}",
+        method,
+        path,
+        status.as_u16(),
+        elapsed_ms,
+        request_id
+    );
+    response
+}
+
+fn sampled(rate: f64) -> bool {
+    rate >= 1.0 || (rate > 0.0 && fastrand::f64() < rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_bounds_always_or_never_sample() {
+        assert!((0..100).all(|_| sampled(1.0)));
+        assert!((0..100).all(|_| !sampled(0.0)));
+    }
+}
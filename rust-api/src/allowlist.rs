@@ -0,0 +1,35 @@
+//! Host/scheme allowlist guarding every URL the gateway connects to (SSRF)
+
+use reqwest::Url;
+
+#[derive(Debug, Clone)]
+pub struct HostAllowlist {
+    hosts: Vec<String>,
+    schemes: Vec<String>,
+}
+
+impl HostAllowlist {
+    /// Host entries match exactly, or by suffix when written as `*.example.com`
+    pub fn new(hosts: Vec<String>, schemes: Vec<String>) -> Self {
+        let lower = |v: Vec<String>| v.into_iter().map(|s| s.to_ascii_lowercase()).collect();
+        Self {
+            hosts: lower(hosts),
+            schemes: lower(schemes),
+        }
+    }
+
+    pub fn permits(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+        self.schemes.iter().any(|s| s == url.scheme())
+            && self
+                .hosts
+                .iter()
+                .any(|allowed| match allowed.strip_prefix("*.") {
+                    Some(suffix) => host.ends_with(&format!(".{}", suffix)),
+                    None => *allowed == host,
+                })
+    }
+}
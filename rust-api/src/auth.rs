@@ -0,0 +1,72 @@
+//! Bearer-token authentication against the API_TOKENS configured for the gateway
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use std::collections::HashSet;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// The caller identified by a valid bearer token
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub name: String,
+    pub scopes: HashSet<String>,
+    pub max_in_flight: usize,
+}
+
+impl Principal {
+    /// Fail with 403 unless the token was granted `scope`
+    pub fn require_scope(&self, scope: &str) -> Result<(), ApiError> {
+        if self.scopes.contains(scope) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(format!(
+                "token {:?} lacks the {:?} scope",
+                self.name, scope
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Principal {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let presented = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or_else(|| ApiError::Unauthorized("missing bearer token".to_string()))?;
+
+        let config = state.config();
+        config
+            .api_tokens
+            .iter()
+            .find(|token| constant_time_eq(token.token.as_bytes(), presented.as_bytes()))
+            .map(|token| Principal {
+                name: token.name.clone(),
+                scopes: token.scopes.clone(),
+                max_in_flight: token.max_in_flight,
+            })
+            .ok_or_else(|| ApiError::Unauthorized("invalid bearer token".to_string()))
+    }
+}
+
+/// Compare without short-circuiting so response timing doesn't leak how much
+/// of a token matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
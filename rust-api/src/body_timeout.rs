@@ -0,0 +1,69 @@
+//! Whole-body read timeout for client requests (REQUEST_BODY_TIMEOUT_SECONDS)
+//!
+//! Downstream timeouts only start once the gateway has the request, so a
+//! client trickling an upload in a byte at a time would otherwise hold its
+//! handler indefinitely. The clock starts when the handler first reads the
+//! body, so time spent queued for admission isn't charged to the client.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use futures::{stream, StreamExt};
+use std::error::Error;
+use tokio::time::Instant;
+
+use crate::state::AppState;
+
+/// The body error a timed-out read fails with; extractors map it to 408
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("request body not received within {seconds}s")]
+pub struct BodyTimeout {
+    pub seconds: u64,
+}
+
+/// Middleware that fails body reads still unfinished after the timeout
+pub async fn limit_body_time(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let timeout = state.config().request_body_timeout;
+    if timeout.is_zero() {
+        return next.run(request).await;
+    }
+    let (parts, body) = request.into_parts();
+    let chunks = stream::unfold(
+        Some((body.into_data_stream(), None)),
+        move |reading| async move {
+            let (mut chunks, deadline) = reading?;
+            let deadline = deadline.unwrap_or_else(|| Instant::now() + timeout);
+            match tokio::time::timeout_at(deadline, chunks.next()).await {
+                Ok(Some(chunk)) => Some((chunk, Some((chunks, Some(deadline))))),
+                Ok(None) => None,
+                Err(_) => {
+                    let timed_out = BodyTimeout {
+                        seconds: timeout.as_secs(),
+                    };
+                    Some((Err(axum::Error::new(timed_out)), None))
+                }
+            }
+        },
+    );
+    next.run(Request::from_parts(parts, Body::from_stream(chunks)))
+        .await
+}
+
+/// The [`BodyTimeout`] somewhere in `error`'s source chain, if any
+pub fn cause(error: &(dyn Error + 'static)) -> Option<BodyTimeout> {
+    let mut current = Some(error);
+    while let Some(error) = current {
+        if let Some(timeout) = error.downcast_ref::<BodyTimeout>() {
+            return Some(*timeout);
+        }
+        current = error.source();
+    }
+    None
+}
@@ -0,0 +1,117 @@
+//! Signed webhook delivery for async analysis jobs
+//!
+//! Each POST carries `X-Callback-Timestamp` (Unix seconds) and
+//! `X-Callback-Signature: sha256=<hex>`, an HMAC-SHA256 over
+//! `"{timestamp}.{body}"` keyed with CALLBACK_SIGNING_SECRET. Receivers should
+//! recompute it and reject stale timestamps to prevent replays.
+
+use reqwest::Url;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::jobs::JobView;
+use crate::signing;
+use crate::state::AppState;
+use crate::timestamp;
+
+pub const TIMESTAMP_HEADER: &str = "x-callback-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-callback-signature";
+
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Separate from the downstream client so redirects can't steer a callback
+/// past the allowlist
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("callback HTTP client builds")
+    })
+}
+
+/// Check a client-supplied callback_url before accepting the job
+pub fn validate_url(config: &Config, raw: &str) -> Result<Url, ApiError> {
+    if config.callback_signing_secret.is_empty() {
+        return Err(ApiError::BadRequest(
+            "callback_url is not supported: CALLBACK_SIGNING_SECRET is not configured".to_string(),
+        ));
+    }
+    let url = Url::parse(raw)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid callback_url: {}", e)))?;
+    if !config.downstream_allowlist.permits(&url) {
+        return Err(ApiError::BadRequest(format!(
+            "callback_url host {:?} is not in the allowlist",
+            url.host_str().unwrap_or_default()
+        )));
+    }
+    Ok(url)
+}
+
+pub fn sign(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    signing::hmac_sha256_hex(secret, &[timestamp.to_string().as_bytes(), b".", body])
+}
+
+/// POST the finished job to `url`, retrying failures and non-2xx responses
+/// with exponential backoff up to CALLBACK_MAX_ATTEMPTS
+pub async fn deliver(state: &AppState, url: Url, job: &JobView) {
+    let config = state.config();
+    let body = serde_json::to_vec(job).expect("job views serialize to JSON");
+    let attempts = config.callback_max_attempts.max(1);
+    let mut backoff = config.callback_retry_base;
+
+    for attempt in 1..=attempts {
+        // Re-sign each attempt so the timestamp stays fresh
+        let now = timestamp::epoch_seconds();
+        let result = client()
+            .post(url.clone())
+            .timeout(ATTEMPT_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, now.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                sign(&config.callback_signing_secret, now, &body),
+            )
+            .body(body.clone())
+            .send()
+            .await;
+
+        match result {
+            Ok(response) if response.status().is_success() => {
+                state.metrics.incr("callback_deliveries_total");
+                return;
+            }
+            Ok(response) => log::warn!(
+                "Callback for job {} to {} returned {} (attempt {}/{})",
+                job.job_id,
+                url,
+                response.status(),
+                attempt,
+                attempts
+            ),
+            Err(e) => log::warn!(
+                "Callback for job {} to {} failed: {} (attempt {}/{})",
+                job.job_id,
+                url,
+                e,
+                attempt,
+                attempts
+            ),
+        }
+        if attempt < attempts {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+
+    state.metrics.incr("callback_failures_total");
+    log::error!(
+        "Giving up on callback for job {} to {} after {} attempts",
+        job.job_id,
+        url,
+        attempts
+    );
+}
@@ -0,0 +1,265 @@
+//! Reporter citations such as `478 A.2d 202`, for exact-match case lookup
+//!
+//! Researchers paste citations in many shapes: with the case name in front,
+//! a pin cite or court-and-year parenthetical after, and the reporter spaced
+//! or dotted however their style guide prefers. Parsing pulls out the volume,
+//! reporter and first page; [`Citation::key`] reduces the reporter to letters
+//! and digits so those variations compare equal.
+
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Citation {
+    pub volume: u32,
+    /// As written, with runs of whitespace collapsed, e.g. `A.2d` or `L. Ed. 2d`
+    pub reporter: String,
+    /// First page of the opinion; a pin cite after it is dropped
+    pub page: u32,
+}
+
+fn citation_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    // volume, then a reporter starting with a letter, then the first page
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\b(\d{1,5})\s+([A-Za-z][A-Za-z0-9.'&\s]*?[A-Za-z.])\s*(\d{1,6})\b").unwrap()
+    })
+}
+
+impl Citation {
+    /// Every citation in running text, in order of appearance. Loose by
+    /// design: prose like "12 months 2023" also matches, so use it to match
+    /// known citations against, not to list a document's authorities.
+    pub fn find_all(text: &str) -> Vec<Citation> {
+        citation_pattern()
+            .captures_iter(text)
+            .map(|captures| Citation::from_captures(&captures))
+            .collect()
+    }
+
+    fn from_captures(captures: &Captures) -> Citation {
+        // The pattern caps both numbers at six digits, well inside u32
+        let number = |i: usize| captures[i].parse::<u32>().expect("pattern matched digits");
+        Citation {
+            volume: number(1),
+            reporter: captures[2].split_whitespace().collect::<Vec<_>>().join(" "),
+            page: number(3),
+        }
+    }
+
+    /// Comparison form: `478 A2D 202` for `478 A.2d 202`, `478 A. 2d 202`
+    /// and `478 a.2d 202` alike
+    pub fn key(&self) -> String {
+        let reporter: String = self
+            .reporter
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect();
+        format!(
+            "{} {} {}",
+            self.volume,
+            reporter.to_ascii_uppercase(),
+            self.page
+        )
+    }
+}
+
+/// How [`normalize`] writes the reporters it recognizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CitationStyle {
+    /// Leave citations exactly as the text has them
+    AsWritten,
+    /// `478 A.2d 202`, `410 U.S. 113`, `35 L. Ed. 2d 147`
+    Bluebook,
+    /// Bluebook with the spaces inside the reporter dropped: `L.Ed.2d`
+    Compact,
+}
+
+impl FromStr for CitationStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "as_written" | "none" | "" => Ok(CitationStyle::AsWritten),
+            "bluebook" => Ok(CitationStyle::Bluebook),
+            "compact" => Ok(CitationStyle::Compact),
+            other => Err(format!(
+                "unknown citation style {:?}, expected as_written, bluebook or compact",
+                other
+            )),
+        }
+    }
+}
+
+/// Reporters [`normalize`] knows, by [`Citation::key`] form, with their
+/// Bluebook abbreviation. Single-letter reporters (`A.`, `P.`) are left
+/// out: "12 months 2023"-style prose matches too easily to rewrite them.
+const REPORTERS: &[(&str, &str)] = &[
+    ("US", "U.S."),
+    ("SCT", "S. Ct."),
+    ("LED", "L. Ed."),
+    ("LED2D", "L. Ed. 2d"),
+    ("F2D", "F.2d"),
+    ("F3D", "F.3d"),
+    ("F4TH", "F.4th"),
+    ("FSUPP", "F. Supp."),
+    ("FSUPP2D", "F. Supp. 2d"),
+    ("FSUPP3D", "F. Supp. 3d"),
+    ("A2D", "A.2d"),
+    ("A3D", "A.3d"),
+    ("P2D", "P.2d"),
+    ("P3D", "P.3d"),
+    ("NE2D", "N.E.2d"),
+    ("NE3D", "N.E.3d"),
+    ("NW2D", "N.W.2d"),
+    ("SE2D", "S.E.2d"),
+    ("SW2D", "S.W.2d"),
+    ("SW3D", "S.W.3d"),
+    ("SO2D", "So. 2d"),
+    ("SO3D", "So. 3d"),
+    ("CALRPTR", "Cal. Rptr."),
+    ("CALRPTR2D", "Cal. Rptr. 2d"),
+    ("CALRPTR3D", "Cal. Rptr. 3d"),
+    ("NYS2D", "N.Y.S.2d"),
+];
+
+/// Rewrite every citation whose reporter is in the known table in `style`,
+/// leaving anything else untouched. Returns the new text and how many
+/// citations were actually changed.
+pub fn normalize(text: &str, style: CitationStyle) -> (String, usize) {
+    if style == CitationStyle::AsWritten {
+        return (text.to_string(), 0);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut start = 0;
+    let mut changed = 0;
+    while let Some(captures) = citation_pattern().captures_at(text, start) {
+        let whole = captures.get(0).expect("group 0 always matches");
+        let citation = Citation::from_captures(&captures);
+        let Some(reporter) = known_reporter(&citation) else {
+            // The loose pattern may have started on a year or a count just
+            // before a real citation; resume right after this volume
+            start = captures.get(1).expect("volume always matches").end();
+            continue;
+        };
+        let styled = match style {
+            CitationStyle::Compact => reporter.replace(' ', ""),
+            _ => reporter.to_string(),
+        };
+        let replacement = format!("{} {} {}", citation.volume, styled, citation.page);
+        if replacement != whole.as_str() {
+            out.push_str(&text[copied..whole.start()]);
+            out.push_str(&replacement);
+            copied = whole.end();
+            changed += 1;
+        }
+        start = whole.end();
+    }
+    out.push_str(&text[copied..]);
+    (out, changed)
+}
+
+fn known_reporter(citation: &Citation) -> Option<&'static str> {
+    let key: String = citation
+        .reporter
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_uppercase();
+    REPORTERS
+        .iter()
+        .find(|(known, _)| *known == key)
+        .map(|(_, bluebook)| *bluebook)
+}
+
+impl FromStr for Citation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        citation_pattern()
+            .captures(s)
+            .map(|captures| Citation::from_captures(&captures))
+            .ok_or_else(|| format!("no reporter citation found in {:?}", s.trim()))
+    }
+}
+
+impl fmt::Display for Citation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.volume, self.reporter, self.page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Citation {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_bare_and_decorated_citations() {
+        let bare = parse("478 A.2d 202");
+        assert_eq!(bare.volume, 478);
+        assert_eq!(bare.reporter, "A.2d");
+        assert_eq!(bare.page, 202);
+
+        assert_eq!(parse("Hilder v. St. Peter, 478 A.2d 202 (Vt. 1984)"), bare);
+        assert_eq!(parse("478 A.2d 202, 208"), bare);
+        assert_eq!(parse("  478   A.2d   202 "), bare);
+        assert_eq!(parse("410 U.S. 113").reporter, "U.S.");
+        assert_eq!(parse("35 L. Ed. 2d 147").reporter, "L. Ed. 2d");
+    }
+
+    #[test]
+    fn key_ignores_reporter_spacing_punctuation_and_case() {
+        let key = parse("478 A.2d 202").key();
+        assert_eq!(key, "478 A2D 202");
+        assert_eq!(parse("478 A. 2d 202").key(), key);
+        assert_eq!(parse("478 a.2d 202").key(), key);
+        assert_eq!(parse("478 A2d 202").key(), key);
+    }
+
+    #[test]
+    fn finds_every_citation_in_running_text() {
+        let text = "The tenant relies on Hilder v. St. Peter, 478 A. 2d 202 (Vt. 1984), \
+                    and on Javins, 428 F.2d 1071, 1080, for the implied warranty.";
+        let keys: Vec<String> = Citation::find_all(text).iter().map(Citation::key).collect();
+        assert_eq!(keys, ["478 A2D 202", "428 F2D 1071"]);
+    }
+
+    #[test]
+    fn normalizes_known_reporters_and_leaves_the_rest() {
+        let text = "See 478 A. 2d 202 (Vt. 1984); 410 us 113; 35 L.Ed.2d 147, \
+                    and 12 Vt. 45, decided in 1984 and 428 F2d 1071.";
+        let (bluebook, changed) = normalize(text, CitationStyle::Bluebook);
+        assert_eq!(
+            bluebook,
+            "See 478 A.2d 202 (Vt. 1984); 410 U.S. 113; 35 L. Ed. 2d 147, \
+             and 12 Vt. 45, decided in 1984 and 428 F.2d 1071."
+        );
+        assert_eq!(changed, 4);
+
+        let (compact, changed) = normalize(&bluebook, CitationStyle::Compact);
+        assert!(compact.contains("35 L.Ed.2d 147"), "{}", compact);
+        assert!(compact.contains("478 A.2d 202"), "{}", compact);
+        assert_eq!(changed, 1);
+
+        assert_eq!(normalize(&bluebook, CitationStyle::Bluebook).1, 0);
+        assert_eq!(
+            normalize(text, CitationStyle::AsWritten),
+            (text.to_string(), 0)
+        );
+    }
+
+    #[test]
+    fn rejects_text_without_a_citation() {
+        assert!("Hilder v. St. Peter".parse::<Citation>().is_err());
+        assert!("478 202".parse::<Citation>().is_err());
+        assert!("".parse::<Citation>().is_err());
+    }
+}
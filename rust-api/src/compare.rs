@@ -0,0 +1,82 @@
+//! Term-level comparison of two passages, for explaining how a precedent
+//! relates to a set of facts
+
+use std::collections::HashSet;
+
+/// Words too common in legal prose to say anything about a case
+const STOPWORDS: &[&str] = &[
+    "about", "after", "against", "also", "been", "before", "being", "between", "both", "could",
+    "court", "does", "during", "each", "from", "have", "having", "into", "more", "must", "only",
+    "other", "over", "same", "shall", "should", "such", "than", "that", "their", "them", "then",
+    "there", "these", "they", "this", "those", "under", "upon", "were", "what", "when", "where",
+    "whether", "which", "while", "will", "with", "would",
+];
+
+/// Shorter words are mostly function words
+const MIN_TERM_CHARS: usize = 4;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Comparison {
+    pub shared: Vec<String>,
+    pub only_left: Vec<String>,
+    pub only_right: Vec<String>,
+}
+
+/// Distinct content terms in order of first appearance
+pub fn terms(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_TERM_CHARS)
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .filter(|word| seen.insert(word.clone()))
+        .collect()
+}
+
+/// Split the terms of `left` and `right` into those they share and those
+/// only one has, each list capped at `limit`
+pub fn compare(left: &str, right: &str, limit: usize) -> Comparison {
+    let left = terms(left);
+    let right = terms(right);
+    let in_left: HashSet<&str> = left.iter().map(String::as_str).collect();
+    let in_right: HashSet<&str> = right.iter().map(String::as_str).collect();
+
+    let pick = |terms: &[String], keep: &dyn Fn(&str) -> bool| -> Vec<String> {
+        terms
+            .iter()
+            .filter(|term| keep(term))
+            .take(limit)
+            .cloned()
+            .collect()
+    };
+    Comparison {
+        shared: pick(&left, &|term| in_right.contains(term)),
+        only_left: pick(&left, &|term| !in_right.contains(term)),
+        only_right: pick(&right, &|term| !in_left.contains(term)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms_skip_short_and_common_words() {
+        assert_eq!(
+            terms("The landlord failed to repair the heating; the Landlord appealed."),
+            ["landlord", "failed", "repair", "heating", "appealed"]
+        );
+    }
+
+    #[test]
+    fn compare_splits_shared_and_distinct_terms() {
+        let comparison = compare(
+            "Tenant withheld rent after the heating failed",
+            "Tenant withheld rent because the roof leaked",
+            2,
+        );
+        assert_eq!(comparison.shared, ["tenant", "withheld"]);
+        assert_eq!(comparison.only_left, ["heating", "failed"]);
+        assert_eq!(comparison.only_right, ["because", "roof"]);
+    }
+}
@@ -0,0 +1,213 @@
+//! Per-token caps on concurrent expensive requests, so one tenant cannot
+//! take all of the OCR capacity, and a bounded queue in front of the
+//! pipeline as a whole so bursts wait briefly instead of failing outright
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::auth::Principal;
+use crate::error::ApiError;
+use crate::metrics::Metrics;
+
+/// Gauge of requests currently waiting in the [`PipelineQueue`]
+pub const QUEUE_DEPTH_METRIC: &str = "pipeline_queue_depth";
+
+/// Both slots an analysis holds while it runs; dropping it frees them
+pub struct Admission {
+    _tenant: Option<OwnedSemaphorePermit>,
+    _pipeline: Option<OwnedSemaphorePermit>,
+}
+
+impl Admission {
+    pub fn new(
+        tenant: Option<OwnedSemaphorePermit>,
+        pipeline: Option<OwnedSemaphorePermit>,
+    ) -> Self {
+        Self {
+            _tenant: tenant,
+            _pipeline: pipeline,
+        }
+    }
+}
+
+/// Global cap on concurrent analyses. Requests over it wait, up to
+/// `max_queue` of them for at most `max_wait`, before getting 503.
+#[derive(Default)]
+pub struct PipelineQueue {
+    semaphore: Mutex<Option<(usize, Arc<Semaphore>)>>,
+    waiting: AtomicU64,
+}
+
+/// Counts a request as queued for as long as it is alive
+struct Waiting<'a> {
+    queue: &'a PipelineQueue,
+    metrics: &'a Metrics,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        let depth = self.queue.waiting.fetch_sub(1, Ordering::SeqCst) - 1;
+        self.metrics.set(QUEUE_DEPTH_METRIC, depth);
+    }
+}
+
+impl PipelineQueue {
+    /// Take a pipeline slot, waiting for one if need be. A `max_concurrent`
+    /// of 0 is unlimited and gets `None`.
+    pub async fn acquire(
+        &self,
+        max_concurrent: usize,
+        max_queue: usize,
+        max_wait: Duration,
+        metrics: &Metrics,
+    ) -> Result<Option<OwnedSemaphorePermit>, ApiError> {
+        if max_concurrent == 0 {
+            return Ok(None);
+        }
+        let semaphore = {
+            let mut slot = self.semaphore.lock().unwrap();
+            // As for tenants, a reload that changes the limit starts afresh
+            match &*slot {
+                Some((limit, semaphore)) if *limit == max_concurrent => semaphore.clone(),
+                _ => {
+                    let semaphore = Arc::new(Semaphore::new(max_concurrent));
+                    *slot = Some((max_concurrent, semaphore.clone()));
+                    semaphore
+                }
+            }
+        };
+        if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+            return Ok(Some(permit));
+        }
+
+        let overloaded = |reason: String| {
+            metrics.incr("pipeline_queue_rejections_total");
+            ApiError::ServiceUnavailable(reason)
+        };
+        let depth = self.waiting.fetch_add(1, Ordering::SeqCst) + 1;
+        let _waiting = Waiting {
+            queue: self,
+            metrics,
+        };
+        metrics.set(QUEUE_DEPTH_METRIC, depth);
+        if depth > max_queue as u64 {
+            return Err(overloaded(format!(
+                "analysis queue is full ({} waiting)",
+                max_queue
+            )));
+        }
+        match tokio::time::timeout(max_wait, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(Some(permit)),
+            // Only a replaced semaphore is ever dropped, never closed
+            Ok(Err(_)) => Err(overloaded("analysis queue was reset".to_string())),
+            Err(_) => Err(overloaded(format!(
+                "no analysis slot became free within {}s",
+                max_wait.as_secs()
+            ))),
+        }
+    }
+}
+
+/// One semaphore per token name, created on first use
+#[derive(Default)]
+pub struct TenantLimiter {
+    semaphores: Mutex<HashMap<String, (usize, Arc<Semaphore>)>>,
+}
+
+impl TenantLimiter {
+    /// Take one of the caller's in-flight slots, held until the permit drops.
+    /// Fails with 429 rather than queueing when they are all taken. Anonymous
+    /// callers and tokens with no limit get `None`.
+    pub fn try_acquire(
+        &self,
+        principal: Option<&Principal>,
+    ) -> Result<Option<OwnedSemaphorePermit>, ApiError> {
+        let Some(principal) = principal.filter(|p| p.max_in_flight > 0) else {
+            return Ok(None);
+        };
+        let limit = principal.max_in_flight;
+        let semaphore = {
+            let mut semaphores = self.semaphores.lock().unwrap();
+            let entry = semaphores
+                .entry(principal.name.clone())
+                .or_insert_with(|| (limit, Arc::new(Semaphore::new(limit))));
+            // A reload changed the limit: start afresh and let the old
+            // permits drain with the requests holding them
+            if entry.0 != limit {
+                *entry = (limit, Arc::new(Semaphore::new(limit)));
+            }
+            entry.1.clone()
+        };
+        semaphore.try_acquire_owned().map(Some).map_err(|_| {
+            ApiError::TooManyRequests(format!(
+                "token {:?} already has {} requests in flight",
+                principal.name, limit
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(max_in_flight: usize) -> Principal {
+        Principal {
+            name: "tenant".to_string(),
+            scopes: Default::default(),
+            max_in_flight,
+        }
+    }
+
+    #[test]
+    fn rejects_beyond_the_cap_until_a_permit_drops() {
+        let limiter = TenantLimiter::default();
+        let tenant = principal(1);
+        let first = limiter.try_acquire(Some(&tenant)).unwrap();
+        assert!(first.is_some());
+        assert!(matches!(
+            limiter.try_acquire(Some(&tenant)),
+            Err(ApiError::TooManyRequests(_))
+        ));
+        drop(first);
+        assert!(limiter.try_acquire(Some(&tenant)).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn queued_requests_wait_for_a_slot_then_time_out() {
+        let queue = PipelineQueue::default();
+        let metrics = Metrics::default();
+        let wait = Duration::from_millis(50);
+        let first = queue.acquire(1, 1, wait, &metrics).await.unwrap();
+        assert!(first.is_some());
+
+        // Freed while the second request is queued
+        let release = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(first);
+        };
+        let (second, ()) = tokio::join!(queue.acquire(1, 1, wait, &metrics), release);
+        let second = second.unwrap();
+        assert!(second.is_some());
+
+        assert!(matches!(
+            queue.acquire(1, 1, wait, &metrics).await,
+            Err(ApiError::ServiceUnavailable(_))
+        ));
+        assert!(matches!(
+            queue.acquire(1, 0, wait, &metrics).await,
+            Err(ApiError::ServiceUnavailable(_))
+        ));
+        assert_eq!(metrics.snapshot()[QUEUE_DEPTH_METRIC], 0);
+    }
+
+    #[test]
+    fn anonymous_and_unlimited_callers_are_not_capped() {
+        let limiter = TenantLimiter::default();
+        assert!(limiter.try_acquire(None).unwrap().is_none());
+        assert!(limiter.try_acquire(Some(&principal(0))).unwrap().is_none());
+    }
+}
@@ -0,0 +1,834 @@
+//! Gateway configuration loaded from environment variables
+//! Variable names and defaults mirror .env.example
+
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::Url;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::allowlist::HostAllowlist;
+use crate::citation::CitationStyle;
+use crate::forwarded;
+use crate::mime;
+use crate::models::{OpinionType, Outcome, SectionType};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("invalid value {value:?} for {key}: {reason}")]
+    Invalid {
+        key: &'static str,
+        value: String,
+        reason: String,
+    },
+    #[error("failed to read config file {path}: {reason}")]
+    File { path: String, reason: String },
+}
+
+/// A bearer token accepted by the gateway. `name` identifies the caller in
+/// logs and per-tenant limits so the secret itself is never logged.
+#[derive(Clone)]
+pub struct ApiToken {
+    pub name: String,
+    pub token: String,
+    pub scopes: HashSet<String>,
+    /// Expensive requests this token may have in flight at once; 0 is unlimited
+    pub max_in_flight: usize,
+}
+
+impl std::fmt::Debug for ApiToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiToken")
+            .field("name", &self.name)
+            .field("scopes", &self.scopes)
+            .field("max_in_flight", &self.max_in_flight)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub port: u16,
+    /// Path every route is nested under, e.g. `/legal-judge`; empty for none.
+    /// `/health` is also served at the root for probes.
+    pub api_prefix: String,
+    /// Serve bare response bodies instead of ApiEnvelope while clients migrate
+    pub legacy_unwrapped_responses: bool,
+    /// Fraction of successful requests written to the access log; errors
+    /// are always logged
+    pub access_log_sample_rate: f64,
+    /// Tokio worker threads; 0 uses one per CPU core
+    pub runtime_worker_threads: usize,
+    /// Cap on threads for blocking work such as PDF inspection
+    pub runtime_max_blocking_threads: usize,
+    pub api_tokens: Vec<ApiToken>,
+    /// Opinion types only tokens with the paired scope may request;
+    /// per_curiam is always open
+    pub opinion_type_scopes: Vec<(OpinionType, String)>,
+    /// Attach X-Signature to JSON responses; see [`crate::signing`]
+    pub sign_responses: bool,
+    pub response_signing_key: String,
+
+    // Python services
+    pub embedding_service_url: String,
+    pub ingestion_service_url: String,
+    pub search_service_url: String,
+    pub prediction_service_url: String,
+    pub opinion_service_url: String,
+    pub ocr_service_url: String,
+    /// OCR engines tried in order; OCR_SERVICE_URL alone when unset
+    pub ocr_service_urls: Vec<String>,
+    /// Every downstream URL must match this, at startup and per request
+    pub downstream_allowlist: HostAllowlist,
+
+    // Analyze pipeline
+    pub analyze_total_timeout: Duration,
+    pub analyze_job_retention: Duration,
+    /// Analyses kept per token for GET /api/analyze-brief/history, for up
+    /// to `analyze_job_retention`; 0 disables it
+    pub analyze_history_size: usize,
+    /// Whole-download limit for /api/analyze-url fetches
+    pub remote_document_timeout: Duration,
+    /// How long full OCR text stays at GET /api/ocr-text/:id; 0 disables it
+    pub ocr_text_retention: Duration,
+    /// Answer 200 with an empty judge_opinion and a warning when opinion
+    /// generation fails, instead of failing the analysis
+    pub analyze_opinion_fail_open: bool,
+    /// Neutralize prompt-injection phrasing in OCR text before it reaches
+    /// the opinion service
+    pub analyze_sanitize_prompt_injection: bool,
+    /// Analyses run at once across all callers; 0 is unlimited
+    pub pipeline_max_concurrent: usize,
+    pub pipeline_max_queue: usize,
+    pub pipeline_queue_timeout: Duration,
+    /// Retries all downstream calls in one analysis may make between them
+    pub pipeline_retry_budget: u32,
+    /// Backoff and attempt time those retries may spend between them
+    pub pipeline_retry_budget_time: Duration,
+    pub callback_signing_secret: String,
+    pub callback_max_attempts: u32,
+    pub callback_retry_base: Duration,
+
+    // Uploads
+    pub max_upload_bytes: usize,
+    pub max_json_body_bytes: usize,
+    /// Longest a client may take to send its body once it's read; 0 disables
+    pub request_body_timeout: Duration,
+    /// Uploads larger than this are streamed to a temp file instead of memory
+    pub upload_spool_threshold_bytes: usize,
+    pub health_min_temp_free_mb: u64,
+    /// How long /health waits on each downstream's own /health, all checked
+    /// at once; 0 skips them
+    pub health_check_timeout: Duration,
+    /// Multipart field names accepted for the brief; a form with more than
+    /// one part under any of these names is rejected with 400
+    pub upload_field_names: Vec<String>,
+    /// Most parts read from an analyze form, the document included
+    pub max_multipart_fields: usize,
+    /// Upload types accepted, checked against the declared type and magic bytes
+    pub upload_allowed_mime_types: Vec<String>,
+
+    // OCR
+    pub ocr_default_lang: String,
+    pub ocr_autodetect_lang: bool,
+    pub ocr_autodetect_min_confidence: f64,
+    /// Documents longer than this many pages are OCR'd in page-range chunks; 0 disables
+    pub ocr_chunk_pages: u32,
+    pub ocr_chunk_concurrency: usize,
+    /// Fail the analysis when any chunk fails, instead of continuing with
+    /// the pages that were read and listing the rest in `failed_pages`
+    pub ocr_fail_on_partial: bool,
+    pub ocr_lossy_utf8: bool,
+    pub ocr_normalize_text: bool,
+
+    // Downstream connection pool
+    pub downstream_pool_max_idle_per_host: usize,
+    pub downstream_pool_idle_timeout: Duration,
+    /// Connections opened to each downstream URL before serving; 0 skips it
+    pub warmup_connections: usize,
+    pub warmup_timeout: Duration,
+    /// Refuse to start when any downstream can't be reached at warm-up
+    pub strict_warmup: bool,
+    pub downstream_max_connections_per_host: usize,
+    pub downstream_user_agent: String,
+    /// Sent as `Authorization: Bearer <key>` on every downstream request;
+    /// empty sends none
+    pub downstream_api_key: String,
+    /// Lowercased client request header names relayed on downstream calls
+    pub forward_headers: Vec<String>,
+    /// Retries per downstream call after a connection failure, timeout or
+    /// 502/503/504
+    pub max_retries: u32,
+    /// Delay before the first retry, doubling after each
+    pub retry_backoff: Duration,
+
+    // Prediction
+    pub default_jurisdiction: String,
+    /// Lowercased jurisdiction codes accepted on PredictionRequest
+    pub allowed_jurisdictions: Vec<String>,
+
+    // Opinion generation
+    pub max_precedents_limit: i32,
+    /// How long generated opinions stay retrievable at GET /api/opinion/:id
+    pub opinion_retention: Duration,
+    /// Style generated opinions' recognized citations are rewritten in
+    pub opinion_citation_style: CitationStyle,
+    pub confidence_warn_threshold: f64,
+    pub prediction_search_fallback: bool,
+    /// Predictions citing fewer supporting cases carry a weak-support warning
+    pub min_supporting_cases: usize,
+    /// Reject weakly supported predictions with 422 instead of warning
+    pub min_supporting_cases_strict: bool,
+    /// Labels favoured, in order, when outcomes tie for the top probability
+    pub outcome_tie_priority: Vec<String>,
+    /// Lowercased prediction-service labels and the outcome each stands for;
+    /// labels naming an [`Outcome`] need no entry
+    pub outcome_labels: Vec<(String, Outcome)>,
+    /// Share of a hybrid search score from the semantic side (0-1)
+    pub search_hybrid_semantic_weight: f64,
+    /// Larger top_k values are clamped to this, in every search the gateway runs
+    pub max_top_k: i32,
+    /// Snippet length when a SearchRequest doesn't set max_snippet_chars
+    pub search_max_snippet_chars: usize,
+    /// Length of snippets built from `full_document` for results that come
+    /// without one; 0 leaves them empty
+    pub search_snippet_fallback_chars: usize,
+    /// Off strips `full_document` from search results, leaving clients to
+    /// fetch the ones they need from POST /api/cases/batch
+    pub search_include_full_documents: bool,
+    /// Most document_ids one POST /api/cases/batch may ask for
+    pub case_batch_max_ids: usize,
+    /// Requests asking for a lower min_similarity are raised to this
+    pub global_min_similarity: f64,
+    /// section_type values passed through to clients; others become "other"
+    pub search_result_section_types: Vec<String>,
+    /// (court, weight) pairs; a result's score is multiplied by the weight
+    /// of the first court its court name contains, case-insensitively
+    pub search_court_weights: Vec<(String, f64)>,
+    /// Returned as `guidance` when a search comes back empty because the
+    /// index holds no documents at all
+    pub search_no_corpus_guidance: String,
+    /// Named search service URLs a request's `indices` may fan out to
+    pub search_indices: Vec<(String, String)>,
+    /// Collapse runs of whitespace inside queries to single spaces; queries
+    /// are trimmed either way
+    pub search_collapse_query_whitespace: bool,
+    pub stats_refresh_interval: Duration,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::load(&EnvSource::default())
+    }
+
+    /// Re-read configuration with values from `path` (a .env file) taking
+    /// precedence over the process environment
+    pub fn from_env_file(path: &str) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| ConfigError::File {
+            path: path.to_string(),
+            reason: e.to_string(),
+        })?;
+        let overrides = parse_env_file(&contents);
+        Self::load(&EnvSource { overrides })
+    }
+
+    fn load(env: &EnvSource) -> Result<Self, ConfigError> {
+        let mut ocr_service_urls = env.list("OCR_SERVICE_URLS", "");
+        if ocr_service_urls.is_empty() {
+            ocr_service_urls.push(env.or("OCR_SERVICE_URL", "http://localhost:8000"));
+        }
+
+        let config = Self {
+            port: env.parse("RUST_API_PORT", 8080)?,
+            api_prefix: env
+                .or("API_PREFIX", "")
+                .trim()
+                .trim_end_matches('/')
+                .to_string(),
+            legacy_unwrapped_responses: env.parse("LEGACY_UNWRAPPED_RESPONSES", false)?,
+            access_log_sample_rate: env.parse("ACCESS_LOG_SAMPLE_RATE", 1.0)?,
+            runtime_worker_threads: env.parse("RUNTIME_WORKER_THREADS", 0)?,
+            runtime_max_blocking_threads: env.parse("RUNTIME_MAX_BLOCKING_THREADS", 512)?,
+            api_tokens: parse_api_tokens(
+                &env.or("API_TOKENS", ""),
+                env.parse("TOKEN_MAX_IN_FLIGHT", 4)?,
+                &env.list("TOKEN_MAX_IN_FLIGHT_OVERRIDES", ""),
+            )?,
+            opinion_type_scopes: parse_opinion_type_scopes(&env.list("OPINION_TYPE_SCOPES", ""))?,
+            sign_responses: env.parse("SIGN_RESPONSES", false)?,
+            response_signing_key: env.or("RESPONSE_SIGNING_KEY", ""),
+
+            embedding_service_url: env.or("EMBEDDING_SERVICE_URL", "http://localhost:8001"),
+            ingestion_service_url: env.or("INGESTION_SERVICE_URL", "http://localhost:8002"),
+            search_service_url: env.or("SEARCH_SERVICE_URL", "http://localhost:8003"),
+            prediction_service_url: env.or("PREDICTION_SERVICE_URL", "http://localhost:8004"),
+            opinion_service_url: env.or("OPINION_SERVICE_URL", "http://localhost:8005"),
+            ocr_service_url: ocr_service_urls[0].clone(),
+            ocr_service_urls,
+            downstream_allowlist: HostAllowlist::new(
+                env.list(
+                    "DOWNSTREAM_ALLOWED_HOSTS",
+                    "localhost,127.0.0.1,python_services,ocr_service",
+                ),
+                env.list("DOWNSTREAM_ALLOWED_SCHEMES", "http,https"),
+            ),
+
+            analyze_total_timeout: Duration::from_secs(
+                env.parse("ANALYZE_TOTAL_TIMEOUT_SECONDS", 90)?,
+            ),
+            analyze_job_retention: Duration::from_secs(
+                env.parse("ANALYZE_JOB_RETENTION_SECONDS", 3600)?,
+            ),
+            analyze_history_size: env.parse("ANALYZE_HISTORY_SIZE", 100)?,
+            remote_document_timeout: Duration::from_secs(
+                env.parse("REMOTE_DOCUMENT_TIMEOUT_SECONDS", 30)?,
+            ),
+            ocr_text_retention: Duration::from_secs(env.parse("OCR_TEXT_RETENTION_SECONDS", 3600)?),
+            analyze_opinion_fail_open: env.parse("ANALYZE_OPINION_FAIL_OPEN", true)?,
+            analyze_sanitize_prompt_injection: env
+                .parse("ANALYZE_SANITIZE_PROMPT_INJECTION", false)?,
+            pipeline_max_concurrent: env.parse("PIPELINE_MAX_CONCURRENT", 0)?,
+            pipeline_max_queue: env.parse("PIPELINE_MAX_QUEUE", 16)?,
+            pipeline_queue_timeout: Duration::from_secs(
+                env.parse("PIPELINE_QUEUE_TIMEOUT_SECONDS", 10)?,
+            ),
+            pipeline_retry_budget: env.parse("PIPELINE_RETRY_BUDGET", 5)?,
+            pipeline_retry_budget_time: Duration::from_secs(
+                env.parse("PIPELINE_RETRY_BUDGET_SECONDS", 15)?,
+            ),
+            callback_signing_secret: env.or("CALLBACK_SIGNING_SECRET", ""),
+            callback_max_attempts: env.parse("CALLBACK_MAX_ATTEMPTS", 5)?,
+            callback_retry_base: Duration::from_secs(env.parse("CALLBACK_RETRY_BASE_SECONDS", 1)?),
+
+            max_upload_bytes: env.parse("MAX_UPLOAD_BYTES", 50 * 1024 * 1024)?,
+            max_json_body_bytes: env.parse("MAX_JSON_BODY_BYTES", 1024 * 1024)?,
+            request_body_timeout: Duration::from_secs(
+                env.parse("REQUEST_BODY_TIMEOUT_SECONDS", 60)?,
+            ),
+            upload_spool_threshold_bytes: env.parse("UPLOAD_SPOOL_THRESHOLD_BYTES", 1024 * 1024)?,
+            health_min_temp_free_mb: env.parse("HEALTH_MIN_TEMP_FREE_MB", 512)?,
+            health_check_timeout: Duration::from_millis(
+                env.parse("HEALTH_CHECK_TIMEOUT_MS", 2000)?,
+            ),
+            upload_field_names: env.list("UPLOAD_FIELD_NAMES", "file"),
+            max_multipart_fields: env.parse("MAX_MULTIPART_FIELDS", 16)?,
+            upload_allowed_mime_types: env
+                .list("UPLOAD_ALLOWED_MIME_TYPES", "application/pdf")
+                .into_iter()
+                .map(|mime| mime.to_ascii_lowercase())
+                .collect(),
+
+            ocr_default_lang: env.or("OCR_DEFAULT_LANG", "eng"),
+            ocr_autodetect_lang: env.parse("OCR_AUTODETECT_LANG", false)?,
+            ocr_autodetect_min_confidence: env.parse("OCR_AUTODETECT_MIN_CONFIDENCE", 0.6)?,
+            ocr_chunk_pages: env.parse("OCR_CHUNK_PAGES", 10)?,
+            ocr_chunk_concurrency: env.parse("OCR_CHUNK_CONCURRENCY", 4)?,
+            ocr_fail_on_partial: env.parse("OCR_FAIL_ON_PARTIAL", false)?,
+            ocr_lossy_utf8: env.parse("OCR_LOSSY_UTF8", false)?,
+            ocr_normalize_text: env.parse("OCR_NORMALIZE_TEXT", false)?,
+
+            downstream_pool_max_idle_per_host: env
+                .parse("DOWNSTREAM_POOL_MAX_IDLE_PER_HOST", 32)?,
+            downstream_pool_idle_timeout: Duration::from_secs(
+                env.parse("DOWNSTREAM_POOL_IDLE_TIMEOUT_SECONDS", 90)?,
+            ),
+            warmup_connections: env.parse("WARMUP_CONNECTIONS", 2)?,
+            warmup_timeout: Duration::from_secs(env.parse("WARMUP_TIMEOUT_SECONDS", 5)?),
+            strict_warmup: env.parse("STRICT_WARMUP", false)?,
+            downstream_max_connections_per_host: env
+                .parse("DOWNSTREAM_MAX_CONNECTIONS_PER_HOST", 64)?,
+            downstream_user_agent: env.or(
+                "DOWNSTREAM_USER_AGENT",
+                concat!("legal-judge-api/", env!("CARGO_PKG_VERSION")),
+            ),
+            downstream_api_key: env.or("DOWNSTREAM_API_KEY", ""),
+            forward_headers: env
+                .list("FORWARD_HEADERS", "")
+                .into_iter()
+                .map(|name| name.to_ascii_lowercase())
+                .collect(),
+            max_retries: env.parse("MAX_RETRIES", 3)?,
+            retry_backoff: Duration::from_millis(env.parse("RETRY_BACKOFF_MS", 1000)?),
+
+            default_jurisdiction: env.or("DEFAULT_JURISDICTION", "us").to_ascii_lowercase(),
+            allowed_jurisdictions: env
+                .list("ALLOWED_JURISDICTIONS", "us")
+                .into_iter()
+                .map(|j| j.to_ascii_lowercase())
+                .collect(),
+
+            max_precedents_limit: env.parse("MAX_PRECEDENTS_LIMIT", 20)?,
+            opinion_retention: Duration::from_secs(env.parse("OPINION_RETENTION_SECONDS", 3600)?),
+            opinion_citation_style: env
+                .parse("OPINION_CITATION_STYLE", CitationStyle::AsWritten)?,
+            confidence_warn_threshold: env.parse("CONFIDENCE_WARN_THRESHOLD", 0.5)?,
+            prediction_search_fallback: env.parse("PREDICTION_SEARCH_FALLBACK", false)?,
+            min_supporting_cases: env.parse("MIN_SUPPORTING_CASES", 2)?,
+            min_supporting_cases_strict: env.parse("MIN_SUPPORTING_CASES_STRICT", false)?,
+            outcome_tie_priority: env.list("OUTCOME_TIE_PRIORITY", "MIXED"),
+            outcome_labels: parse_outcome_labels(&env.list("OUTCOME_LABEL_MAP", ""))?,
+            search_hybrid_semantic_weight: env.parse("SEARCH_HYBRID_SEMANTIC_WEIGHT", 0.7)?,
+            max_top_k: env.parse("MAX_TOP_K", 100)?,
+            search_max_snippet_chars: env.parse("SEARCH_MAX_SNIPPET_CHARS", 500)?,
+            search_snippet_fallback_chars: env.parse("SEARCH_SNIPPET_FALLBACK_CHARS", 240)?,
+            search_include_full_documents: env.parse("SEARCH_INCLUDE_FULL_DOCUMENTS", true)?,
+            case_batch_max_ids: env.parse("CASE_BATCH_MAX_IDS", 50)?,
+            global_min_similarity: env.parse("GLOBAL_MIN_SIMILARITY", 0.0)?,
+            search_result_section_types: env
+                .list(
+                    "SEARCH_RESULT_SECTION_TYPES",
+                    "facts,issue,reasoning,holding,judgment",
+                )
+                .into_iter()
+                .map(|section| section.to_ascii_lowercase())
+                .collect(),
+            search_court_weights: parse_court_weights(&env.list("SEARCH_COURT_WEIGHTS", ""))?,
+            search_no_corpus_guidance: env.or(
+                "SEARCH_NO_CORPUS_GUIDANCE",
+                "The case-law index is empty. Ingest documents with POST /api/ingest, \
+                 then search again.",
+            ),
+            search_indices: parse_search_indices(&env.list("SEARCH_INDICES", ""))?,
+            search_collapse_query_whitespace: env
+                .parse("SEARCH_COLLAPSE_QUERY_WHITESPACE", false)?,
+            stats_refresh_interval: Duration::from_secs(
+                env.parse("STATS_REFRESH_INTERVAL_SECONDS", 60)?,
+            ),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.runtime_max_blocking_threads == 0 {
+            return Err(ConfigError::Invalid {
+                key: "RUNTIME_MAX_BLOCKING_THREADS",
+                value: "0".to_string(),
+                reason: "must be at least 1".to_string(),
+            });
+        }
+        if self.sign_responses && self.response_signing_key.is_empty() {
+            return Err(ConfigError::Invalid {
+                key: "RESPONSE_SIGNING_KEY",
+                value: String::new(),
+                reason: "required when SIGN_RESPONSES is enabled".to_string(),
+            });
+        }
+        let headers = [
+            ("DOWNSTREAM_USER_AGENT", &self.downstream_user_agent),
+            ("DOWNSTREAM_API_KEY", &self.downstream_api_key),
+        ];
+        for (key, value) in headers {
+            if HeaderValue::from_str(value).is_err() {
+                return Err(ConfigError::Invalid {
+                    key,
+                    // Don't echo the key itself into logs
+                    value: String::new(),
+                    reason: "not a valid HTTP header value".to_string(),
+                });
+            }
+        }
+        for name in &self.forward_headers {
+            let reason = if HeaderName::from_bytes(name.as_bytes()).is_err() {
+                "not a valid HTTP header name"
+            } else if forwarded::NEVER_FORWARDED.contains(&name.as_str()) {
+                "carries credentials or is hop-by-hop and is never forwarded"
+            } else {
+                continue;
+            };
+            return Err(ConfigError::Invalid {
+                key: "FORWARD_HEADERS",
+                value: name.clone(),
+                reason: reason.to_string(),
+            });
+        }
+        if self.downstream_user_agent.trim().is_empty() {
+            return Err(ConfigError::Invalid {
+                key: "DOWNSTREAM_USER_AGENT",
+                value: String::new(),
+                reason: "must not be empty".to_string(),
+            });
+        }
+        if !self.api_prefix.is_empty() && !self.api_prefix.starts_with('/') {
+            return Err(ConfigError::Invalid {
+                key: "API_PREFIX",
+                value: self.api_prefix.clone(),
+                reason: "must start with /".to_string(),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.access_log_sample_rate) {
+            return Err(ConfigError::Invalid {
+                key: "ACCESS_LOG_SAMPLE_RATE",
+                value: self.access_log_sample_rate.to_string(),
+                reason: "must be between 0 and 1".to_string(),
+            });
+        }
+        if self.max_top_k < 1 {
+            return Err(ConfigError::Invalid {
+                key: "MAX_TOP_K",
+                value: self.max_top_k.to_string(),
+                reason: "must be at least 1".to_string(),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.search_hybrid_semantic_weight) {
+            return Err(ConfigError::Invalid {
+                key: "SEARCH_HYBRID_SEMANTIC_WEIGHT",
+                value: self.search_hybrid_semantic_weight.to_string(),
+                reason: "must be between 0 and 1".to_string(),
+            });
+        }
+        if !(0.0..=1.0).contains(&self.global_min_similarity) {
+            return Err(ConfigError::Invalid {
+                key: "GLOBAL_MIN_SIMILARITY",
+                value: self.global_min_similarity.to_string(),
+                reason: "must be between 0 and 1".to_string(),
+            });
+        }
+        if let Some((court, weight)) = self
+            .search_court_weights
+            .iter()
+            .find(|(_, weight)| !(weight.is_finite() && *weight > 0.0))
+        {
+            return Err(ConfigError::Invalid {
+                key: "SEARCH_COURT_WEIGHTS",
+                value: format!("{}={}", court, weight),
+                reason: "weights must be positive".to_string(),
+            });
+        }
+        if let Some(unknown) = self
+            .search_result_section_types
+            .iter()
+            .find(|section| section.parse::<SectionType>().is_err())
+        {
+            return Err(ConfigError::Invalid {
+                key: "SEARCH_RESULT_SECTION_TYPES",
+                value: unknown.clone(),
+                reason: "not a known section type".to_string(),
+            });
+        }
+        if self.upload_allowed_mime_types.is_empty() {
+            return Err(ConfigError::Invalid {
+                key: "UPLOAD_ALLOWED_MIME_TYPES",
+                value: String::new(),
+                reason: "at least one type is required".to_string(),
+            });
+        }
+        if let Some(unknown) = self
+            .upload_allowed_mime_types
+            .iter()
+            .find(|mime| !mime::is_known(mime))
+        {
+            return Err(ConfigError::Invalid {
+                key: "UPLOAD_ALLOWED_MIME_TYPES",
+                value: unknown.clone(),
+                reason: "the gateway has no signature for this type".to_string(),
+            });
+        }
+        if self.upload_field_names.is_empty() {
+            return Err(ConfigError::Invalid {
+                key: "UPLOAD_FIELD_NAMES",
+                value: String::new(),
+                reason: "at least one field name is required".to_string(),
+            });
+        }
+        if self.case_batch_max_ids == 0 {
+            return Err(ConfigError::Invalid {
+                key: "CASE_BATCH_MAX_IDS",
+                value: "0".to_string(),
+                reason: "must be at least 1".to_string(),
+            });
+        }
+        if self.max_multipart_fields == 0 {
+            return Err(ConfigError::Invalid {
+                key: "MAX_MULTIPART_FIELDS",
+                value: "0".to_string(),
+                reason: "must be at least 1".to_string(),
+            });
+        }
+
+        if !self
+            .allowed_jurisdictions
+            .contains(&self.default_jurisdiction)
+        {
+            return Err(ConfigError::Invalid {
+                key: "DEFAULT_JURISDICTION",
+                value: self.default_jurisdiction.clone(),
+                reason: "not listed in ALLOWED_JURISDICTIONS".to_string(),
+            });
+        }
+
+        let urls = [
+            ("EMBEDDING_SERVICE_URL", &self.embedding_service_url),
+            ("INGESTION_SERVICE_URL", &self.ingestion_service_url),
+            ("SEARCH_SERVICE_URL", &self.search_service_url),
+            ("PREDICTION_SERVICE_URL", &self.prediction_service_url),
+            ("OPINION_SERVICE_URL", &self.opinion_service_url),
+            ("OCR_SERVICE_URL", &self.ocr_service_url),
+        ];
+        let ocr_engines = self
+            .ocr_service_urls
+            .iter()
+            .map(|url| ("OCR_SERVICE_URLS", url));
+        let search_indices = self
+            .search_indices
+            .iter()
+            .map(|(_, url)| ("SEARCH_INDICES", url));
+        for (key, value) in urls.into_iter().chain(ocr_engines).chain(search_indices) {
+            let invalid = |reason: &str| ConfigError::Invalid {
+                key,
+                value: value.clone(),
+                reason: reason.to_string(),
+            };
+            let url = Url::parse(value).map_err(|e| invalid(&e.to_string()))?;
+            if !self.downstream_allowlist.permits(&url) {
+                return Err(invalid(
+                    "host or scheme not in DOWNSTREAM_ALLOWED_HOSTS/DOWNSTREAM_ALLOWED_SCHEMES",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where configuration values come from: explicit overrides (a re-read .env
+/// file), falling back to the process environment
+#[derive(Debug, Default)]
+struct EnvSource {
+    overrides: HashMap<String, String>,
+}
+
+impl EnvSource {
+    fn get(&self, key: &str) -> Option<String> {
+        self.overrides
+            .get(key)
+            .cloned()
+            .or_else(|| std::env::var(key).ok())
+    }
+
+    fn or(&self, key: &str, default: &str) -> String {
+        self.get(key).unwrap_or_else(|| default.to_string())
+    }
+
+    fn list(&self, key: &str, default: &str) -> Vec<String> {
+        self.or(key, default)
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    fn parse<T>(&self, key: &'static str, default: T) -> Result<T, ConfigError>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        match self.get(key) {
+            Some(value) => value
+                .trim()
+                .parse()
+                .map_err(|e: T::Err| ConfigError::Invalid {
+                    key,
+                    reason: e.to_string(),
+                    value,
+                }),
+            None => Ok(default),
+        }
+    }
+}
+
+/// Minimal .env parser: `KEY=value` lines, optional `export ` prefix and
+/// surrounding quotes; blank lines and `#` comments are skipped
+fn parse_env_file(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = [('"', '"'), ('\'', '\'')]
+                .iter()
+                .find_map(|(open, close)| value.strip_prefix(*open)?.strip_suffix(*close))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Parse `API_TOKENS`: comma-separated `name:token:scope|scope` entries,
+/// each allowed `max_in_flight` concurrent requests unless a `name=limit`
+/// entry in `overrides` says otherwise
+fn parse_api_tokens(
+    raw: &str,
+    max_in_flight: usize,
+    overrides: &[String],
+) -> Result<Vec<ApiToken>, ConfigError> {
+    let mut tokens = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let mut parts = entry.splitn(3, ':');
+            let name = parts.next().unwrap_or_default().trim();
+            let token = parts.next().unwrap_or_default().trim();
+            if name.is_empty() || token.is_empty() {
+                return Err(ConfigError::Invalid {
+                    key: "API_TOKENS",
+                    value: name.to_string(),
+                    reason: "entries must look like name:token[:scope|scope]".to_string(),
+                });
+            }
+            let scopes = parts
+                .next()
+                .unwrap_or_default()
+                .split('|')
+                .map(str::trim)
+                .filter(|scope| !scope.is_empty())
+                .map(str::to_string)
+                .collect();
+            Ok(ApiToken {
+                name: name.to_string(),
+                token: token.to_string(),
+                scopes,
+                max_in_flight,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for entry in overrides {
+        let invalid = |reason: &str| ConfigError::Invalid {
+            key: "TOKEN_MAX_IN_FLIGHT_OVERRIDES",
+            value: entry.clone(),
+            reason: reason.to_string(),
+        };
+        let (name, limit) = entry
+            .split_once('=')
+            .ok_or_else(|| invalid("entries must look like name=limit"))?;
+        let limit = limit
+            .trim()
+            .parse()
+            .map_err(|e: std::num::ParseIntError| invalid(&e.to_string()))?;
+        let token = tokens
+            .iter_mut()
+            .find(|token| token.name == name.trim())
+            .ok_or_else(|| invalid("no API_TOKENS entry has this name"))?;
+        token.max_in_flight = limit;
+    }
+    Ok(tokens)
+}
+
+/// Parse `OPINION_TYPE_SCOPES`: comma-separated `opinion_type=scope` entries
+fn parse_opinion_type_scopes(
+    entries: &[String],
+) -> Result<Vec<(OpinionType, String)>, ConfigError> {
+    entries
+        .iter()
+        .map(|entry| {
+            let invalid = |reason: &str| ConfigError::Invalid {
+                key: "OPINION_TYPE_SCOPES",
+                value: entry.clone(),
+                reason: reason.to_string(),
+            };
+            let (opinion_type, scope) = entry
+                .split_once('=')
+                .map(|(opinion_type, scope)| (opinion_type.trim(), scope.trim()))
+                .filter(|(_, scope)| !scope.is_empty())
+                .ok_or_else(|| invalid("entries must look like opinion_type=scope"))?;
+            let opinion_type: OpinionType =
+                opinion_type.parse().map_err(|e: String| invalid(&e))?;
+            if opinion_type == OpinionType::PerCuriam {
+                return Err(invalid("per_curiam, the default, is always open"));
+            }
+            Ok((opinion_type, scope.to_string()))
+        })
+        .collect()
+}
+
+/// Parse `OUTCOME_LABEL_MAP`: comma-separated `label=Outcome` entries
+fn parse_outcome_labels(entries: &[String]) -> Result<Vec<(String, Outcome)>, ConfigError> {
+    entries
+        .iter()
+        .map(|entry| {
+            let invalid = |reason: &str| ConfigError::Invalid {
+                key: "OUTCOME_LABEL_MAP",
+                value: entry.clone(),
+                reason: reason.to_string(),
+            };
+            let (label, outcome) = entry
+                .split_once('=')
+                .filter(|(label, _)| !label.trim().is_empty())
+                .ok_or_else(|| invalid("entries must look like label=Outcome"))?;
+            let outcome = outcome.trim().parse().map_err(|e: String| invalid(&e))?;
+            Ok((label.trim().to_lowercase(), outcome))
+        })
+        .collect()
+}
+
+/// Parse `SEARCH_INDICES`: comma-separated `name=url` entries with
+/// distinct names
+fn parse_search_indices(entries: &[String]) -> Result<Vec<(String, String)>, ConfigError> {
+    let mut indices: Vec<(String, String)> = Vec::new();
+    for entry in entries {
+        let invalid = |reason: &str| ConfigError::Invalid {
+            key: "SEARCH_INDICES",
+            value: entry.clone(),
+            reason: reason.to_string(),
+        };
+        let (name, url) = entry
+            .split_once('=')
+            .map(|(name, url)| (name.trim(), url.trim().trim_end_matches('/')))
+            .filter(|(name, url)| !name.is_empty() && !url.is_empty())
+            .ok_or_else(|| invalid("entries must look like name=url"))?;
+        if indices.iter().any(|(known, _)| known == name) {
+            return Err(invalid("index names must be unique"));
+        }
+        indices.push((name.to_string(), url.to_string()));
+    }
+    Ok(indices)
+}
+
+/// Parse `SEARCH_COURT_WEIGHTS`: comma-separated `court=weight` entries,
+/// kept in order since the first matching court wins
+fn parse_court_weights(entries: &[String]) -> Result<Vec<(String, f64)>, ConfigError> {
+    entries
+        .iter()
+        .map(|entry| {
+            let invalid = |reason: &str| ConfigError::Invalid {
+                key: "SEARCH_COURT_WEIGHTS",
+                value: entry.clone(),
+                reason: reason.to_string(),
+            };
+            let (court, weight) = entry
+                .split_once('=')
+                .filter(|(court, _)| !court.trim().is_empty())
+                .ok_or_else(|| invalid("entries must look like court=weight"))?;
+            let weight = weight
+                .trim()
+                .parse()
+                .map_err(|e: std::num::ParseFloatError| invalid(&e.to_string()))?;
+            Ok((court.trim().to_lowercase(), weight))
+        })
+        .collect()
+}
+
+/// The live configuration, swapped wholesale by POST /admin/reload.
+/// Readers take a cheap `Arc` snapshot, so a request sees one consistent
+/// config even if a reload lands while it is running.
+#[derive(Debug, Clone)]
+pub struct SharedConfig(Arc<RwLock<Arc<Config>>>);
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        SharedConfig(Arc::new(RwLock::new(Arc::new(config))))
+    }
+
+    pub fn load(&self) -> Arc<Config> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub fn store(&self, config: Config) {
+        *self.0.write().unwrap() = Arc::new(config);
+    }
+}
@@ -0,0 +1,161 @@
+//! Court level classification from free-text court names
+//!
+//! Documents and results carry the court as the reporter states it
+//! ("Vermont Supreme Court", "S.D.N.Y.", "Ct. App."), so the level is
+//! inferred from common naming patterns rather than looked up.
+
+use serde::{Deserialize, Serialize};
+
+/// Where a court sits in the hierarchy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CourtLevel {
+    /// A court of last resort
+    Supreme,
+    Appellate,
+    Trial,
+    /// Not recognized from the name
+    Other,
+}
+
+impl CourtLevel {
+    pub const ALL: [CourtLevel; 4] = [
+        CourtLevel::Supreme,
+        CourtLevel::Appellate,
+        CourtLevel::Trial,
+        CourtLevel::Other,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CourtLevel::Supreme => "supreme",
+            CourtLevel::Appellate => "appellate",
+            CourtLevel::Trial => "trial",
+            CourtLevel::Other => "other",
+        }
+    }
+
+    /// Classify a court name, falling back to [`CourtLevel::Other`]
+    pub fn classify(court: &str) -> CourtLevel {
+        let name = court
+            .to_lowercase()
+            .replace(|c: char| !c.is_alphanumeric() && c != '.', " ");
+        let name = format!(
+            " {} ",
+            name.split_whitespace().collect::<Vec<_>>().join(" ")
+        );
+        let has = |patterns: &[&str]| patterns.iter().any(|p| name.contains(p));
+        let new_york = has(&[" new york ", " n.y. ", " ny "]);
+
+        // New York's Supreme Court is its trial court, its Appellate Division
+        // the intermediate court and its Court of Appeals the highest
+        if has(&[" appellate division ", " app. div. ", " app.div. "]) {
+            return CourtLevel::Appellate;
+        }
+        if new_york && has(&[" supreme court ", " sup. ct. "]) {
+            return CourtLevel::Trial;
+        }
+        if has(&[" supreme ", " sup. ct. ", " s. ct. ", " scotus "])
+            || (new_york || has(&[" maryland ", " md. "])) && has(&[" court of appeals "])
+        {
+            return CourtLevel::Supreme;
+        }
+        if has(&[" appeal ", " appeals ", " appellate ", " app. ", " cir. "]) {
+            return CourtLevel::Appellate;
+        }
+        if has(&[
+            " district court ",
+            " superior court ",
+            " circuit court ",
+            " county court ",
+            " municipal court ",
+            " family court ",
+            " probate ",
+            " chancery ",
+            " common pleas ",
+            " bankruptcy ",
+            " magistrate ",
+            " small claims ",
+            " housing court ",
+            " trial ",
+            " super. ct. ",
+            " dist. ct. ",
+            " d. ",
+        ]) || is_federal_district_abbreviation(&name)
+        {
+            return CourtLevel::Trial;
+        }
+        CourtLevel::Other
+    }
+}
+
+impl std::str::FromStr for CourtLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CourtLevel::ALL
+            .into_iter()
+            .find(|level| level.as_str() == s)
+            .ok_or_else(|| format!("unknown court level {:?}", s))
+    }
+}
+
+/// "S.D.N.Y.", "N.D. Cal." and the like: a compass point and "D." for district
+fn is_federal_district_abbreviation(name: &str) -> bool {
+    [" n.d.", " s.d.", " e.d.", " w.d.", " c.d.", " m.d."]
+        .iter()
+        .any(|prefix| name.contains(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_representative_court_names() {
+        for (court, level) in [
+            ("Supreme Court of the United States", CourtLevel::Supreme),
+            ("Vermont Supreme Court", CourtLevel::Supreme),
+            (
+                "Supreme Judicial Court of Massachusetts",
+                CourtLevel::Supreme,
+            ),
+            ("S. Ct.", CourtLevel::Supreme),
+            ("New York Court of Appeals", CourtLevel::Supreme),
+            (
+                "United States Court of Appeals for the Ninth Circuit",
+                CourtLevel::Appellate,
+            ),
+            ("9th Cir.", CourtLevel::Appellate),
+            ("California Court of Appeal", CourtLevel::Appellate),
+            ("Cal. Ct. App.", CourtLevel::Appellate),
+            (
+                "Supreme Court, Appellate Division, First Department",
+                CourtLevel::Appellate,
+            ),
+            ("New York Supreme Court, Kings County", CourtLevel::Trial),
+            (
+                "United States District Court for the District of Vermont",
+                CourtLevel::Trial,
+            ),
+            ("S.D.N.Y.", CourtLevel::Trial),
+            ("D. Vt.", CourtLevel::Trial),
+            ("Superior Court", CourtLevel::Trial),
+            ("Circuit Court of Cook County", CourtLevel::Trial),
+            ("Court of Common Pleas", CourtLevel::Trial),
+            ("Appeals Court of Massachusetts", CourtLevel::Appellate),
+            ("Tax Court", CourtLevel::Other),
+            ("", CourtLevel::Other),
+        ] {
+            assert_eq!(CourtLevel::classify(court), level, "{}", court);
+        }
+    }
+
+    #[test]
+    fn round_trips_through_strings() {
+        for level in CourtLevel::ALL {
+            assert_eq!(level.as_str().parse::<CourtLevel>(), Ok(level));
+        }
+        assert!("federal".parse::<CourtLevel>().is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! Minimal RFC 4180 CSV encoding for tabular exports
+
+/// Quote a field when it contains a delimiter, quote or line break,
+/// doubling any embedded quotes
+pub fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// One CRLF-terminated record
+pub fn record<I, S>(fields: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut line = fields
+        .into_iter()
+        .map(|field| escape_field(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_fields_are_left_unquoted() {
+        assert_eq!(record(["Roe v. Wade", "1973"]), "Roe v. Wade,1973\r\n");
+    }
+
+    #[test]
+    fn quotes_fields_with_commas_newlines_and_quotes() {
+        assert_eq!(
+            record(["Smith, Jr. v. Jones", "line one\nline two", "the \"rule\""]),
+            "\"Smith, Jr. v. Jones\",\"line one\nline two\",\"the \"\"rule\"\"\"\r\n"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! Client-supplied end-to-end deadline (X-Request-Deadline, epoch milliseconds)
+//!
+//! The middleware scopes the deadline to the request's task so
+//! [`crate::downstream::Downstream`] can fit every call inside it without each
+//! handler passing it along.
+
+use axum::{
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+use crate::error::ApiError;
+
+pub const REQUEST_DEADLINE_HEADER: &str = "x-request-deadline";
+/// Sent to downstream services with the milliseconds left in the budget
+pub const REQUEST_BUDGET_HEADER: &str = "x-request-budget-ms";
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Time left before the current request's deadline, or None when the client
+/// didn't set one
+pub fn remaining() -> Option<Duration> {
+    DEADLINE
+        .try_with(|deadline| deadline.saturating_duration_since(Instant::now()))
+        .ok()
+}
+
+/// Middleware that rejects already-expired requests with 504 and runs the
+/// rest with the deadline in scope
+pub async fn apply_deadline(request: Request, next: Next) -> Response {
+    let Some(raw) = request.headers().get(REQUEST_DEADLINE_HEADER) else {
+        return next.run(request).await;
+    };
+    let deadline_ms = match raw.to_str().ok().and_then(|v| v.trim().parse::<u64>().ok()) {
+        Some(ms) => ms,
+        None => {
+            return ApiError::BadRequest(format!(
+                "{} must be a Unix timestamp in milliseconds",
+                REQUEST_DEADLINE_HEADER
+            ))
+            .into_response()
+        }
+    };
+
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    if deadline_ms <= now_ms {
+        return ApiError::DeadlineExceeded.into_response();
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(deadline_ms - now_ms);
+    DEADLINE.scope(deadline, next.run(request)).await
+}
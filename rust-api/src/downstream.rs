@@ -0,0 +1,633 @@
+//! Shared HTTP client for calls to the Python services
+//! Bounds in-flight connections per service so pool exhaustion shows up as a
+//! measured wait instead of an unexplained latency spike
+
+use axum::body::Bytes;
+use futures::{Stream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, USER_AGENT};
+use reqwest::{Method, StatusCode, Url};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::{Config, SharedConfig};
+use crate::deadline;
+use crate::forwarded;
+use crate::metrics::Metrics;
+use crate::request_id;
+use crate::retry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Service {
+    Ocr,
+    Embedding,
+    Ingestion,
+    Search,
+    Prediction,
+    Opinion,
+}
+
+impl Service {
+    pub const ALL: [Service; 6] = [
+        Service::Ocr,
+        Service::Embedding,
+        Service::Ingestion,
+        Service::Search,
+        Service::Prediction,
+        Service::Opinion,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Service::Ocr => "ocr",
+            Service::Embedding => "embedding",
+            Service::Ingestion => "ingestion",
+            Service::Search => "search",
+            Service::Prediction => "prediction",
+            Service::Opinion => "opinion",
+        }
+    }
+
+    pub fn base_url(self, config: &Config) -> &str {
+        match self {
+            Service::Ocr => &config.ocr_service_url,
+            Service::Embedding => &config.embedding_service_url,
+            Service::Ingestion => &config.ingestion_service_url,
+            Service::Search => &config.search_service_url,
+            Service::Prediction => &config.prediction_service_url,
+            Service::Opinion => &config.opinion_service_url,
+        }
+    }
+}
+
+impl fmt::Display for Service {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DownstreamError {
+    #[error("{service} service request failed: {source}")]
+    Request {
+        service: Service,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("{service} service returned {status}")]
+    Status {
+        service: Service,
+        status: reqwest::StatusCode,
+        body: String,
+    },
+    /// 401/403: DOWNSTREAM_API_KEY is missing or wrong. The body is dropped
+    /// since it may echo the key back.
+    #[error("{service} service rejected the gateway's credentials ({status})")]
+    CredentialsRejected {
+        service: Service,
+        status: reqwest::StatusCode,
+    },
+    #[error("{service} service URL {url} is not in the downstream allowlist")]
+    Disallowed { service: Service, url: String },
+    #[error("{service} service returned an unexpected body: {source}")]
+    Decode {
+        service: Service,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("request deadline exceeded before the {service} service responded")]
+    DeadlineExceeded { service: Service },
+    #[error("{service} service returned a body that is not valid UTF-8")]
+    InvalidUtf8 {
+        service: Service,
+        valid_up_to: usize,
+    },
+}
+
+/// A downstream response with its body fully read
+#[derive(Debug)]
+pub struct DownstreamResponse {
+    pub status: reqwest::StatusCode,
+    pub body: Bytes,
+}
+
+impl DownstreamResponse {
+    pub fn json<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.body)
+    }
+
+    /// Validate a 2xx body as UTF-8. With `lossy`, invalid sequences are
+    /// replaced by U+FFFD and the number of replacements is returned;
+    /// otherwise they are an error.
+    pub fn ensure_utf8(&mut self, service: Service, lossy: bool) -> Result<usize, DownstreamError> {
+        if !self.status.is_success() {
+            return Ok(0);
+        }
+        let valid_up_to = match std::str::from_utf8(&self.body) {
+            Ok(_) => return Ok(0),
+            Err(e) => e.valid_up_to(),
+        };
+        if !lossy {
+            return Err(DownstreamError::InvalidUtf8 {
+                service,
+                valid_up_to,
+            });
+        }
+        // from_utf8_lossy emits one U+FFFD per invalid sequence
+        let mut replacements = 0;
+        let mut rest = &self.body[..];
+        while let Err(e) = std::str::from_utf8(rest) {
+            replacements += 1;
+            let skip = e.error_len().unwrap_or(rest.len() - e.valid_up_to());
+            rest = &rest[e.valid_up_to() + skip..];
+        }
+        self.body = Bytes::from(String::from_utf8_lossy(&self.body).into_owned());
+        Ok(replacements)
+    }
+
+    /// Require a 2xx status and decode the body as `T`
+    pub fn decode<T: DeserializeOwned>(self, service: Service) -> Result<T, DownstreamError> {
+        if !self.status.is_success() {
+            let body = String::from_utf8_lossy(&self.body).into_owned();
+            return Err(status_error(service, self.status, body));
+        }
+        self.json()
+            .map_err(|source| DownstreamError::Decode { service, source })
+    }
+}
+
+/// A 2xx response whose body is read as it arrives; the service's
+/// connection permit is held until the body stream is dropped
+pub struct DownstreamStream {
+    service: Service,
+    response: reqwest::Response,
+    permit: OwnedSemaphorePermit,
+    metrics: Arc<Metrics>,
+}
+
+impl DownstreamStream {
+    pub fn into_bytes(self) -> impl Stream<Item = Result<Bytes, DownstreamError>> {
+        let DownstreamStream {
+            service,
+            response,
+            permit,
+            metrics,
+        } = self;
+        let bytes_metric = format!("downstream_response_bytes_total.{}", service.name());
+        response.bytes_stream().map(move |chunk| {
+            let _held = &permit;
+            let chunk = chunk.map_err(|source| DownstreamError::Request { service, source })?;
+            metrics.add(&bytes_metric, chunk.len() as u64);
+            Ok(chunk)
+        })
+    }
+}
+
+pub struct Downstream {
+    client: reqwest::Client,
+    config: SharedConfig,
+    permits: HashMap<Service, Arc<Semaphore>>,
+    metrics: Arc<Metrics>,
+}
+
+impl Downstream {
+    /// Pool limits are fixed here; the allowlist is re-read from `shared` on
+    /// every request so /admin/reload can change it
+    pub fn new(shared: &SharedConfig, metrics: Arc<Metrics>) -> reqwest::Result<Self> {
+        let config = shared.load();
+        // Only the first URL is checked against the allowlist, so a redirect
+        // could otherwise carry a forwarded body to any host
+        let client = reqwest::Client::builder()
+            .pool_max_idle_per_host(config.downstream_pool_max_idle_per_host)
+            .pool_idle_timeout(config.downstream_pool_idle_timeout)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        let permits = Service::ALL
+            .into_iter()
+            .map(|service| {
+                let limit = config.downstream_max_connections_per_host.max(1);
+                (service, Arc::new(Semaphore::new(limit)))
+            })
+            .collect();
+
+        Ok(Self {
+            client,
+            config: shared.clone(),
+            permits,
+            metrics,
+        })
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Send a request to `service` and read the whole body while holding a
+    /// connection permit for that service, retrying transient failures up to
+    /// MAX_RETRIES times within the deadline and any pipeline retry budget
+    pub async fn execute(
+        &self,
+        service: Service,
+        request: reqwest::RequestBuilder,
+    ) -> Result<DownstreamResponse, DownstreamError> {
+        self.execute_retrying(service, request, || std::future::ready(None))
+            .await
+    }
+
+    /// [`Self::execute`] for requests with a streamed body, such as
+    /// multipart uploads, which can't be cloned for a retry: `rebuild` makes
+    /// a fresh one each time, or returns None to give up
+    pub async fn execute_retrying<F, Fut>(
+        &self,
+        service: Service,
+        request: reqwest::RequestBuilder,
+        mut rebuild: F,
+    ) -> Result<DownstreamResponse, DownstreamError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Option<reqwest::RequestBuilder>>,
+    {
+        let config = self.config.load();
+        let mut request = self.prepare(&config, service, request)?;
+        let mut backoff = config.retry_backoff;
+        let mut retries = 0;
+        let mut retry_started: Option<Instant> = None;
+        loop {
+            let cloned = (retries < config.max_retries)
+                .then(|| request.try_clone())
+                .flatten();
+            let result = self.send_within_deadline(service, request).await;
+            if let Some(started) = retry_started.take() {
+                retry::record(started.elapsed());
+            }
+            if retries >= config.max_retries || !is_transient(&result) {
+                return result;
+            }
+            if deadline::remaining().is_some_and(|remaining| remaining <= backoff) {
+                return result;
+            }
+            let next = match cloned {
+                Some(next) => next,
+                None => match rebuild().await {
+                    Some(next) => self.prepare(&config, service, next)?,
+                    None => return result,
+                },
+            };
+            if !retry::acquire(backoff) {
+                self.metrics.incr("retry_budget_exhausted_total");
+                log::warn!(
+                    "Pipeline retry budget exhausted; not retrying {} service request",
+                    service
+                );
+                return result;
+            }
+
+            retries += 1;
+            self.metrics.incr("downstream_retries_total");
+            log::warn!(
+                "Retrying {} service request in {}ms ({}/{}): {}",
+                service,
+                backoff.as_millis(),
+                retries,
+                config.max_retries,
+                match &result {
+                    Ok(response) => response.status.to_string(),
+                    Err(e) => e.to_string(),
+                }
+            );
+            retry_started = Some(Instant::now());
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+            request = next;
+        }
+    }
+
+    /// Build the request, check it against the allowlist and identify the
+    /// gateway on it
+    fn prepare(
+        &self,
+        config: &Config,
+        service: Service,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Request, DownstreamError> {
+        let mut request = request
+            .build()
+            .map_err(|source| DownstreamError::Request { service, source })?;
+        if !config.downstream_allowlist.permits(request.url()) {
+            self.metrics.incr("downstream_disallowed_total");
+            log::warn!(
+                "Rejected {} service request to non-allowlisted {}",
+                service,
+                request.url()
+            );
+            return Err(DownstreamError::Disallowed {
+                service,
+                url: request.url().to_string(),
+            });
+        }
+        identify(config, request.headers_mut());
+        forwarded::apply(request.headers_mut());
+        Ok(request)
+    }
+
+    /// One attempt, bounded by the request deadline when the client set one
+    async fn send_within_deadline(
+        &self,
+        service: Service,
+        mut request: reqwest::Request,
+    ) -> Result<DownstreamResponse, DownstreamError> {
+        let Some(remaining) = deadline::remaining() else {
+            return self.send(service, request).await;
+        };
+        if remaining.is_zero() {
+            return Err(DownstreamError::DeadlineExceeded { service });
+        }
+        if let Ok(value) = HeaderValue::from_str(&remaining.as_millis().to_string()) {
+            request
+                .headers_mut()
+                .insert(deadline::REQUEST_BUDGET_HEADER, value);
+        }
+        tokio::time::timeout(remaining, self.send(service, request))
+            .await
+            .map_err(|_| DownstreamError::DeadlineExceeded { service })?
+    }
+
+    async fn send(
+        &self,
+        service: Service,
+        request: reqwest::Request,
+    ) -> Result<DownstreamResponse, DownstreamError> {
+        let to_error = |source| DownstreamError::Request { service, source };
+        let (method, path) = (request.method().clone(), request.url().path().to_string());
+        let _permit = self.acquire(service).await;
+        let started = Instant::now();
+        let result = async {
+            let response = self.client.execute(request).await?;
+            let status = response.status();
+            Ok((status, response.bytes().await?))
+        }
+        .await;
+        let (status, size) = match &result {
+            Ok((status, body)) => (Some(*status), Some(body.len())),
+            Err(_) => (None, None),
+        };
+        self.record_call(service, &method, &path, status, size, started.elapsed());
+        let (status, body) = result.map_err(to_error)?;
+        Ok(DownstreamResponse { status, body })
+    }
+
+    /// Log and count one downstream call: status (None when no response
+    /// arrived), body size when known, and latency. Only the URL path is
+    /// logged, never query strings or bodies, which can carry case text.
+    fn record_call(
+        &self,
+        service: Service,
+        method: &Method,
+        path: &str,
+        status: Option<StatusCode>,
+        size: Option<usize>,
+        elapsed: Duration,
+    ) {
+        let name = service.name();
+        let elapsed_ms = elapsed.as_millis() as u64;
+        let status_label = status.map_or("error".to_string(), |s| s.as_u16().to_string());
+        self.metrics
+            .incr(&format!("downstream_calls_total.{}", name));
+        self.metrics.incr(&format!(
+            "downstream_responses_total.{}.{}",
+            name, status_label
+        ));
+        self.metrics
+            .add(&format!("downstream_latency_ms_total.{}", name), elapsed_ms);
+        if let Some(size) = size {
+            self.metrics.add(
+                &format!("downstream_response_bytes_total.{}", name),
+                size as u64,
+            );
+        }
+        if matches!(
+            status,
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+        ) {
+            self.metrics
+                .incr(&format!("downstream_auth_failures_total.{}", name));
+            log::warn!(
+                "{} service rejected the gateway's credentials ({}); check DOWNSTREAM_API_KEY",
+                name,
+                status_label
+            );
+        }
+        log::info!(
+            "downstream service={} {} {} status={} bytes={} {}ms request_id={}",
+            name,
+            method,
+            path,
+            status_label,
+            size.map_or("-".to_string(), |size| size.to_string()),
+            elapsed_ms,
+            request_id::current().map_or("-".to_string(), |id| id.0)
+        );
+    }
+
+    /// Send a request whose body is forwarded to the client as it arrives.
+    /// Not retried, since part of it may already be on its way; the request
+    /// deadline bounds only the wait for the response headers.
+    pub async fn open_stream(
+        &self,
+        service: Service,
+        request: reqwest::RequestBuilder,
+    ) -> Result<DownstreamStream, DownstreamError> {
+        let to_error = |source| DownstreamError::Request { service, source };
+        let config = self.config.load();
+        let mut request = self.prepare(&config, service, request)?;
+        let remaining = deadline::remaining();
+        if let Some(remaining) = remaining {
+            if remaining.is_zero() {
+                return Err(DownstreamError::DeadlineExceeded { service });
+            }
+            if let Ok(value) = HeaderValue::from_str(&remaining.as_millis().to_string()) {
+                request
+                    .headers_mut()
+                    .insert(deadline::REQUEST_BUDGET_HEADER, value);
+            }
+        }
+
+        let (method, path) = (request.method().clone(), request.url().path().to_string());
+        let permit = self.acquire(service).await;
+        let started = Instant::now();
+        let send = self.client.execute(request);
+        let response = match remaining {
+            Some(remaining) => tokio::time::timeout(remaining, send)
+                .await
+                .map_err(|_| DownstreamError::DeadlineExceeded { service })?,
+            None => send.await,
+        };
+        let status = response.as_ref().ok().map(reqwest::Response::status);
+        // The body is still to come, so only the time to headers is known
+        self.record_call(service, &method, &path, status, None, started.elapsed());
+        let response = response.map_err(to_error)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(status_error(service, status, body));
+        }
+        Ok(DownstreamStream {
+            service,
+            response,
+            permit,
+            metrics: self.metrics.clone(),
+        })
+    }
+
+    /// POST `body` as JSON to `path` on `service`, requiring a 2xx response
+    /// and decoding it as `T`
+    pub async fn post_json<B, T>(
+        &self,
+        config: &Config,
+        service: Service,
+        path: &str,
+        body: &B,
+    ) -> Result<T, DownstreamError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.post_json_at(service, service.base_url(config), path, body)
+            .await
+    }
+
+    /// [`Self::post_json`] to another instance of `service` at `base_url`,
+    /// such as one of several search indices
+    pub async fn post_json_at<B, T>(
+        &self,
+        service: Service,
+        base_url: &str,
+        path: &str,
+        body: &B,
+    ) -> Result<T, DownstreamError>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let url = format!("{}{}", base_url, path);
+        self.execute(service, self.client.post(url).json(body))
+            .await?
+            .decode(service)
+    }
+
+    /// GET `path` on `service`, requiring a 2xx response and decoding it as `T`
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        config: &Config,
+        service: Service,
+        path: &str,
+    ) -> Result<T, DownstreamError> {
+        let url = format!("{}{}", service.base_url(config), path);
+        self.execute(service, self.client.get(url))
+            .await?
+            .decode(service)
+    }
+
+    /// [`Self::get_json`] with `query` encoded as the query string
+    pub async fn get_json_with_query<Q, T>(
+        &self,
+        config: &Config,
+        service: Service,
+        path: &str,
+        query: &Q,
+    ) -> Result<T, DownstreamError>
+    where
+        Q: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let url = format!("{}{}", service.base_url(config), path);
+        self.execute(service, self.client.get(url).query(query))
+            .await?
+            .decode(service)
+    }
+
+    async fn acquire(&self, service: Service) -> OwnedSemaphorePermit {
+        let semaphore = self.permits[&service].clone();
+        if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+            return permit;
+        }
+
+        let started = Instant::now();
+        let permit = semaphore
+            .acquire_owned()
+            .await
+            .expect("downstream semaphore is never closed");
+        let waited_ms = started.elapsed().as_millis() as u64;
+
+        self.metrics.incr("downstream_pool_waits_total");
+        self.metrics.add(
+            &format!("downstream_pool_wait_ms_total.{}", service.name()),
+            waited_ms,
+        );
+        log::warn!(
+            "{} service connection pool exhausted; waited {}ms for a connection",
+            service.name(),
+            waited_ms
+        );
+        permit
+    }
+}
+
+/// The error for a non-2xx response
+fn status_error(service: Service, status: StatusCode, body: String) -> DownstreamError {
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        DownstreamError::CredentialsRejected { service, status }
+    } else {
+        DownstreamError::Status {
+            service,
+            status,
+            body,
+        }
+    }
+}
+
+/// Failures worth another attempt: the service was unreachable, too slow,
+/// or said so itself with 502/503/504
+fn is_transient(result: &Result<DownstreamResponse, DownstreamError>) -> bool {
+    match result {
+        Ok(response) => matches!(response.status.as_u16(), 502..=504),
+        Err(DownstreamError::Request { source, .. }) => source.is_connect() || source.is_timeout(),
+        Err(_) => false,
+    }
+}
+
+/// Set the gateway's User-Agent and, when configured, its API key as the
+/// bearer token the services' verify_token checks; read per request so a
+/// config reload takes effect without rebuilding the client
+fn identify(config: &Config, headers: &mut HeaderMap) {
+    // Both were checked as header values when the config was validated
+    if let Ok(value) = HeaderValue::from_str(&config.downstream_user_agent) {
+        headers.insert(USER_AGENT, value);
+    }
+    if config.downstream_api_key.is_empty() {
+        return;
+    }
+    let bearer = format!("Bearer {}", config.downstream_api_key);
+    if let Ok(mut value) = HeaderValue::from_str(&bearer) {
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+}
+
+/// Strip any user:password from a service URL before it is shown to clients
+pub fn redact_credentials(raw: &str) -> String {
+    match Url::parse(raw) {
+        Ok(mut url) => {
+            let _ = url.set_username("");
+            let _ = url.set_password(None);
+            url.to_string()
+        }
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
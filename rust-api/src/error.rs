@@ -0,0 +1,174 @@
+//! Client-facing errors, rendered as ErrorResponse JSON bodies
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::downstream::DownstreamError;
+use crate::models::{ErrorResponse, FieldViolation};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("no route for {method} {path}")]
+    RouteNotFound {
+        method: String,
+        path: String,
+        available: String,
+    },
+    #[error("method {method} is not allowed for {path}")]
+    MethodNotAllowed {
+        method: String,
+        path: String,
+        allowed: String,
+    },
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    PayloadTooLarge(String),
+    /// The client sent its body slower than REQUEST_BODY_TIMEOUT_SECONDS allows
+    #[error("{0}")]
+    RequestTimeout(String),
+    #[error("unsupported upload type {found}")]
+    UnsupportedMediaType { found: String, allowed: String },
+    /// The OCR service rejected the PDF itself, e.g. as encrypted or corrupt
+    #[error("document could not be processed")]
+    UnprocessableDocument { detail: String },
+    /// Some pages' OCR chunks failed and OCR_FAIL_ON_PARTIAL is on
+    #[error("OCR failed for {} of the document's pages", .failed_pages.len())]
+    IncompleteOcr { failed_pages: Vec<u32> },
+    /// Fewer supporting cases than MIN_SUPPORTING_CASES, in strict mode
+    #[error(
+        "prediction is backed by {found} supporting case(s); at least {required} are required"
+    )]
+    WeakSupport { found: usize, required: usize },
+    #[error("{0}")]
+    TooManyRequests(String),
+    #[error("{0}")]
+    ServiceUnavailable(String),
+    #[error("{what} failed validation")]
+    Validation {
+        what: &'static str,
+        violations: Vec<FieldViolation>,
+    },
+    #[error(transparent)]
+    Downstream(#[from] DownstreamError),
+    #[error("{operation} timed out after {seconds}s")]
+    Timeout {
+        operation: &'static str,
+        seconds: u64,
+        stage: Option<&'static str>,
+    },
+    #[error("request deadline exceeded")]
+    DeadlineExceeded,
+    #[error("failed to buffer upload: {0}")]
+    TempFile(#[source] std::io::Error),
+}
+
+impl ApiError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) | ApiError::RouteNotFound { .. } => StatusCode::NOT_FOUND,
+            ApiError::MethodNotAllowed { .. } => StatusCode::METHOD_NOT_ALLOWED,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::RequestTimeout(_) => StatusCode::REQUEST_TIMEOUT,
+            ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::UnsupportedMediaType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::Validation { .. }
+            | ApiError::UnprocessableDocument { .. }
+            | ApiError::WeakSupport { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Downstream(DownstreamError::DeadlineExceeded { .. }) => {
+                StatusCode::GATEWAY_TIMEOUT
+            }
+            ApiError::Downstream(_) | ApiError::IncompleteOcr { .. } => StatusCode::BAD_GATEWAY,
+            ApiError::Timeout { .. } | ApiError::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::TempFile(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn details(&self) -> Option<String> {
+        match self {
+            ApiError::Downstream(DownstreamError::Status { body, .. }) if !body.is_empty() => {
+                Some(body.clone())
+            }
+            ApiError::Downstream(DownstreamError::CredentialsRejected { .. }) => {
+                Some("gateway misconfigured: downstream rejected credentials".to_string())
+            }
+            ApiError::Downstream(DownstreamError::InvalidUtf8 { valid_up_to, .. }) => {
+                Some(format!("first invalid byte at offset {}", valid_up_to))
+            }
+            ApiError::Timeout {
+                stage: Some(stage), ..
+            } => Some(format!("active stage: {}", stage)),
+            ApiError::UnprocessableDocument { detail } if !detail.is_empty() => {
+                Some(detail.clone())
+            }
+            ApiError::IncompleteOcr { failed_pages } => {
+                Some(format!("failed pages: {}", page_list(failed_pages)))
+            }
+            ApiError::UnsupportedMediaType { allowed, .. } => {
+                Some(format!("allowed types: {}", allowed))
+            }
+            ApiError::RouteNotFound { available, .. } => {
+                Some(format!("available endpoints: {}", available))
+            }
+            ApiError::MethodNotAllowed { allowed, .. } => {
+                Some(format!("allowed methods: {}", allowed))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `[3, 4]` as `3, 4`
+pub fn page_list(pages: &[u32]) -> String {
+    pages
+        .iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl ApiError {
+    /// The status and body this error is reported with, logging server errors
+    pub fn into_error_response(self) -> (StatusCode, ErrorResponse) {
+        let status = self.status_code();
+        if status.is_server_error() {
+            log::error!("{}", self);
+        }
+        let details = self.details();
+        let error = self.to_string();
+        let violations = match self {
+            ApiError::Validation { violations, .. } => violations,
+            _ => Vec::new(),
+        };
+        let body = ErrorResponse {
+            status: "error".to_string(),
+            error,
+            details,
+            violations,
+        };
+        (status, body)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, body) = self.into_error_response();
+        (status, Json(body)).into_response()
+    }
+}
@@ -0,0 +1,68 @@
+//! Sparse fieldsets: `?fields=a,b` trims a response to those top-level fields
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::ApiError;
+
+/// Parse a comma-separated `fields` value, rejecting names not in `known`
+pub fn parse(raw: &str, known: &[&str]) -> Result<Vec<String>, ApiError> {
+    let fields: Vec<String> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(str::to_string)
+        .collect();
+    if fields.is_empty() {
+        return Err(ApiError::BadRequest(
+            "fields must name at least one field".to_string(),
+        ));
+    }
+    let unknown: Vec<&str> = fields
+        .iter()
+        .map(String::as_str)
+        .filter(|field| !known.contains(field))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown fields {:?}; expected any of {:?}",
+            unknown, known
+        )));
+    }
+    Ok(fields)
+}
+
+/// Serialize `value` keeping only `fields`. Requested fields the value
+/// omits (an unset optional) are simply absent.
+pub fn select<T: Serialize>(value: &T, fields: &[String]) -> Value {
+    let mut value = serde_json::to_value(value).expect("responses serialize to JSON");
+    if let Value::Object(map) = &mut value {
+        map.retain(|key, _| fields.iter().any(|field| field == key));
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const KNOWN: &[&str] = &["label", "score", "notes"];
+
+    #[test]
+    fn parses_trimmed_names_and_rejects_unknown_ones() {
+        assert_eq!(parse(" label, score ,", KNOWN).unwrap(), ["label", "score"]);
+        assert!(matches!(
+            parse("label,nope", KNOWN),
+            Err(ApiError::BadRequest(message)) if message.contains("nope")
+        ));
+        assert!(parse(" , ", KNOWN).is_err());
+    }
+
+    #[test]
+    fn keeps_only_requested_top_level_fields() {
+        let value = json!({ "label": "A", "score": 0.9, "notes": { "score": 1 } });
+        let fields = vec!["score".to_string()];
+        assert_eq!(select(&value, &fields), json!({ "score": 0.9 }));
+    }
+}
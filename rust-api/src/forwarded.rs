@@ -0,0 +1,92 @@
+//! Client request headers relayed to downstream services (FORWARD_HEADERS)
+//!
+//! Only allowlisted names are captured, so a client's Authorization or
+//! cookies never reach the Python services. The middleware scopes the
+//! captured headers to the request's task, the same way [`crate::deadline`]
+//! scopes its budget, and [`crate::downstream::Downstream`] adds them to
+//! every call it makes.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+// Held in reqwest's header types, which downstream requests are built with
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::future::Future;
+
+use crate::state::AppState;
+
+/// Names FORWARD_HEADERS may not list: credentials and hop-by-hop headers
+/// that would either leak auth or break the downstream connection
+pub const NEVER_FORWARDED: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "host",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "te",
+    "trailer",
+    "upgrade",
+    "content-length",
+    "content-type",
+];
+
+tokio::task_local! {
+    static FORWARDED: HeaderMap;
+}
+
+/// The headers captured from the request whose task is running, if any
+pub fn current() -> Option<HeaderMap> {
+    FORWARDED.try_with(HeaderMap::clone).ok()
+}
+
+/// Run `future` with `headers` as [`current`], for work spawned off a request
+pub async fn scope<F: Future>(headers: HeaderMap, future: F) -> F::Output {
+    FORWARDED.scope(headers, future).await
+}
+
+/// Add the captured headers to an outgoing request, leaving any the gateway
+/// already set (User-Agent, Authorization, the request budget) untouched
+pub fn apply(headers: &mut HeaderMap) {
+    let _ = FORWARDED.try_with(|forwarded| {
+        for name in forwarded.keys() {
+            if headers.contains_key(name) {
+                continue;
+            }
+            for value in forwarded.get_all(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+    });
+}
+
+/// Middleware that captures the allowlisted headers for downstream calls
+pub async fn capture_headers(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = state.config();
+    let names = &config.forward_headers;
+    if names.is_empty() {
+        return next.run(request).await;
+    }
+    let mut captured = HeaderMap::new();
+    for name in names {
+        // Every name was checked as a header name when the config was validated
+        let Ok(name) = HeaderName::from_bytes(name.as_bytes()) else {
+            continue;
+        };
+        for value in request.headers().get_all(name.as_str()) {
+            if let Ok(value) = HeaderValue::from_bytes(value.as_bytes()) {
+                captured.append(name.clone(), value);
+            }
+        }
+    }
+    FORWARDED.scope(captured, next.run(request)).await
+}
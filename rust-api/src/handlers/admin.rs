@@ -0,0 +1,90 @@
+use axum::extract::State;
+use serde::Serialize;
+
+use crate::auth::Principal;
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct ReloadResponse {
+    pub reloaded: bool,
+    /// Changed settings that are only read at startup and still need a restart
+    pub restart_required: Vec<&'static str>,
+}
+
+/// Re-read CONFIG_FILE (falling back to the process environment), validate
+/// it, and swap it in. On any error the running config is left untouched.
+pub async fn reload_config(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Principal,
+) -> Result<ApiJson<ReloadResponse>, ApiError> {
+    principal.require_scope("admin")?;
+
+    let current = state.config();
+    let path = std::env::var("CONFIG_FILE").unwrap_or_else(|_| ".env".to_string());
+    let next = if std::path::Path::new(&path).exists() {
+        Config::from_env_file(&path)
+    } else {
+        Config::from_env()
+    }
+    .map_err(|e| ApiError::BadRequest(format!("config reload rejected: {}", e)))?;
+
+    let restart_required = restart_only_changes(&current, &next);
+    state.config.store(next);
+    log::info!(
+        "Configuration reloaded by {} (restart still required for: {:?})",
+        principal.name,
+        restart_required
+    );
+
+    let response = ReloadResponse {
+        reloaded: true,
+        restart_required,
+    };
+    Ok(ApiJson::new(&state, request_id, response))
+}
+
+/// Settings baked into the listener, router or HTTP client at startup
+fn restart_only_changes(current: &Config, next: &Config) -> Vec<&'static str> {
+    let checks = [
+        ("RUST_API_PORT", current.port != next.port),
+        ("API_PREFIX", current.api_prefix != next.api_prefix),
+        (
+            "RUNTIME_WORKER_THREADS",
+            current.runtime_worker_threads != next.runtime_worker_threads,
+        ),
+        (
+            "RUNTIME_MAX_BLOCKING_THREADS",
+            current.runtime_max_blocking_threads != next.runtime_max_blocking_threads,
+        ),
+        (
+            "MAX_UPLOAD_BYTES",
+            current.max_upload_bytes != next.max_upload_bytes,
+        ),
+        (
+            "MAX_JSON_BODY_BYTES",
+            current.max_json_body_bytes != next.max_json_body_bytes,
+        ),
+        (
+            "DOWNSTREAM_POOL_MAX_IDLE_PER_HOST",
+            current.downstream_pool_max_idle_per_host != next.downstream_pool_max_idle_per_host,
+        ),
+        (
+            "DOWNSTREAM_POOL_IDLE_TIMEOUT_SECONDS",
+            current.downstream_pool_idle_timeout != next.downstream_pool_idle_timeout,
+        ),
+        (
+            "DOWNSTREAM_MAX_CONNECTIONS_PER_HOST",
+            current.downstream_max_connections_per_host != next.downstream_max_connections_per_host,
+        ),
+    ];
+    checks
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(key, _)| key)
+        .collect()
+}
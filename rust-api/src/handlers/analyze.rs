@@ -0,0 +1,916 @@
+use axum::{
+    body::Bytes,
+    extract::{Multipart, Path, Query, State},
+    http::{
+        header::{ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_RANGE, CONTENT_TYPE, RANGE},
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use futures::future;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::auth::Principal;
+use crate::callback;
+use crate::citation::Citation;
+use crate::concurrency::Admission;
+use crate::downstream::{self, DownstreamError};
+use crate::error::{self, ApiError};
+use crate::fields;
+use crate::forwarded;
+use crate::handlers::{opinion, search};
+use crate::history::AnalysisSummary;
+use crate::injection;
+use crate::jobs::{JobStatus, JobView};
+use crate::json::JsonBody;
+use crate::mime;
+use crate::models::{
+    AnalyzeOutcome, AnalyzeResponse, AnalyzeUrlRequest, CaseContext, CaseResult, OpinionRequest,
+    OpinionResponse, SearchRequest, SimilarityScore,
+};
+use crate::ocr;
+use crate::pdf;
+use crate::pipeline::{Stage, StageTracker};
+use crate::range::{self, RangeRequest};
+use crate::remote;
+use crate::report;
+use crate::request_id::{self, RequestId};
+use crate::response::ApiJson;
+use crate::retry::{self, RetryBudget};
+use crate::state::AppState;
+use crate::timestamp;
+use crate::upload::{self, Upload};
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyzeParams {
+    /// `sync` (default) or `async`
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Comma-separated AnalyzeResponse fields to return; all when absent
+    #[serde(default)]
+    pub fields: Option<String>,
+}
+
+/// What AnalyzeParams asked for, checked before any work starts
+struct AnalyzeOptions {
+    asynchronous: bool,
+    fields: Option<Vec<String>>,
+}
+
+/// The parsed multipart form
+pub struct Submission {
+    pdf: Upload,
+    lang: Option<String>,
+    callback_url: Option<String>,
+    exclude_cited: bool,
+    candidate_document_ids: Vec<String>,
+}
+
+impl AnalyzeParams {
+    fn options(&self) -> Result<AnalyzeOptions, ApiError> {
+        let asynchronous = match self.mode.as_deref() {
+            None | Some("sync") => false,
+            Some("async") => true,
+            Some(other) => {
+                return Err(ApiError::BadRequest(format!(
+                    "mode must be \"sync\" or \"async\", got {:?}",
+                    other
+                )))
+            }
+        };
+        let fields = self
+            .fields
+            .as_deref()
+            .map(|raw| fields::parse(raw, &AnalyzeResponse::FIELDS))
+            .transpose()?;
+        if asynchronous && fields.is_some() {
+            return Err(ApiError::BadRequest(
+                "fields requires mode=sync; async job results are returned whole".to_string(),
+            ));
+        }
+        Ok(AnalyzeOptions {
+            asynchronous,
+            fields,
+        })
+    }
+}
+
+pub async fn analyze_brief(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Option<Principal>,
+    Query(params): Query<AnalyzeParams>,
+    multipart: Multipart,
+) -> Result<Response, ApiError> {
+    log::info!("Received analysis request...");
+    let options = params.options()?;
+    let submission = read_submission(&state, multipart);
+    analyze(&state, request_id, principal, options, submission).await
+}
+
+/// Analyze a document the gateway downloads itself, so clients needn't
+/// proxy large files hosted elsewhere
+pub async fn analyze_url(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Option<Principal>,
+    Query(params): Query<AnalyzeParams>,
+    JsonBody(request): JsonBody<AnalyzeUrlRequest>,
+) -> Result<Response, ApiError> {
+    log::info!("Received remote analysis request...");
+    let options = params.options()?;
+    let config = state.config();
+    let url = remote::validate_url(&config, &request.url)?;
+    if let Some(lang) = &request.lang {
+        if !ocr::is_valid_language(lang) {
+            return Err(ApiError::BadRequest(format!(
+                "Invalid OCR language: {:?}",
+                lang
+            )));
+        }
+    }
+    let candidate_document_ids = candidate_ids(&state, request.candidate_document_ids)?;
+    let submission = async move {
+        Ok(Submission {
+            pdf: remote::fetch(&config, url).await?,
+            lang: request.lang,
+            callback_url: request.callback_url,
+            exclude_cited: request.exclude_cited,
+            candidate_document_ids,
+        })
+    };
+    analyze(&state, request_id, principal, options, submission).await
+}
+
+/// OCR engines fail on encrypted PDFs with errors that don't say why, so
+/// catch them before anything is forwarded
+async fn reject_encrypted(pdf: &Upload) -> Result<(), ApiError> {
+    if pdf::is_encrypted(pdf).await.map_err(ApiError::TempFile)? {
+        return Err(ApiError::UnprocessableDocument {
+            detail: "document is password-protected; remove the password and upload it again"
+                .to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Run the pipeline on the document `submission` yields, inline or as a job
+async fn analyze(
+    state: &AppState,
+    request_id: RequestId,
+    principal: Option<Principal>,
+    options: AnalyzeOptions,
+    submission: impl Future<Output = Result<Submission, ApiError>>,
+) -> Result<Response, ApiError> {
+    // Held until the analysis finishes, in the background job for async mode
+    let tenant = match state.tenants.try_acquire(principal.as_ref()) {
+        Ok(permit) => permit,
+        Err(e) => {
+            state.metrics.incr("tenant_concurrency_rejections_total");
+            return Err(e);
+        }
+    };
+    let config = state.config();
+    let pipeline = state
+        .pipeline_queue
+        .acquire(
+            config.pipeline_max_concurrent,
+            config.pipeline_max_queue,
+            config.pipeline_queue_timeout,
+            &state.metrics,
+        )
+        .await?;
+    let admission = Admission::new(tenant, pipeline);
+
+    let submission = async {
+        let submission = submission.await?;
+        reject_encrypted(&submission.pdf).await?;
+        Ok::<_, ApiError>(submission)
+    };
+    if options.asynchronous {
+        let submission = submission.await?;
+        let owner = principal.map(|principal| principal.name);
+        let job = submit_job(state, submission, admission, owner)?;
+        // The result is polled separately; its body carries the mock flag
+        let response = ApiJson::new(state, request_id, job);
+        return Ok((StatusCode::ACCEPTED, response).into_response());
+    }
+
+    // Dropping the pipeline future on timeout cancels whatever downstream
+    // calls are in flight; nothing in it is spawned, so nothing is leaked
+    let stage = StageTracker::default();
+    let total_timeout = state.config().analyze_total_timeout;
+    let pipeline = async {
+        let submission = submission.await?;
+        if submission.callback_url.is_some() {
+            return Err(ApiError::BadRequest(
+                "callback_url requires mode=async".to_string(),
+            ));
+        }
+        run_analysis(state, &stage, submission).await
+    };
+    let response = tokio::time::timeout(total_timeout, pipeline)
+        .await
+        .map_err(|_| ApiError::Timeout {
+            operation: "analysis",
+            seconds: total_timeout.as_secs(),
+            stage: Some(stage.current().name()),
+        })??;
+    drop(admission);
+    if let Some(principal) = &principal {
+        let summary = AnalysisSummary::new(request_id.0.clone(), false, &response);
+        record_history(state, &principal.name, summary);
+    }
+
+    let mock = response.mock;
+    let response = match &options.fields {
+        Some(fields) => ApiJson::new(state, request_id, fields::select(&response, fields))
+            .mock(mock)
+            .into_response(),
+        None => ApiJson::new(state, request_id, response)
+            .mock(mock)
+            .into_response(),
+    };
+    Ok(response)
+}
+
+/// The caller's recent analyses, newest first
+pub async fn analysis_history(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Principal,
+) -> ApiJson<Vec<AnalysisSummary>> {
+    let retention = state.config().analyze_job_retention;
+    let recent = state.history.recent(&principal.name, retention);
+    ApiJson::new(&state, request_id, recent)
+}
+
+fn record_history(state: &AppState, owner: &str, summary: AnalysisSummary) {
+    let config = state.config();
+    state.history.record(
+        owner,
+        summary,
+        config.analyze_history_size,
+        config.analyze_job_retention,
+    );
+}
+
+/// Poll an async analysis job; another principal's job is a 404
+pub async fn analysis_job(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Option<Principal>,
+    Path(job_id): Path<String>,
+) -> Result<ApiJson<JobView>, ApiError> {
+    let caller = principal.as_ref().map(|principal| principal.name.as_str());
+    let job = state
+        .jobs
+        .get(&job_id, caller)
+        .ok_or_else(|| ApiError::NotFound(format!("No analysis job {}", job_id)))?;
+    let mock = job.result.as_ref().is_some_and(|result| result.mock);
+    Ok(ApiJson::new(&state, request_id, job).mock(mock))
+}
+
+/// Cancel an async analysis job: 202 when this stopped it, 200 with the
+/// job unchanged when it had already finished, 404 for another
+/// principal's job
+pub async fn cancel_analysis_job(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Option<Principal>,
+    Path(job_id): Path<String>,
+) -> Result<(StatusCode, ApiJson<JobView>), ApiError> {
+    let caller = principal.as_ref().map(|principal| principal.name.as_str());
+    let (job, cancelled) = state
+        .jobs
+        .cancel(&job_id, caller)
+        .ok_or_else(|| ApiError::NotFound(format!("No analysis job {}", job_id)))?;
+    if !cancelled {
+        return Ok((StatusCode::OK, ApiJson::new(&state, request_id, job)));
+    }
+    state.metrics.incr("analyze_jobs_cancelled_total");
+    Ok((StatusCode::ACCEPTED, ApiJson::new(&state, request_id, job)))
+}
+
+/// A completed job's analysis as a PDF report for sharing
+pub async fn analysis_report(
+    State(state): State<AppState>,
+    principal: Option<Principal>,
+    Path(job_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let caller = principal.as_ref().map(|principal| principal.name.as_str());
+    let job = state
+        .jobs
+        .get(&job_id, caller)
+        .ok_or_else(|| ApiError::NotFound(format!("No analysis job {}", job_id)))?;
+    let analysis = match (job.status, &job.result) {
+        (JobStatus::Completed, Some(analysis)) => analysis,
+        (JobStatus::Failed, _) => {
+            return Err(ApiError::Conflict(format!(
+                "Analysis job {} failed, so there is no report",
+                job_id
+            )))
+        }
+        (JobStatus::Cancelled, _) => {
+            return Err(ApiError::Conflict(format!(
+                "Analysis job {} was cancelled, so there is no report",
+                job_id
+            )))
+        }
+        _ => {
+            return Err(ApiError::Conflict(format!(
+                "Analysis job {} has not finished; poll GET /api/analyze-brief/{} first",
+                job_id, job_id
+            )))
+        }
+    };
+    let pdf = report::render(&job.job_id, &timestamp::now_rfc3339(), analysis);
+    Ok((
+        [
+            (CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"analysis-{}.pdf\"", job.job_id),
+            ),
+        ],
+        pdf,
+    )
+        .into_response())
+}
+
+/// Queue the analysis in the background; the result is kept in the job
+/// store and, when a callback_url was given, POSTed there
+fn submit_job(
+    state: &AppState,
+    submission: Submission,
+    admission: Admission,
+    owner: Option<String>,
+) -> Result<JobView, ApiError> {
+    let config = state.config();
+    let callback_url: Option<Url> = submission
+        .callback_url
+        .as_deref()
+        .map(|raw| callback::validate_url(&config, raw))
+        .transpose()?;
+    let (job, cancel) = state
+        .jobs
+        .create(config.analyze_job_retention, owner.clone());
+
+    let state = state.clone();
+    let job_id = job.job_id.clone();
+    let work = async move {
+        state.jobs.mark_running(&job_id);
+        let stage = StageTracker::default();
+        let total_timeout = state.config().analyze_total_timeout;
+        let analysis =
+            tokio::time::timeout(total_timeout, run_analysis(&state, &stage, submission));
+        // Dropping the analysis abandons its in-flight downstream calls and
+        // frees its admission slot
+        let outcome = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                log::info!("Analysis job {} cancelled during {}", job_id, stage.current().name());
+                return;
+            }
+            outcome = analysis => outcome,
+        };
+        let outcome = outcome
+            .unwrap_or_else(|_| {
+                Err(ApiError::Timeout {
+                    operation: "analysis",
+                    seconds: total_timeout.as_secs(),
+                    stage: Some(stage.current().name()),
+                })
+            })
+            .map_err(|e| e.into_error_response().1);
+        drop(admission);
+        // Recorded first so the history lists a job as soon as polling
+        // shows it completed
+        if let (Some(owner), Ok(analysis)) = (&owner, &outcome) {
+            let summary = AnalysisSummary::new(job_id.clone(), true, analysis);
+            record_history(&state, owner, summary);
+        }
+
+        let Some(finished) = state.jobs.finish(&job_id, outcome) else {
+            return;
+        };
+        if let Some(url) = callback_url {
+            callback::deliver(&state, url, &finished).await;
+        }
+    };
+    // Keep the submitting request's ID and forwarded headers on the job's
+    // downstream calls
+    let work = forwarded::scope(forwarded::current().unwrap_or_default(), work);
+    match request_id::current() {
+        Some(request_id) => tokio::spawn(request_id::scope(request_id, work)),
+        None => tokio::spawn(work),
+    };
+    Ok(job)
+}
+
+async fn read_submission(
+    state: &AppState,
+    mut multipart: Multipart,
+) -> Result<Submission, ApiError> {
+    // 1. Extract PDF from multipart
+    let config = state.config();
+    let mut pdf_bytes = None;
+    let mut lang = None;
+    let mut callback_url = None;
+    let mut exclude_cited = false;
+    let mut candidate_document_ids = Vec::new();
+    let mut field_count = 0;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(upload::multipart_error)?
+    {
+        field_count += 1;
+        if field_count > config.max_multipart_fields {
+            return Err(ApiError::BadRequest(format!(
+                "Too many multipart fields; at most {} are accepted",
+                config.max_multipart_fields
+            )));
+        }
+        let upload_field = field
+            .name()
+            .filter(|name| config.upload_field_names.iter().any(|n| n == name));
+        if let Some(name) = upload_field {
+            // Until multi-document analysis exists, guessing which one the
+            // client meant would risk analyzing the wrong filing
+            if pdf_bytes.is_some() {
+                return Err(ApiError::BadRequest(format!(
+                    "Only one document is accepted per request; got a second {:?} field",
+                    name
+                )));
+            }
+            let file_name = field.file_name().and_then(upload::safe_file_name);
+            let allowed = &config.upload_allowed_mime_types;
+            let declared = mime::declared_type(field.content_type());
+            mime::check_declared(declared.as_deref(), allowed)?;
+            let upload = upload::read_field(field, config.upload_spool_threshold_bytes).await?;
+            if !upload.is_empty() {
+                let prefix = upload
+                    .prefix(mime::SNIFF_LEN)
+                    .await
+                    .map_err(ApiError::TempFile)?;
+                mime::check_content(&prefix, declared.as_deref(), allowed)?;
+            }
+            log::info!(
+                "Got PDF bytes: {} bytes from {}",
+                upload.len(),
+                file_name.as_deref().unwrap_or("an unnamed upload")
+            );
+            pdf_bytes = Some(upload);
+            continue;
+        }
+        match field.name() {
+            Some("lang") => {
+                let value = field.text().await.unwrap_or_default();
+                if !ocr::is_valid_language(&value) {
+                    return Err(ApiError::BadRequest(format!(
+                        "Invalid OCR language: {:?}",
+                        value
+                    )));
+                }
+                lang = Some(value);
+            }
+            Some("callback_url") => {
+                callback_url = Some(field.text().await.map_err(upload::multipart_error)?);
+            }
+            Some("exclude_cited") => {
+                let value = field.text().await.map_err(upload::multipart_error)?;
+                exclude_cited = value.trim().parse().map_err(|_| {
+                    ApiError::BadRequest(format!(
+                        "exclude_cited must be \"true\" or \"false\", got {:?}",
+                        value
+                    ))
+                })?;
+            }
+            // Comma-separated, and the field may repeat
+            Some("candidate_document_ids") => {
+                let value = field.text().await.map_err(upload::multipart_error)?;
+                candidate_document_ids.extend(value.split(',').map(|id| id.trim().to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    let pdf_bytes = match pdf_bytes {
+        Some(upload) if !upload.is_empty() => upload,
+        _ => {
+            return Err(ApiError::BadRequest(format!(
+                "No file uploaded; expected a multipart field named {}",
+                config.upload_field_names.join(" or ")
+            )))
+        }
+    };
+    Ok(Submission {
+        pdf: pdf_bytes,
+        lang,
+        callback_url,
+        exclude_cited,
+        candidate_document_ids: candidate_ids(state, candidate_document_ids)?,
+    })
+}
+
+/// Candidate ids with blanks and repeats dropped, each checked for shape;
+/// at most MAX_TOP_K, since they are ranked in one search
+fn candidate_ids(state: &AppState, raw: Vec<String>) -> Result<Vec<String>, ApiError> {
+    let mut seen = HashSet::new();
+    let ids: Vec<String> = raw
+        .into_iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty() && seen.insert(id.clone()))
+        .collect();
+    for id in &ids {
+        search::check_document_id(id)?;
+    }
+    let max = state.config().max_top_k as usize;
+    if ids.len() > max {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} candidate_document_ids are accepted, got {}",
+            max,
+            ids.len()
+        )));
+    }
+    Ok(ids)
+}
+
+/// Run the pipeline with one retry budget shared by all of its downstream
+/// calls, reporting what it used under `metadata.retry_budget`
+async fn run_analysis(
+    state: &AppState,
+    stage: &StageTracker,
+    submission: Submission,
+) -> Result<AnalyzeResponse, ApiError> {
+    let budget = Arc::new(RetryBudget::new(&state.config()));
+    let mut response =
+        retry::scope(budget.clone(), analyze_document(state, stage, submission)).await?;
+    response
+        .metadata
+        .insert("retry_budget".to_string(), json!(budget.usage()));
+    Ok(response)
+}
+
+async fn analyze_document(
+    state: &AppState,
+    stage: &StageTracker,
+    submission: Submission,
+) -> Result<AnalyzeResponse, ApiError> {
+    let Submission {
+        pdf: pdf_bytes,
+        lang,
+        exclude_cited,
+        candidate_document_ids,
+        ..
+    } = submission;
+
+    // 2. Call Python OCR Service
+    stage.enter(Stage::LanguageDetection);
+    let language = ocr::choose_language(state, &pdf_bytes, lang).await;
+    let mut metadata = HashMap::from([
+        ("ocr_language".to_string(), json!(language.language)),
+        ("ocr_language_source".to_string(), json!(language.source)),
+    ]);
+    if let Some(confidence) = language.confidence {
+        metadata.insert(
+            "detected_language_confidence".to_string(),
+            json!(confidence),
+        );
+    }
+
+    log::info!("Sending to OCR service...");
+    stage.enter(Stage::Ocr);
+    let mut ocr_text_id = None;
+    let mut pages = Vec::new();
+    let mut failed_pages = Vec::new();
+    // Mocking response for now if OCR is down
+    let ocr_text = match ocr::extract(state, &pdf_bytes, Some(language.language)).await {
+        Ok(extraction) => {
+            metadata.insert("ocr_chunking".to_string(), json!(extraction.strategy));
+            metadata.insert("ocr".to_string(), json!(extraction.quality()));
+            metadata.insert(
+                "ocr_engine".to_string(),
+                json!(downstream::redact_credentials(&extraction.engine)),
+            );
+            if let Some(raw_text) = &extraction.raw_text {
+                metadata.insert("ocr_raw_text".to_string(), json!(preview(raw_text)));
+            }
+            if extraction.replacement_chars > 0 {
+                metadata.insert(
+                    "ocr_replacement_chars".to_string(),
+                    json!(extraction.replacement_chars),
+                );
+            }
+            pages = extraction.pages;
+            failed_pages = extraction.failed_pages;
+            match extraction.full_text {
+                Some(text) => {
+                    ocr_text_id = store_ocr_text(state, &text);
+                    text
+                }
+                None => "No text returned".to_string(),
+            }
+        }
+        Err(ApiError::Downstream(DownstreamError::Decode { .. })) => {
+            "OCR Failed to parse JSON".to_string()
+        }
+        Err(e @ ApiError::TempFile(_))
+        | Err(e @ ApiError::UnprocessableDocument { .. })
+        | Err(e @ ApiError::PayloadTooLarge(_))
+        | Err(e @ ApiError::IncompleteOcr { .. })
+        | Err(e @ ApiError::Downstream(DownstreamError::InvalidUtf8 { .. }))
+        | Err(e @ ApiError::Downstream(DownstreamError::CredentialsRejected { .. }))
+        | Err(e @ ApiError::Downstream(DownstreamError::DeadlineExceeded { .. })) => return Err(e),
+        Err(e) => {
+            log::error!("OCR Service Error: {}", e);
+            "Error contacting OCR service (Is it running?). Using mock text.".to_string()
+        }
+    };
+
+    log::info!("OCR Complete. Length: {}", ocr_text.len());
+
+    // 3. (Todo) Vector Search & Prediction
+    // Returning dummy data for Phase 2A demo, flagged as mock so it can't
+    // be mistaken for a real analysis
+    let mut top_cases = vec![
+        CaseResult {
+            case_name: "Hilder v. St. Peter".to_string(),
+            citation: "478 A.2d 202 (Vt. 1984)".to_string(),
+            relevance_score: SimilarityScore::new(0.92),
+            snippet: "Implied warranty of habitability exists in every residential lease..."
+                .to_string(),
+        },
+        CaseResult {
+            case_name: "Javins v. First National Realty".to_string(),
+            citation: "428 F.2d 1071".to_string(),
+            relevance_score: SimilarityScore::new(0.88),
+            snippet: "Leases of urban dwellings contain implied warranty...".to_string(),
+        },
+    ];
+    let mut warnings = Vec::new();
+    if !failed_pages.is_empty() {
+        state.metrics.incr("ocr_partial_extractions_total");
+        warnings.push(format!(
+            "OCR failed for pages {}; the analysis is based on the remaining pages",
+            error::page_list(&failed_pages)
+        ));
+    }
+    if !candidate_document_ids.is_empty() {
+        let candidates = rank_candidates(state, &ocr_text, &candidate_document_ids).await;
+        metadata.insert(
+            "candidate_document_ids".to_string(),
+            json!(candidates.ranked),
+        );
+        if !candidates.skipped.is_empty() {
+            metadata.insert(
+                "skipped_candidate_document_ids".to_string(),
+                json!(candidates.skipped),
+            );
+        }
+        warnings.extend(candidates.warnings);
+        top_cases = candidates.cases;
+    }
+    if exclude_cited {
+        let excluded = remove_cited(&mut top_cases, &ocr_text);
+        metadata.insert("excluded_cited_cases".to_string(), json!(excluded));
+    }
+
+    // 4. Judge opinion, optional under ANALYZE_OPINION_FAIL_OPEN
+    stage.enter(Stage::Opinion);
+    let opinion_text: Cow<str> = if state.config().analyze_sanitize_prompt_injection {
+        let (sanitized, detections) = injection::sanitize(&ocr_text);
+        if !detections.is_empty() {
+            log::warn!(
+                "Neutralized {} possible prompt injection(s) in the brief",
+                detections.len()
+            );
+            state
+                .metrics
+                .add("prompt_injection_detections_total", detections.len() as u64);
+            metadata.insert("prompt_injection".to_string(), json!(detections));
+        }
+        Cow::Owned(sanitized)
+    } else {
+        Cow::Borrowed(&ocr_text)
+    };
+    let judge_opinion = match draft_opinion(state, &opinion_text).await {
+        Ok(opinion) => {
+            metadata.insert("opinion_id".to_string(), json!(opinion.opinion_id));
+            opinion.opinion.full_text
+        }
+        Err(e) if state.config().analyze_opinion_fail_open => {
+            log::warn!(
+                "Opinion generation failed; returning analysis without it: {}",
+                e
+            );
+            state.metrics.incr("analyze_opinion_failures_total");
+            warnings.push(format!(
+                "Opinion generation failed ({}); judge_opinion is empty",
+                e
+            ));
+            String::new()
+        }
+        Err(e) => return Err(e),
+    };
+
+    let response = AnalyzeResponse {
+        ocr_text: preview(&ocr_text),
+        ocr_text_id,
+        pages,
+        failed_pages,
+        predicted_outcome: AnalyzeOutcome {
+            label: "PLAINTIFF_WINS".to_string(),
+            probabilities: BTreeMap::from([
+                ("PLAINTIFF_WINS".to_string(), 0.85),
+                ("DEFENDANT_WINS".to_string(), 0.10),
+                ("MIXED".to_string(), 0.05),
+            ]),
+        },
+        top_cases,
+        judge_opinion,
+        warnings,
+        metadata,
+        mock: true,
+    };
+
+    Ok(response)
+}
+
+/// top_cases drawn only from a caller's candidate list
+struct Candidates {
+    cases: Vec<CaseResult>,
+    /// Ids that were found, in the order of `cases`
+    ranked: Vec<String>,
+    /// Ids the ingestion service doesn't know or couldn't return
+    skipped: Vec<String>,
+    warnings: Vec<String>,
+}
+
+/// Look up each candidate, skipping unknown ones with a warning, and rank
+/// them by their best section score in a search on the brief restricted
+/// to them. Candidates the search doesn't return are kept, scored 0.
+async fn rank_candidates(state: &AppState, text: &str, ids: &[String]) -> Candidates {
+    let lookups = future::join_all(ids.iter().map(|id| search::fetch_document(state, id))).await;
+    let mut warnings = Vec::new();
+    let mut skipped = Vec::new();
+    let mut documents = Vec::new();
+    for (id, lookup) in ids.iter().zip(lookups) {
+        match lookup {
+            Ok(document) => documents.push((id.clone(), document)),
+            Err(e) => {
+                let reason = match e {
+                    ApiError::NotFound(_) => "not found".to_string(),
+                    e => e.to_string(),
+                };
+                warnings.push(format!("Candidate document {} skipped: {}", id, reason));
+                skipped.push(id.clone());
+            }
+        }
+    }
+
+    let found: Vec<String> = documents.iter().map(|(id, _)| id.clone()).collect();
+    let mut best: HashMap<String, (SimilarityScore, String)> = HashMap::new();
+    if !found.is_empty() {
+        let request = SearchRequest::new(search::facts_query(text, ""))
+            .with_top_k(state.config().max_top_k)
+            .with_min_similarity(0.0)
+            .with_document_ids(found);
+        match search::search_cases(state, &request).await {
+            Ok(response) => {
+                for result in response.results {
+                    let Some(id) = search::result_document_id(&result).map(str::to_string) else {
+                        continue;
+                    };
+                    let better = !best
+                        .get(&id)
+                        .is_some_and(|(score, _)| *score >= result.similarity_score);
+                    if better {
+                        best.insert(id, (result.similarity_score, result.snippet));
+                    }
+                }
+            }
+            Err(e) => {
+                log::warn!("Candidate ranking search failed: {}", e);
+                warnings.push(format!(
+                    "Ranking the candidate documents failed ({}); they are listed unscored",
+                    e
+                ));
+            }
+        }
+    }
+
+    let mut ranked: Vec<(String, CaseResult)> = documents
+        .into_iter()
+        .map(|(id, document)| {
+            let (relevance_score, snippet) = best.remove(&id).unwrap_or_else(|| {
+                let snippet = document.holding.chars().take(200).collect();
+                (SimilarityScore::default(), snippet)
+            });
+            let citation = document
+                .citation
+                .clone()
+                .unwrap_or_else(|| format!("{} {}", document.court, document.year));
+            let case = CaseResult {
+                case_name: document.case_name,
+                citation,
+                relevance_score,
+                snippet,
+            };
+            (id, case)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.relevance_score.total_cmp(&a.1.relevance_score));
+    let (ranked, cases) = ranked.into_iter().unzip();
+    Candidates {
+        cases,
+        ranked,
+        skipped,
+        warnings,
+    }
+}
+
+/// Opinion on the brief as OCR'd; nothing structured (parties, issue) has
+/// been extracted from it yet, so the text stands in as the facts
+async fn draft_opinion(state: &AppState, ocr_text: &str) -> Result<OpinionResponse, ApiError> {
+    let request = OpinionRequest::new(CaseContext {
+        case_number: String::new(),
+        petitioner: String::new(),
+        respondent: String::new(),
+        lower_court: String::new(),
+        facts: ocr_text.to_string(),
+        issue: String::new(),
+        procedural_history: None,
+    });
+    opinion::generate(state, request, None).await
+}
+
+/// Drop the cases whose citation appears in `text`, returning how many went.
+/// Cases without a parseable citation are kept.
+fn remove_cited(cases: &mut Vec<CaseResult>, text: &str) -> usize {
+    let cited: HashSet<String> = Citation::find_all(text).iter().map(Citation::key).collect();
+    let before = cases.len();
+    cases.retain(|case| match case.citation.parse::<Citation>() {
+        Ok(citation) => !cited.contains(&citation.key()),
+        Err(_) => true,
+    });
+    before - cases.len()
+}
+
+/// Keep the full text for GET /api/ocr-text/:id, returning its ID
+fn store_ocr_text(state: &AppState, text: &str) -> Option<String> {
+    let retention = state.config().ocr_text_retention;
+    if retention.is_zero() {
+        return None;
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    state
+        .ocr_texts
+        .insert(id.clone(), Bytes::from(text.to_string()), retention);
+    Some(id)
+}
+
+/// The full OCR text of an analysis, honouring single byte-range `Range`
+/// requests so long documents can be paged through
+pub async fn ocr_text(
+    State(state): State<AppState>,
+    Path(text_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let text = state
+        .ocr_texts
+        .get(&text_id, state.config().ocr_text_retention)
+        .ok_or_else(|| ApiError::NotFound(format!("No OCR text {}", text_id)))?;
+    let len = text.len();
+    let requested = headers.get(RANGE).and_then(|value| value.to_str().ok());
+    let content_type = (CONTENT_TYPE, "text/plain; charset=utf-8");
+    let accept_ranges = (ACCEPT_RANGES, "bytes");
+
+    Ok(match range::parse(requested, len) {
+        RangeRequest::Full => (StatusCode::OK, [content_type, accept_ranges], text).into_response(),
+        RangeRequest::Partial(bytes) => (
+            StatusCode::PARTIAL_CONTENT,
+            [content_type, accept_ranges],
+            [(CONTENT_RANGE, range::content_range(&bytes, len))],
+            text.slice(bytes),
+        )
+            .into_response(),
+        RangeRequest::Unsatisfiable => (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(CONTENT_RANGE, format!("bytes */{}", len))],
+        )
+            .into_response(),
+    })
+}
+
+/// Truncate OCR text for the response
+fn preview(text: &str) -> String {
+    text.chars().take(500).collect::<String>() + "..."
+}
@@ -0,0 +1,128 @@
+use axum::extract::State;
+use futures::future;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::auth::Principal;
+use crate::error::ApiError;
+use crate::handlers::{ingest, opinion, predict, search};
+use crate::json::JsonBody;
+use crate::models::ErrorResponse;
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+
+pub const MAX_BATCH_OPERATIONS: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct BatchOperation {
+    /// Echoed back so clients can match results without relying on order
+    #[serde(default)]
+    pub id: Option<String>,
+    pub op: String,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub op: String,
+    /// HTTP status the operation would have had as its own request
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub results: Vec<BatchResult>,
+}
+
+/// Run several tagged operations concurrently. Each result carries its own
+/// status, and one failing operation doesn't fail the batch.
+pub async fn batch(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Option<Principal>,
+    JsonBody(operations): JsonBody<Vec<BatchOperation>>,
+) -> Result<ApiJson<BatchResponse>, ApiError> {
+    if operations.is_empty() || operations.len() > MAX_BATCH_OPERATIONS {
+        return Err(ApiError::BadRequest(format!(
+            "A batch must contain 1-{} operations, got {}",
+            MAX_BATCH_OPERATIONS,
+            operations.len()
+        )));
+    }
+
+    let results = future::join_all(operations.into_iter().map(|operation| {
+        let state = &state;
+        let principal = principal.as_ref();
+        async move {
+            let outcome = run_operation(state, principal, &operation.op, operation.payload).await;
+            let (status, data, error) = match outcome {
+                Ok(data) => (200, Some(data), None),
+                Err(e) => {
+                    let (status, body) = e.into_error_response();
+                    (status.as_u16(), None, Some(body))
+                }
+            };
+            BatchResult {
+                id: operation.id,
+                op: operation.op,
+                status,
+                data,
+                error,
+            }
+        }
+    }))
+    .await;
+
+    let mock = results.iter().any(|result| {
+        result
+            .data
+            .as_ref()
+            .and_then(|data| data.get("mock"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    });
+    Ok(ApiJson::new(&state, request_id, BatchResponse { results }).mock(mock))
+}
+
+async fn run_operation(
+    state: &AppState,
+    principal: Option<&Principal>,
+    op: &str,
+    payload: Value,
+) -> Result<Value, ApiError> {
+    match op {
+        "search" => Ok(to_value(
+            search::search_cases(state, &parse(op, payload)?).await?,
+        )),
+        "predict" => Ok(to_value(
+            predict::predict_outcome(state, parse(op, payload)?).await?,
+        )),
+        "generate_opinion" => Ok(to_value(
+            opinion::generate(state, parse(op, payload)?, principal).await?,
+        )),
+        "ingest" => Ok(to_value(ingest::ingest(state, &parse(op, payload)?).await?)),
+        "validate" => Ok(to_value(ingest::validate(&parse(op, payload)?))),
+        _ => Err(ApiError::BadRequest(format!(
+            "Unknown op {:?}; expected search, predict, generate_opinion, ingest or validate",
+            op
+        ))),
+    }
+}
+
+/// Check a payload against the operation's request model
+fn parse<T: DeserializeOwned>(op: &str, payload: Value) -> Result<T, ApiError> {
+    serde_json::from_value(payload)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid {} payload: {}", op, e)))
+}
+
+fn to_value<T: Serialize>(data: T) -> Value {
+    serde_json::to_value(data).expect("response models serialize to JSON")
+}
@@ -0,0 +1,91 @@
+//! JSON bodies for requests that match no route, or a route but not its method
+
+use axum::extract::Extension;
+use axum::http::{Method, Uri};
+
+use crate::error::ApiError;
+
+/// API_PREFIX as the router was built with it, which a reload can't change
+#[derive(Debug, Clone)]
+pub struct ApiPrefix(pub String);
+
+/// Every route `app()` serves, for self-documenting 404s and 405s. Keep in
+/// step with the router; tests/fallback.rs checks each entry is routed.
+pub const ENDPOINTS: &[(&str, &str)] = &[
+    ("POST", "/admin/reload"),
+    ("GET", "/health"),
+    ("GET", "/metrics"),
+    ("GET", "/api/version"),
+    ("GET", "/api/schema/:model"),
+    ("POST", "/api/analyze-brief"),
+    ("POST", "/api/analyze-url"),
+    ("GET", "/api/analyze-brief/history"),
+    ("GET", "/api/analyze-brief/:id"),
+    ("DELETE", "/api/analyze-brief/:id"),
+    ("GET", "/api/analyze-brief/:id/report.pdf"),
+    ("GET", "/api/ocr-text/:id"),
+    ("POST", "/api/ingest"),
+    ("POST", "/api/validate"),
+    ("GET", "/api/stats"),
+    ("GET", "/api/stats/outcomes"),
+    ("GET", "/api/search"),
+    ("POST", "/api/search"),
+    ("GET", "/api/similar-cases/:document_id"),
+    ("GET", "/api/case/by-citation"),
+    ("POST", "/api/cases/batch"),
+    ("POST", "/api/batch"),
+    ("POST", "/api/predict"),
+    ("POST", "/api/predict/explain"),
+    ("POST", "/api/generate-opinion"),
+    ("POST", "/api/generate-opinion/stream"),
+    ("GET", "/api/opinion/:id"),
+];
+
+pub async fn not_found(
+    Extension(ApiPrefix(prefix)): Extension<ApiPrefix>,
+    method: Method,
+    uri: Uri,
+) -> ApiError {
+    let available: Vec<String> = ENDPOINTS
+        .iter()
+        .map(|(method, path)| format!("{} {}{}", method, prefix, path))
+        .collect();
+    ApiError::RouteNotFound {
+        method: method.to_string(),
+        path: uri.path().to_string(),
+        available: available.join(", "),
+    }
+}
+
+/// axum adds the Allow header itself; the body repeats it for humans
+pub async fn method_not_allowed(
+    Extension(ApiPrefix(prefix)): Extension<ApiPrefix>,
+    method: Method,
+    uri: Uri,
+) -> ApiError {
+    // Root probes are served unprefixed too
+    let path = uri.path().strip_prefix(&prefix).unwrap_or(uri.path());
+    let allowed: Vec<&str> = ENDPOINTS
+        .iter()
+        .filter(|(_, pattern)| matches_route(pattern, path))
+        .map(|(method, _)| *method)
+        .collect();
+    ApiError::MethodNotAllowed {
+        method: method.to_string(),
+        path: uri.path().to_string(),
+        allowed: allowed.join(", "),
+    }
+}
+
+/// Segment-wise match where `:name` segments match anything
+fn matches_route(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.split('/');
+    let mut path = path.split('/');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(expected), Some(actual)) if expected.starts_with(':') || expected == actual => {}
+            _ => return false,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use futures::future;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::disk;
+use crate::downstream::{self, Service};
+use crate::models::{HealthResponse, VersionResponse};
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+
+pub async fn health_check(State(state): State<AppState>) -> Json<HealthResponse> {
+    let config = state.config();
+    let mut status = "ok";
+    let mut components = HashMap::new();
+
+    // Only relevant when uploads can actually be spooled to disk
+    if config.upload_spool_threshold_bytes < config.max_upload_bytes {
+        let (healthy, detail) = temp_disk(config.health_min_temp_free_mb);
+        if !healthy {
+            status = "degraded";
+        }
+        components.insert("temp_disk".to_string(), detail);
+    }
+
+    // Concurrent, so a slow service costs at most one timeout in total
+    let timeout = config.health_check_timeout;
+    if !timeout.is_zero() {
+        let client = state.downstream.client();
+        let config = &config;
+        let checks = Service::ALL.into_iter().map(|service| async move {
+            let check = downstream_check(client, service.base_url(config), timeout).await;
+            (service, check)
+        });
+        for (service, (healthy, detail)) in future::join_all(checks).await {
+            if !healthy {
+                status = "degraded";
+            }
+            components.insert(service.name().to_string(), detail);
+        }
+    }
+
+    Json(HealthResponse {
+        status: status.to_string(),
+        service: "legal-judge-api-rust".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        components,
+    })
+}
+
+/// The service's own GET /health; anything but a 2xx within `timeout` is
+/// degraded
+async fn downstream_check(
+    client: &reqwest::Client,
+    base: &str,
+    timeout: Duration,
+) -> (bool, String) {
+    let url = format!("{}/health", base.trim_end_matches('/'));
+    let started = Instant::now();
+    match client.get(&url).timeout(timeout).send().await {
+        Ok(response) if response.status().is_success() => (
+            true,
+            format!(
+                "ok: {} in {}ms",
+                response.status().as_u16(),
+                started.elapsed().as_millis()
+            ),
+        ),
+        Ok(response) => (
+            false,
+            format!("degraded: /health returned {}", response.status().as_u16()),
+        ),
+        Err(e) if e.is_timeout() => (
+            false,
+            format!("degraded: no response within {}ms", timeout.as_millis()),
+        ),
+        Err(e) => (false, format!("degraded: {}", e.without_url())),
+    }
+}
+
+/// Free space in the spool directory against HEALTH_MIN_TEMP_FREE_MB
+fn temp_disk(min_free_mb: u64) -> (bool, String) {
+    let dir = std::env::temp_dir();
+    match disk::available_bytes(&dir) {
+        Ok(bytes) => {
+            let available_mb = bytes / (1024 * 1024);
+            let state = if available_mb >= min_free_mb {
+                "ok"
+            } else {
+                "degraded"
+            };
+            let detail = format!(
+                "{}: {} MB available in {} (minimum {} MB)",
+                state,
+                available_mb,
+                dir.display(),
+                min_free_mb
+            );
+            (state == "ok", detail)
+        }
+        Err(e) => (
+            false,
+            format!("degraded: cannot check {}: {}", dir.display(), e),
+        ),
+    }
+}
+
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.metrics.snapshot())
+}
+
+pub async fn version(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> ApiJson<VersionResponse> {
+    let downstream_services = Service::ALL
+        .into_iter()
+        .map(|service| {
+            let url = downstream::redact_credentials(service.base_url(&state.config()));
+            (service.name().to_string(), url)
+        })
+        .collect();
+
+    let response = VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: env!("BUILD_GIT_COMMIT").to_string(),
+        build_timestamp: env!("BUILD_TIMESTAMP").to_string(),
+        rustc_version: env!("BUILD_RUSTC_VERSION").to_string(),
+        downstream_services,
+    };
+    ApiJson::new(&state, request_id, response)
+}
@@ -0,0 +1,63 @@
+use axum::extract::State;
+
+use crate::downstream::Service;
+use crate::error::ApiError;
+use crate::json::JsonBody;
+use crate::models::{CaseLawDocument, IngestionResult, ValidationReport};
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+use crate::validation;
+
+/// Validate a structured case and forward it to the ingestion service's
+/// /ingest/document for embedding and indexing
+pub async fn ingest_document(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    JsonBody(document): JsonBody<CaseLawDocument>,
+) -> Result<ApiJson<IngestionResult>, ApiError> {
+    let result = ingest(&state, &document).await?;
+    Ok(ApiJson::new(&state, request_id, result))
+}
+
+/// Run /api/ingest's validation only, so bulk uploaders can filter a
+/// dataset before paying for embedding
+pub async fn validate_document(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    JsonBody(document): JsonBody<CaseLawDocument>,
+) -> ApiJson<ValidationReport> {
+    ApiJson::new(&state, request_id, validate(&document))
+}
+
+pub fn validate(document: &CaseLawDocument) -> ValidationReport {
+    let validation_errors = validation::validate_case_law_document(document);
+    ValidationReport {
+        valid: validation_errors.is_empty(),
+        validation_errors,
+    }
+}
+
+pub async fn ingest(
+    state: &AppState,
+    document: &CaseLawDocument,
+) -> Result<IngestionResult, ApiError> {
+    let violations = validation::validate_case_law_document(document);
+    if !violations.is_empty() {
+        return Err(ApiError::Validation {
+            what: "CaseLawDocument",
+            violations,
+        });
+    }
+
+    let result: IngestionResult = state
+        .downstream
+        .post_json(
+            &state.config(),
+            Service::Ingestion,
+            "/ingest/document",
+            document,
+        )
+        .await?;
+    Ok(result)
+}
@@ -0,0 +1,11 @@
+pub mod admin;
+pub mod analyze;
+pub mod batch;
+pub mod fallback;
+pub mod health;
+pub mod ingest;
+pub mod opinion;
+pub mod predict;
+pub mod schema;
+pub mod search;
+pub mod stats;
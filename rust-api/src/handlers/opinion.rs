@@ -0,0 +1,383 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::convert::Infallible;
+use std::pin::Pin;
+
+use crate::auth::Principal;
+use crate::citation::{self, CitationStyle};
+use crate::downstream::{DownstreamError, Service};
+use crate::error::ApiError;
+use crate::json::JsonBody;
+use crate::models::{GeneratedOpinion, OpinionRequest, OpinionResponse};
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+use crate::store::StoredOpinion;
+use crate::timestamp;
+
+/// Shape returned by the opinion service's /generate/opinion
+#[derive(Deserialize)]
+struct GenerateOpinionResponse {
+    opinion: GeneratedOpinion,
+}
+
+/// Scope allowed to receive opinions without the disclaimer
+pub const DISCLAIMER_EXEMPT_SCOPE: &str = "internal";
+
+/// Footer the opinion service appends to `full_text`
+const DISCLAIMER_FOOTER: &str = "\n---\nDISCLAIMER:";
+
+pub async fn generate_opinion(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Option<Principal>,
+    JsonBody(request): JsonBody<OpinionRequest>,
+) -> Result<ApiJson<OpinionResponse>, ApiError> {
+    let response = generate(&state, request, principal.as_ref()).await?;
+    let mock = response.mock;
+    Ok(ApiJson::new(&state, request_id, response).mock(mock))
+}
+
+pub async fn generate(
+    state: &AppState,
+    mut request: OpinionRequest,
+    principal: Option<&Principal>,
+) -> Result<OpinionResponse, ApiError> {
+    check_request(state, &mut request, principal)?;
+    let GenerateOpinionResponse { opinion } = state
+        .downstream
+        .post_json(
+            &state.config(),
+            Service::Opinion,
+            "/generate/opinion",
+            &request,
+        )
+        .await?;
+    Ok(finish(state, &request, opinion, principal))
+}
+
+/// Authorize dropping the disclaimer and restricted opinion types, and
+/// clamp max_precedents
+fn check_request(
+    state: &AppState,
+    request: &mut OpinionRequest,
+    principal: Option<&Principal>,
+) -> Result<(), ApiError> {
+    // Generated opinions read like real rulings, and once copied out of this
+    // API the disclaimer is the only thing marking them as machine-written
+    // research output rather than legal advice. Dropping it is therefore
+    // limited to identified, explicitly trusted internal consumers that take
+    // on labelling the text themselves; anonymous callers always get it.
+    if !request.include_disclaimer {
+        match principal {
+            Some(principal) => principal.require_scope(DISCLAIMER_EXEMPT_SCOPE)?,
+            None => {
+                return Err(ApiError::Unauthorized(
+                    "include_disclaimer=false requires an authenticated caller".to_string(),
+                ))
+            }
+        }
+    }
+    if let Some(scope) = opinion_type_scope(state, &request.opinion_type) {
+        match principal {
+            Some(principal) => principal.require_scope(&scope)?,
+            None => {
+                return Err(ApiError::Unauthorized(format!(
+                    "opinion_type {:?} requires an authenticated caller",
+                    request.opinion_type
+                )))
+            }
+        }
+    }
+    request.max_precedents =
+        effective_max_precedents(request.max_precedents, state.config().max_precedents_limit)?;
+    Ok(())
+}
+
+/// The scope OPINION_TYPE_SCOPES requires for `opinion_type`, if any
+fn opinion_type_scope(state: &AppState, opinion_type: &str) -> Option<String> {
+    state
+        .config()
+        .opinion_type_scopes
+        .iter()
+        .find(|(restricted, _)| restricted.as_str() == opinion_type)
+        .map(|(_, scope)| scope.clone())
+}
+
+/// Post-process a generated opinion and keep it for GET /api/opinion/:id
+fn finish(
+    state: &AppState,
+    request: &OpinionRequest,
+    mut opinion: GeneratedOpinion,
+    principal: Option<&Principal>,
+) -> OpinionResponse {
+    opinion.generation_metadata.max_precedents = Some(request.max_precedents);
+    echo_procedural_history(
+        &mut opinion,
+        request.case_context.procedural_history.as_deref(),
+    );
+    normalize_citations(&mut opinion, state.config().opinion_citation_style);
+    if !request.include_disclaimer {
+        opinion.disclaimer = None;
+        if let Some(footer) = opinion.full_text.rfind(DISCLAIMER_FOOTER) {
+            opinion.full_text.truncate(footer);
+            opinion
+                .full_text
+                .truncate(opinion.full_text.trim_end().len());
+        }
+        log::info!(
+            "Opinion disclaimer omitted at the request of {}",
+            principal.map_or("unknown", |p| p.name.as_str())
+        );
+    }
+
+    let mock = opinion.generation_metadata.mock;
+    let response = OpinionResponse {
+        status: "success".to_string(),
+        opinion_id: uuid::Uuid::new_v4().to_string(),
+        generated_at: timestamp::now_rfc3339(),
+        opinion,
+        mock,
+    };
+    let stored = StoredOpinion {
+        response: response.clone(),
+        owner: principal.map(|principal| principal.name.clone()),
+    };
+    state.opinions.insert(
+        response.opinion_id.clone(),
+        stored,
+        state.config().opinion_retention,
+    );
+    response
+}
+
+/// One line of the opinion service's /generate/opinion/stream, which
+/// emits newline-delimited JSON: `{"token": ...}` lines as text is
+/// generated, then either `{"opinion": {...}}` or `{"error": ...}`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StreamLine {
+    Token { token: String },
+    Opinion { opinion: Box<GeneratedOpinion> },
+    Error { error: String },
+}
+
+/// Where [`generate_opinion_stream`] is in the downstream body
+struct StreamState {
+    body: Pin<Box<dyn Stream<Item = Result<Bytes, DownstreamError>> + Send>>,
+    buffered: Vec<u8>,
+    ended: bool,
+    request: OpinionRequest,
+    principal: Option<Principal>,
+    state: AppState,
+}
+
+/// POST /api/generate-opinion/stream: the opinion as server-sent events,
+/// `token` events (`{"text": ...}`) while it is generated, then one `done`
+/// event carrying the same body GET /api/opinion/:id returns, or an
+/// `error` event shaped like an error response. Tokens are forwarded as
+/// generated; only the `done` opinion has the disclaimer footer removed
+/// when include_disclaimer is false.
+pub async fn generate_opinion_stream(
+    State(state): State<AppState>,
+    principal: Option<Principal>,
+    JsonBody(mut request): JsonBody<OpinionRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    check_request(&state, &mut request, principal.as_ref())?;
+    let url = format!(
+        "{}/generate/opinion/stream",
+        Service::Opinion.base_url(&state.config())
+    );
+    let body = state
+        .downstream
+        .open_stream(
+            Service::Opinion,
+            state.downstream.client().post(url).json(&request),
+        )
+        .await?
+        .into_bytes();
+
+    let events = stream::unfold(
+        StreamState {
+            body: Box::pin(body),
+            buffered: Vec::new(),
+            ended: false,
+            request,
+            principal,
+            state,
+        },
+        |mut stream| async move {
+            let event = next_event(&mut stream).await?;
+            Some((Ok(event), stream))
+        },
+    );
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// The next SSE event, or None once a `done` or `error` event was sent
+async fn next_event(stream: &mut StreamState) -> Option<Event> {
+    if stream.ended {
+        return None;
+    }
+    loop {
+        if let Some(newline) = stream.buffered.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = stream.buffered.drain(..=newline).collect();
+            if let Some(event) = line_event(stream, &line) {
+                return Some(event);
+            }
+            continue;
+        }
+        match stream.body.next().await {
+            Some(Ok(chunk)) => stream.buffered.extend_from_slice(&chunk),
+            Some(Err(e)) => return Some(error_event(stream, ApiError::from(e))),
+            None => {
+                let rest = std::mem::take(&mut stream.buffered);
+                if let Some(event) = line_event(stream, &rest) {
+                    return Some(event);
+                }
+                let error = ApiError::Downstream(DownstreamError::Status {
+                    service: Service::Opinion,
+                    status: reqwest::StatusCode::BAD_GATEWAY,
+                    body: "opinion stream ended before the opinion was complete".to_string(),
+                });
+                return Some(error_event(stream, error));
+            }
+        }
+    }
+}
+
+/// The event for one downstream line; None for blank lines
+fn line_event(stream: &mut StreamState, line: &[u8]) -> Option<Event> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return None;
+    }
+    let event = match serde_json::from_slice::<StreamLine>(line) {
+        Ok(StreamLine::Token { token }) => json_event("token", &json!({ "text": token })),
+        Ok(StreamLine::Opinion { opinion }) => {
+            stream.ended = true;
+            let response = finish(
+                &stream.state,
+                &stream.request,
+                *opinion,
+                stream.principal.as_ref(),
+            );
+            json_event("done", &response)
+        }
+        Ok(StreamLine::Error { error }) => {
+            let error = ApiError::Downstream(DownstreamError::Status {
+                service: Service::Opinion,
+                status: reqwest::StatusCode::BAD_GATEWAY,
+                body: error,
+            });
+            error_event(stream, error)
+        }
+        Err(source) => error_event(
+            stream,
+            ApiError::Downstream(DownstreamError::Decode {
+                service: Service::Opinion,
+                source,
+            }),
+        ),
+    };
+    Some(event)
+}
+
+/// An `error` event ending the stream
+fn error_event(stream: &mut StreamState, error: ApiError) -> Event {
+    stream.ended = true;
+    stream.state.metrics.incr("opinion_stream_errors_total");
+    let (_, body) = error.into_error_response();
+    json_event("error", &body)
+}
+
+fn json_event<T: Serialize>(name: &str, data: &T) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .expect("event data serializes")
+}
+
+/// A previously generated opinion, until OPINION_RETENTION_SECONDS pass;
+/// another principal's opinion is a 404
+pub async fn get_opinion(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    principal: Option<Principal>,
+    Path(opinion_id): Path<String>,
+) -> Result<ApiJson<OpinionResponse>, ApiError> {
+    let caller = principal.as_ref().map(|principal| principal.name.as_str());
+    let response = state
+        .opinions
+        .get(&opinion_id, state.config().opinion_retention)
+        .filter(|stored| stored.visible_to(caller))
+        .map(|stored| stored.response)
+        .ok_or_else(|| ApiError::NotFound(format!("No opinion {}", opinion_id)))?;
+    let mock = response.mock;
+    Ok(ApiJson::new(&state, request_id, response).mock(mock))
+}
+
+/// The service only fills `procedural_history` when its generated text has
+/// a heading it recognises, and otherwise emits it empty. Fall back to the
+/// history the caller supplied, and drop the section when there is neither.
+fn echo_procedural_history(opinion: &mut GeneratedOpinion, supplied: Option<&str>) {
+    const SECTION: &str = "procedural_history";
+    let generated = opinion
+        .sections
+        .get(SECTION)
+        .is_some_and(|text| !text.trim().is_empty());
+    if generated {
+        return;
+    }
+    match supplied
+        .map(str::trim)
+        .filter(|history| !history.is_empty())
+    {
+        Some(history) => {
+            opinion
+                .sections
+                .insert(SECTION.to_string(), history.to_string());
+        }
+        None => {
+            opinion.sections.remove(SECTION);
+        }
+    }
+}
+
+/// Rewrite recognized citations in the text and its sections in `style`.
+/// Sections repeat the full text, so only the full text's rewrites count.
+fn normalize_citations(opinion: &mut GeneratedOpinion, style: CitationStyle) {
+    if style == CitationStyle::AsWritten {
+        return;
+    }
+    let (full_text, normalized) = citation::normalize(&opinion.full_text, style);
+    opinion.full_text = full_text;
+    for text in opinion.sections.values_mut() {
+        *text = citation::normalize(text, style).0;
+    }
+    opinion.generation_metadata.normalized_citations = Some(normalized);
+}
+
+/// Reject non-positive values and clamp anything above the configured limit
+fn effective_max_precedents(requested: i32, limit: i32) -> Result<i32, ApiError> {
+    if requested < 1 {
+        return Err(ApiError::BadRequest(format!(
+            "max_precedents must be at least 1, got {}",
+            requested
+        )));
+    }
+    let limit = limit.max(1);
+    if requested > limit {
+        log::warn!(
+            "max_precedents {} exceeds limit; clamping to {}",
+            requested,
+            limit
+        );
+    }
+    Ok(requested.min(limit))
+}
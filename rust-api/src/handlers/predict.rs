@@ -0,0 +1,617 @@
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::compare::{self, Comparison};
+use crate::downstream::Service;
+use crate::error::ApiError;
+use crate::handlers::search;
+use crate::json::JsonBody;
+use crate::models::{
+    CaseLawDocument, DistinguishingFactors, ExplainRequest, Outcome, OutcomeProbability,
+    PrecedentExplanation, PredictionRequest, PredictionResponse, RationaleFactor, SearchRequest,
+    SimilarityScore, SupportingCase,
+};
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+
+/// Shape returned by the prediction service's /predict/outcome
+#[derive(Deserialize)]
+struct PredictOutcomeResponse {
+    prediction: DownstreamPrediction,
+}
+
+#[derive(Deserialize)]
+struct DownstreamPrediction {
+    outcome: String,
+    probabilities: BTreeMap<String, f64>,
+    confidence: f64,
+    #[serde(default)]
+    supporting_cases: Vec<DownstreamSupportingCase>,
+    #[serde(default)]
+    explanation: String,
+    #[serde(default)]
+    rationale: Option<Vec<RationaleFactor>>,
+}
+
+/// The current prediction service lists supporting cases by name only
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DownstreamSupportingCase {
+    Detailed(SupportingCase),
+    Name(String),
+}
+
+impl From<DownstreamSupportingCase> for SupportingCase {
+    fn from(case: DownstreamSupportingCase) -> Self {
+        match case {
+            DownstreamSupportingCase::Detailed(mut case) => {
+                attribute_evidence(&mut case);
+                case
+            }
+            DownstreamSupportingCase::Name(case_name) => SupportingCase {
+                case_name,
+                year: 0,
+                similarity_score: SimilarityScore::default(),
+                outcome: String::new(),
+                document_id: None,
+                evidence_spans: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Spans default to the case's own document; ones whose offsets are
+/// reversed can't be highlighted and are dropped
+fn attribute_evidence(case: &mut SupportingCase) {
+    case.evidence_spans
+        .retain(|span| span.char_start <= span.char_end);
+    for span in &mut case.evidence_spans {
+        if span.document_id.is_none() {
+            span.document_id = case.document_id.clone();
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PredictParams {
+    /// `map` (default) or `list`, which returns probabilities as an array
+    /// sorted most likely first, e.g. for bar charts
+    #[serde(default)]
+    pub probabilities: Option<String>,
+}
+
+impl PredictParams {
+    fn listed_probabilities(&self) -> Result<bool, ApiError> {
+        match self.probabilities.as_deref() {
+            None | Some("map") => Ok(false),
+            Some("list") => Ok(true),
+            Some(other) => Err(ApiError::BadRequest(format!(
+                "probabilities must be \"map\" or \"list\", got {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+pub async fn predict(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<PredictParams>,
+    JsonBody(request): JsonBody<PredictionRequest>,
+) -> Result<Response, ApiError> {
+    let listed = params.listed_probabilities()?;
+    let response = predict_outcome(&state, request).await?;
+    if !listed {
+        return Ok(ApiJson::new(&state, request_id, response).into_response());
+    }
+    let mut data = serde_json::to_value(&response).expect("responses serialize to JSON");
+    data["probabilities"] = json!(probability_list(&response.probabilities));
+    Ok(ApiJson::new(&state, request_id, data).into_response())
+}
+
+/// `probabilities` as a list, most likely first; ties keep name order
+fn probability_list(probabilities: &BTreeMap<String, f64>) -> Vec<OutcomeProbability> {
+    let mut list: Vec<OutcomeProbability> = probabilities
+        .iter()
+        .map(|(outcome, &probability)| OutcomeProbability {
+            outcome: outcome.clone(),
+            probability,
+        })
+        .collect();
+    list.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+    list
+}
+
+pub async fn predict_outcome(
+    state: &AppState,
+    mut request: PredictionRequest,
+) -> Result<PredictionResponse, ApiError> {
+    request.jurisdiction = Some(resolve_jurisdiction(
+        state,
+        request.jurisdiction.as_deref(),
+    )?);
+
+    let PredictOutcomeResponse { prediction } = state
+        .downstream
+        .post_json(
+            &state.config(),
+            Service::Prediction,
+            "/predict/outcome",
+            &request,
+        )
+        .await?;
+
+    let config = state.config();
+    let threshold = config.confidence_warn_threshold;
+    let mut warnings: Vec<String> = low_confidence_warning(prediction.confidence, threshold)
+        .into_iter()
+        .collect();
+    if !warnings.is_empty() {
+        state.metrics.incr("predictions_low_confidence_total");
+    }
+
+    let mut supporting_cases: Vec<SupportingCase> = prediction
+        .supporting_cases
+        .into_iter()
+        .map(SupportingCase::from)
+        .collect();
+    let mut metadata = BTreeMap::new();
+    let wanted = config.min_supporting_cases.max(1);
+    if supporting_cases.len() < wanted && config.prediction_search_fallback {
+        let cited = supporting_cases.len();
+        let found = search_supporting_cases(state, &request).await;
+        backfill(&mut supporting_cases, found, wanted);
+        if supporting_cases.len() > cited {
+            let source = if cited == 0 {
+                "gateway_search"
+            } else {
+                "model_and_gateway_search"
+            };
+            metadata.insert("supporting_cases_source".to_string(), json!(source));
+        }
+    }
+
+    let required = config.min_supporting_cases;
+    if let Some(warning) = weak_support_warning(supporting_cases.len(), required) {
+        if config.min_supporting_cases_strict {
+            state
+                .metrics
+                .incr("predictions_weak_support_rejected_total");
+            return Err(ApiError::WeakSupport {
+                found: supporting_cases.len(),
+                required,
+            });
+        }
+        state.metrics.incr("predictions_weak_support_total");
+        warnings.push(warning);
+    }
+
+    let (outcome, probabilities) = canonical_outcomes(
+        state,
+        &prediction.outcome,
+        prediction.probabilities,
+        &config.outcome_labels,
+    );
+    let predicted_outcome = break_tie(outcome, &probabilities, &config.outcome_tie_priority);
+
+    Ok(PredictionResponse {
+        status: "success".to_string(),
+        predicted_outcome,
+        probabilities,
+        confidence: prediction.confidence,
+        supporting_cases,
+        explanation: prediction.explanation,
+        rationale: prediction.rationale,
+        warnings,
+        metadata,
+    })
+}
+
+/// Number of cases the search fallback contributes
+const FALLBACK_SUPPORTING_CASES: usize = 5;
+
+/// Best-effort: a failed search leaves the prediction without supporting cases
+async fn search_supporting_cases(
+    state: &AppState,
+    request: &PredictionRequest,
+) -> Vec<SupportingCase> {
+    // Results are per section, so over-fetch to leave room for duplicates
+    let search = SearchRequest::new(search::facts_query(&request.facts, &request.issue))
+        .with_top_k((FALLBACK_SUPPORTING_CASES * 4) as i32);
+    let results = match search::search_cases(state, &search).await {
+        Ok(response) => response.results,
+        Err(e) => {
+            log::warn!("Supporting-case search fallback failed: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut seen = HashSet::new();
+    results
+        .into_iter()
+        .filter(|result| {
+            let key = search::result_document_id(result).unwrap_or(&result.case_name);
+            seen.insert(key.to_string())
+        })
+        .take(FALLBACK_SUPPORTING_CASES)
+        .map(|result| SupportingCase {
+            document_id: search::result_document_id(&result).map(str::to_string),
+            outcome: result
+                .metadata
+                .get("final_judgment")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            case_name: result.case_name,
+            year: result.year,
+            similarity_score: result.similarity_score,
+            evidence_spans: Vec::new(),
+        })
+        .collect()
+}
+
+/// Add searched cases the model didn't already cite until there are `wanted`
+fn backfill(cases: &mut Vec<SupportingCase>, found: Vec<SupportingCase>, wanted: usize) {
+    let key = |case: &SupportingCase| case.document_id.clone().unwrap_or(case.case_name.clone());
+    let mut seen: HashSet<String> = cases.iter().map(key).collect();
+    for case in found {
+        if cases.len() >= wanted {
+            break;
+        }
+        if seen.insert(key(&case)) {
+            cases.push(case);
+        }
+    }
+}
+
+/// Cap on each list of terms in an explanation
+const EXPLAIN_MAX_TERMS: usize = 10;
+
+/// How one precedent relates to the facts of a prediction: the semantic
+/// similarity of each of its sections, plus the terms the two share and
+/// those only one of them has
+pub async fn explain(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    JsonBody(request): JsonBody<ExplainRequest>,
+) -> Result<ApiJson<PrecedentExplanation>, ApiError> {
+    let ExplainRequest {
+        prediction,
+        document_id,
+    } = request;
+    search::check_document_id(&document_id)?;
+    if prediction.facts.trim().is_empty() {
+        return Err(ApiError::BadRequest("facts must not be empty".to_string()));
+    }
+    resolve_jurisdiction(&state, prediction.jurisdiction.as_deref())?;
+    let precedent = search::fetch_document(&state, &document_id).await?;
+
+    let mut warnings = Vec::new();
+    let section_similarity = match section_similarity(&state, &prediction, &document_id).await {
+        Ok(scores) => scores,
+        Err(e) => {
+            log::warn!("Section similarity for {} failed: {}", document_id, e);
+            warnings.push(format!("Semantic similarity is unavailable: {}", e));
+            BTreeMap::new()
+        }
+    };
+    let comparison = compare::compare(
+        &format!("{}\n{}", prediction.facts, prediction.issue),
+        &format!("{}\n{}", precedent.facts, precedent.issue),
+        EXPLAIN_MAX_TERMS,
+    );
+    let explanation = describe(&precedent, &section_similarity, &comparison);
+
+    let response = PrecedentExplanation {
+        status: "success".to_string(),
+        document_id,
+        case_name: precedent.case_name,
+        year: precedent.year,
+        precedent_outcome: precedent.final_judgment,
+        section_similarity,
+        matching_facts: comparison.shared,
+        distinguishing_factors: DistinguishingFactors {
+            only_in_request: comparison.only_left,
+            only_in_precedent: comparison.only_right,
+        },
+        explanation,
+        warnings,
+    };
+    Ok(ApiJson::new(&state, request_id, response))
+}
+
+/// Best score per section of `document_id` in a search on the facts, from
+/// the same embeddings that pick supporting cases
+async fn section_similarity(
+    state: &AppState,
+    request: &PredictionRequest,
+    document_id: &str,
+) -> Result<BTreeMap<String, f64>, ApiError> {
+    let search = SearchRequest::new(search::facts_query(&request.facts, &request.issue))
+        .with_top_k(state.config().max_top_k)
+        .with_min_similarity(0.0);
+    let mut scores = BTreeMap::new();
+    for result in search::search_cases(state, &search).await?.results {
+        if search::result_document_id(&result) != Some(document_id) {
+            continue;
+        }
+        let score = scores.entry(result.section_type).or_insert(0.0_f64);
+        *score = score.max(result.similarity_score.get());
+    }
+    Ok(scores)
+}
+
+fn describe(
+    precedent: &CaseLawDocument,
+    section_similarity: &BTreeMap<String, f64>,
+    comparison: &Comparison,
+) -> String {
+    let listed = |terms: &[String]| terms.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
+    let mut explanation = format!("{} ({})", precedent.case_name, precedent.year);
+    if comparison.shared.is_empty() {
+        explanation.push_str(" shares no key terms with these facts");
+    } else {
+        explanation.push_str(&format!(
+            " shares key terms with these facts ({})",
+            listed(&comparison.shared)
+        ));
+    }
+    if let Some((section, score)) = section_similarity.iter().max_by(|a, b| a.1.total_cmp(b.1)) {
+        explanation.push_str(&format!(
+            "; its {} is the closest match at {:.2}",
+            section, score
+        ));
+    }
+    if !comparison.only_right.is_empty() {
+        explanation.push_str(&format!(
+            "; it turned on facts absent here ({})",
+            listed(&comparison.only_right)
+        ));
+    }
+    explanation.push('.');
+    if !precedent.final_judgment.trim().is_empty() {
+        explanation.push_str(&format!(" Outcome: {}", precedent.final_judgment.trim()));
+    }
+    explanation
+}
+
+/// Probabilities this close to the top one count as tied with it
+const TIE_EPSILON: f64 = 1e-9;
+
+/// The most probable outcome, with ties broken deterministically: among
+/// labels within TIE_EPSILON of the top, the first in `priority`
+/// (case-insensitive), otherwise the alphabetically first
+fn winning_outcome<'a>(
+    probabilities: &'a BTreeMap<String, f64>,
+    priority: &[String],
+) -> Option<&'a str> {
+    let top = probabilities.values().copied().reduce(f64::max)?;
+    let tied: Vec<&str> = probabilities
+        .iter()
+        .filter(|(_, &p)| top - p <= TIE_EPSILON)
+        .map(|(label, _)| label.as_str())
+        .collect();
+    priority
+        .iter()
+        .find_map(|wanted| {
+            tied.iter()
+                .find(|label| label.eq_ignore_ascii_case(wanted))
+                .copied()
+        })
+        // BTreeMap iterates in key order, so this is the alphabetical first
+        .or_else(|| tied.first().copied())
+}
+
+/// Keep the service's label unless it tied for the top probability, in
+/// which case apply [`winning_outcome`]. Labels the probabilities don't
+/// back at all (a placeholder prediction) pass through untouched.
+fn break_tie(
+    outcome: String,
+    probabilities: &BTreeMap<String, f64>,
+    priority: &[String],
+) -> String {
+    let Some(winner) = winning_outcome(probabilities, priority) else {
+        return outcome;
+    };
+    let (Some(&chosen), Some(&best)) = (probabilities.get(&outcome), probabilities.get(winner))
+    else {
+        return outcome;
+    };
+    if winner == outcome || best - chosen > TIE_EPSILON {
+        return outcome;
+    }
+    log::debug!(
+        "Outcome {} tied for the top probability; tie-break chose {}",
+        outcome,
+        winner
+    );
+    winner.to_string()
+}
+
+/// Translate a service label into the gateway's vocabulary: an
+/// OUTCOME_LABEL_MAP entry, else the outcome it names, else Other
+fn canonical_outcome(label: &str, mapping: &[(String, Outcome)]) -> Option<Outcome> {
+    mapping
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(label.trim()))
+        .map(|&(_, outcome)| outcome)
+        .or_else(|| label.trim().parse().ok())
+}
+
+/// The predicted outcome and probabilities in canonical labels. Labels
+/// mapping to the same outcome have their probabilities summed.
+fn canonical_outcomes(
+    state: &AppState,
+    outcome: &str,
+    probabilities: BTreeMap<String, f64>,
+    mapping: &[(String, Outcome)],
+) -> (String, BTreeMap<String, f64>) {
+    let translate = |label: &str| {
+        canonical_outcome(label, mapping).unwrap_or_else(|| {
+            log::warn!(
+                "Unmapped prediction outcome label {:?}; reporting Other",
+                label
+            );
+            state.metrics.incr("prediction_unmapped_labels_total");
+            Outcome::Other
+        })
+    };
+    let mut canonical = BTreeMap::new();
+    for (label, p) in probabilities {
+        *canonical
+            .entry(translate(&label).as_str().to_string())
+            .or_insert(0.0) += p;
+    }
+    (translate(outcome).as_str().to_string(), canonical)
+}
+
+fn low_confidence_warning(confidence: f64, threshold: f64) -> Option<String> {
+    (confidence < threshold).then(|| {
+        format!(
+            "Low-confidence prediction ({:.2} < {:.2}); treat this result with caution",
+            confidence, threshold
+        )
+    })
+}
+
+fn weak_support_warning(found: usize, required: usize) -> Option<String> {
+    (found < required).then(|| {
+        format!(
+            "Weak support: the prediction rests on {} supporting case(s), fewer than {}; \
+             treat this result with caution",
+            found, required
+        )
+    })
+}
+
+/// Fill in DEFAULT_JURISDICTION and check the result against ALLOWED_JURISDICTIONS
+fn resolve_jurisdiction(state: &AppState, requested: Option<&str>) -> Result<String, ApiError> {
+    let config = state.config();
+    let jurisdiction = requested
+        .map(str::trim)
+        .filter(|j| !j.is_empty())
+        .unwrap_or(&config.default_jurisdiction)
+        .to_ascii_lowercase();
+
+    if !config.allowed_jurisdictions.contains(&jurisdiction) {
+        return Err(ApiError::BadRequest(format!(
+            "Unsupported jurisdiction {:?}; expected one of {:?}",
+            jurisdiction, config.allowed_jurisdictions
+        )));
+    }
+    Ok(jurisdiction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probabilities(entries: &[(&str, f64)]) -> BTreeMap<String, f64> {
+        entries
+            .iter()
+            .map(|&(label, p)| (label.to_string(), p))
+            .collect()
+    }
+
+    #[test]
+    fn probability_list_is_most_likely_first_with_ties_by_name() {
+        let list = probability_list(&probabilities(&[
+            ("Remanded", 0.2),
+            ("Affirmed", 0.6),
+            ("Mixed", 0.2),
+        ]));
+        let order: Vec<(&str, f64)> = list
+            .iter()
+            .map(|entry| (entry.outcome.as_str(), entry.probability))
+            .collect();
+        assert_eq!(
+            order,
+            [("Affirmed", 0.6), ("Mixed", 0.2), ("Remanded", 0.2)]
+        );
+    }
+
+    #[test]
+    fn labels_translate_through_the_mapping_then_by_name() {
+        let mapping = vec![("grant".to_string(), Outcome::Reversed)];
+        assert_eq!(
+            canonical_outcome("GRANT", &mapping),
+            Some(Outcome::Reversed)
+        );
+        assert_eq!(
+            canonical_outcome("AFFIRMED", &mapping),
+            Some(Outcome::Affirmed)
+        );
+        assert_eq!(canonical_outcome("Remanded", &[]), Some(Outcome::Remanded));
+        assert_eq!(canonical_outcome("PLAINTIFF_WINS", &mapping), None);
+    }
+
+    #[test]
+    fn exact_ties_prefer_priority_then_alphabetical() {
+        let tied = probabilities(&[("REVERSED", 0.4), ("MIXED", 0.4), ("AFFIRMED", 0.2)]);
+        let priority = vec!["mixed".to_string()];
+        assert_eq!(winning_outcome(&tied, &priority), Some("MIXED"));
+        assert_eq!(winning_outcome(&tied, &[]), Some("MIXED"));
+
+        let tied = probabilities(&[("Reversed", 0.45), ("Affirmed", 0.45), ("Remanded", 0.1)]);
+        assert_eq!(winning_outcome(&tied, &priority), Some("Affirmed"));
+        assert_eq!(
+            break_tie("Reversed".to_string(), &tied, &priority),
+            "Affirmed"
+        );
+        assert_eq!(winning_outcome(&BTreeMap::new(), &priority), None);
+    }
+
+    #[test]
+    fn near_ties_within_epsilon_tie_and_clear_leads_win() {
+        let near = probabilities(&[("Reversed", 0.5), ("Affirmed", 0.5 - 1e-12)]);
+        assert_eq!(winning_outcome(&near, &[]), Some("Affirmed"));
+
+        let clear = probabilities(&[("Reversed", 0.5), ("Affirmed", 0.5 - 1e-6)]);
+        assert_eq!(winning_outcome(&clear, &[]), Some("Reversed"));
+        assert_eq!(break_tie("Reversed".to_string(), &clear, &[]), "Reversed");
+        // A label the probabilities don't cover is left alone
+        assert_eq!(break_tie("Unknown".to_string(), &clear, &[]), "Unknown");
+    }
+
+    #[test]
+    fn weak_support_counts_against_the_minimum() {
+        assert!(weak_support_warning(0, 2).is_some());
+        assert!(weak_support_warning(1, 2).is_some());
+        assert!(weak_support_warning(2, 2).is_none());
+        assert!(weak_support_warning(0, 0).is_none());
+    }
+
+    #[test]
+    fn backfill_skips_cited_cases_and_stops_at_wanted() {
+        let case = |name: &str| SupportingCase {
+            case_name: name.to_string(),
+            year: 1984,
+            similarity_score: SimilarityScore::new(0.9),
+            outcome: String::new(),
+            document_id: None,
+            evidence_spans: Vec::new(),
+        };
+        let mut cases = vec![case("Hilder v. St. Peter")];
+        let found = vec![
+            case("Hilder v. St. Peter"),
+            case("Javins v. First"),
+            case("Pugh v. Holmes"),
+        ];
+        backfill(&mut cases, found, 2);
+        let names: Vec<_> = cases.iter().map(|c| c.case_name.as_str()).collect();
+        assert_eq!(names, ["Hilder v. St. Peter", "Javins v. First"]);
+    }
+
+    #[test]
+    fn warns_only_below_threshold() {
+        assert!(low_confidence_warning(0.49, 0.5).is_some());
+        assert!(low_confidence_warning(0.5, 0.5).is_none());
+        assert!(low_confidence_warning(0.9, 0.5).is_none());
+    }
+}
@@ -0,0 +1,79 @@
+//! JSON Schemas of the request and response models, as a quick reference
+//! for clients integrating without OpenAPI
+
+use axum::extract::{Path, State};
+use schemars::{schema::RootSchema, schema_for};
+
+use crate::error::ApiError;
+use crate::models::{
+    AnalyzeResponse, AnalyzeUrlRequest, CaseBatchRequest, CaseBatchResponse, CaseLawDocument,
+    ErrorResponse, ExplainRequest, HealthResponse, IngestionResult, OpinionRequest,
+    OpinionResponse, OutcomeDistribution, OutcomeProbability, PrecedentExplanation,
+    PredictionRequest, PredictionResponse, SearchRequest, SearchResponse, SearchResult,
+    StatsResponse, ValidationReport, VersionResponse,
+};
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+
+type SchemaFn = fn() -> RootSchema;
+
+/// Models served at GET /api/schema/:model, by type name
+const MODELS: &[(&str, SchemaFn)] = &[
+    ("AnalyzeResponse", || schema_for!(AnalyzeResponse)),
+    ("AnalyzeUrlRequest", || schema_for!(AnalyzeUrlRequest)),
+    ("CaseBatchRequest", || schema_for!(CaseBatchRequest)),
+    ("CaseBatchResponse", || schema_for!(CaseBatchResponse)),
+    ("CaseLawDocument", || schema_for!(CaseLawDocument)),
+    ("ErrorResponse", || schema_for!(ErrorResponse)),
+    ("ExplainRequest", || schema_for!(ExplainRequest)),
+    ("HealthResponse", || schema_for!(HealthResponse)),
+    ("IngestionResult", || schema_for!(IngestionResult)),
+    ("OpinionRequest", || schema_for!(OpinionRequest)),
+    ("OpinionResponse", || schema_for!(OpinionResponse)),
+    ("OutcomeDistribution", || schema_for!(OutcomeDistribution)),
+    ("OutcomeProbability", || schema_for!(OutcomeProbability)),
+    ("PrecedentExplanation", || schema_for!(PrecedentExplanation)),
+    ("PredictionRequest", || schema_for!(PredictionRequest)),
+    ("PredictionResponse", || schema_for!(PredictionResponse)),
+    ("SearchRequest", || schema_for!(SearchRequest)),
+    ("SearchResponse", || schema_for!(SearchResponse)),
+    ("SearchResult", || schema_for!(SearchResult)),
+    ("StatsResponse", || schema_for!(StatsResponse)),
+    ("ValidationReport", || schema_for!(ValidationReport)),
+    ("VersionResponse", || schema_for!(VersionResponse)),
+];
+
+/// The named model's schema; names match case-insensitively
+pub async fn model_schema(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(model): Path<String>,
+) -> Result<ApiJson<RootSchema>, ApiError> {
+    let schema = MODELS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(&model))
+        .map(|(_, schema)| schema())
+        .ok_or_else(|| {
+            let known: Vec<&str> = MODELS.iter().map(|(name, _)| *name).collect();
+            ApiError::NotFound(format!(
+                "No model {:?}; expected one of {}",
+                model,
+                known.join(", ")
+            ))
+        })?;
+    Ok(ApiJson::new(&state, request_id, schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_model_schema_is_titled_with_its_name() {
+        for (name, schema) in MODELS {
+            let title = schema().schema.metadata.and_then(|metadata| metadata.title);
+            assert_eq!(title.as_deref(), Some(*name));
+        }
+    }
+}
@@ -0,0 +1,1071 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{
+        header::{ACCEPT, CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap,
+    },
+    response::{IntoResponse, Response},
+};
+use futures::{future, stream};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+
+use crate::citation::Citation;
+use crate::csv;
+use crate::downstream::{DownstreamError, Service};
+use crate::error::ApiError;
+use crate::json::JsonBody;
+use crate::models::{
+    CaseBatchRequest, CaseBatchResponse, CaseLawDocument, OpinionType, SearchMode, SearchRequest,
+    SearchResponse, SearchResult, SectionType, SimilarityScore, SnippetContext,
+};
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+use crate::stats;
+
+/// The search service rejects queries longer than this
+const MAX_QUERY_CHARS: usize = 1000;
+
+/// Shape returned by the search service's /search
+#[derive(Deserialize)]
+struct DownstreamSearchResponse {
+    results: Vec<SearchResult>,
+    #[serde(default)]
+    search_time_ms: f64,
+    #[serde(default)]
+    distance_metric: Option<String>,
+}
+
+/// Run a semantic search against the search service after checking the
+/// bounds it enforces, so callers get a 400 rather than a 502. top_k above
+/// MAX_TOP_K is clamped rather than rejected, and the query is trimmed.
+pub async fn search_cases(
+    state: &AppState,
+    request: &SearchRequest,
+) -> Result<SearchResponse, ApiError> {
+    let query = normalize_query(
+        &request.query,
+        state.config().search_collapse_query_whitespace,
+    )?;
+    let normalized = (query != request.query).then(|| SearchRequest {
+        query,
+        ..request.clone()
+    });
+    let request = normalized.as_ref().unwrap_or(request);
+    let top_k = effective_top_k(state, request.top_k)?;
+    let clamped = (top_k != request.top_k).then(|| SearchRequest {
+        top_k,
+        ..request.clone()
+    });
+    let requested_top_k = clamped.as_ref().map(|_| request.top_k);
+    let request = clamped.as_ref().unwrap_or(request);
+    if !(0.0..=1.0).contains(&request.min_similarity) {
+        return Err(ApiError::BadRequest(format!(
+            "min_similarity must be between 0 and 1, got {}",
+            request.min_similarity
+        )));
+    }
+
+    let max_snippet_chars = request
+        .max_snippet_chars
+        .unwrap_or(state.config().search_max_snippet_chars);
+    if max_snippet_chars == 0 {
+        return Err(ApiError::BadRequest(
+            "max_snippet_chars must be at least 1".to_string(),
+        ));
+    }
+
+    if let Some(document_ids) = &request.document_ids {
+        for document_id in document_ids {
+            check_document_id(document_id)?;
+        }
+    }
+
+    check_indices(state, &request.indices)?;
+
+    if let Some(section) = &request.section_filter {
+        section.parse::<SectionType>().map_err(|_| {
+            let allowed: Vec<_> = SectionType::ALL.iter().map(|t| t.as_str()).collect();
+            ApiError::BadRequest(format!(
+                "section_filter must be one of {:?}, got {:?}",
+                allowed, section
+            ))
+        })?;
+    }
+
+    let opinion_types = match &request.opinion_type_filter {
+        Some(filter) => Some(parse_opinion_types(filter)?),
+        None => None,
+    };
+
+    // Requests may tighten the server-wide floor but never loosen it
+    let floor = state.config().global_min_similarity;
+    let floored = (request.min_similarity < floor).then(|| SearchRequest {
+        min_similarity: floor,
+        ..request.clone()
+    });
+    if floored.is_some() {
+        state.metrics.incr("search_similarity_floor_applied_total");
+        log::debug!(
+            "min_similarity {} raised to GLOBAL_MIN_SIMILARITY {}",
+            request.min_similarity,
+            floor
+        );
+    }
+    let request = floored.as_ref().unwrap_or(request);
+
+    let mut response = match request.mode {
+        SearchMode::Semantic | SearchMode::Keyword => downstream_search(state, request).await?,
+        SearchMode::Hybrid => hybrid_search(state, request).await?,
+    };
+    // An empty index looks like a query that matched nothing; tell them apart
+    if response.results.is_empty() && stats::corpus_is_empty(state).await {
+        state.metrics.incr("search_no_corpus_total");
+        return Ok(SearchResponse {
+            status: "no_corpus".to_string(),
+            query: request.query.clone(),
+            results: Vec::new(),
+            total_results: 0,
+            search_time_ms: response.search_time_ms.round() as u64,
+            distance_metric: response.distance_metric,
+            effective_top_k: request.top_k,
+            requested_top_k,
+            min_similarity_floor: floored.map(|request| request.min_similarity),
+            court_boost_applied: false,
+            guidance: Some(state.config().search_no_corpus_guidance.clone()),
+        });
+    }
+    let section_types = &state.config().search_result_section_types;
+    let fallback_chars = state.config().search_snippet_fallback_chars;
+    let terms = query_terms(&request.query);
+    for result in &mut response.results {
+        if normalize_section_type(result, section_types) {
+            state.metrics.incr("search_unknown_section_types_total");
+        }
+        if fallback_snippet(result, terms.as_ref(), fallback_chars) {
+            state.metrics.incr("search_snippets_generated_total");
+        }
+        result.snippet_context = snippet_context(result);
+        truncate_snippet(result, max_snippet_chars);
+        result.metadata.insert(
+            "court_level".to_string(),
+            result.court_level().as_str().into(),
+        );
+    }
+    let court_boost_applied =
+        boost_by_court(&mut response.results, &state.config().search_court_weights);
+    // The search service may not apply these filters itself, so enforce them here
+    if let Some(opinion_types) = opinion_types {
+        response.results.retain(|result| {
+            result_opinion_type(result).is_some_and(|t| opinion_types.contains(&t))
+        });
+    }
+    if let Some(document_ids) = &request.document_ids {
+        response.results.retain(|result| {
+            result_document_id(result).is_some_and(|id| document_ids.iter().any(|d| d == id))
+        });
+    }
+    // Only now: the filters and snippet fallback above read the documents
+    if !state.config().search_include_full_documents {
+        for result in &mut response.results {
+            result.full_document = None;
+        }
+    }
+
+    Ok(SearchResponse {
+        status: "success".to_string(),
+        query: request.query.clone(),
+        total_results: response.results.len(),
+        results: response.results,
+        search_time_ms: response.search_time_ms.round() as u64,
+        distance_metric: response.distance_metric,
+        effective_top_k: request.top_k,
+        requested_top_k,
+        min_similarity_floor: floored.map(|request| request.min_similarity),
+        court_boost_applied,
+        guidance: None,
+    })
+}
+
+/// `top_k` clamped to MAX_TOP_K, with a warning; below 1 is a 400
+fn effective_top_k(state: &AppState, top_k: i32) -> Result<i32, ApiError> {
+    if top_k < 1 {
+        return Err(ApiError::BadRequest(format!(
+            "top_k must be at least 1, got {}",
+            top_k
+        )));
+    }
+    let max_top_k = state.config().max_top_k;
+    if top_k > max_top_k {
+        state.metrics.incr("search_top_k_clamped_total");
+        log::warn!(
+            "top_k {} exceeds MAX_TOP_K; clamping to {}",
+            top_k,
+            max_top_k
+        );
+    }
+    Ok(top_k.min(max_top_k))
+}
+
+async fn downstream_search(
+    state: &AppState,
+    request: &SearchRequest,
+) -> Result<DownstreamSearchResponse, ApiError> {
+    let config = state.config();
+    if request.indices.is_empty() {
+        return Ok(state
+            .downstream
+            .post_json(&config, Service::Search, "/search", request)
+            .await?);
+    }
+    let forwarded = SearchRequest {
+        indices: Vec::new(),
+        ..request.clone()
+    };
+    let forwarded = &forwarded;
+    // Walking the configured list also drops repeated names
+    let searches = config
+        .search_indices
+        .iter()
+        .filter(|(name, _)| request.indices.contains(name))
+        .map(|(name, url)| async move {
+            let mut response: DownstreamSearchResponse = state
+                .downstream
+                .post_json_at(Service::Search, url, "/search", forwarded)
+                .await?;
+            for result in &mut response.results {
+                result.index = Some(name.clone());
+            }
+            Ok::<_, ApiError>(response)
+        });
+    let responses = future::try_join_all(searches).await?;
+    Ok(merge_indices(responses, request.top_k as usize))
+}
+
+/// Reject index names that aren't in SEARCH_INDICES
+fn check_indices(state: &AppState, requested: &[String]) -> Result<(), ApiError> {
+    let config = state.config();
+    let unknown = requested.iter().find(|name| {
+        !config
+            .search_indices
+            .iter()
+            .any(|(known, _)| known == *name)
+    });
+    let Some(unknown) = unknown else {
+        return Ok(());
+    };
+    let configured: Vec<&str> = config
+        .search_indices
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    Err(ApiError::BadRequest(if configured.is_empty() {
+        format!(
+            "Unknown search index {:?}; no SEARCH_INDICES are configured",
+            unknown
+        )
+    } else {
+        format!(
+            "Unknown search index {:?}; expected one of {}",
+            unknown,
+            configured.join(", ")
+        )
+    }))
+}
+
+/// One ranking across the indices' results, best `top_k` first. Scores
+/// come from the same service, so they compare directly.
+fn merge_indices(
+    responses: Vec<DownstreamSearchResponse>,
+    top_k: usize,
+) -> DownstreamSearchResponse {
+    let search_time_ms = responses
+        .iter()
+        .map(|response| response.search_time_ms)
+        .fold(0.0, f64::max);
+    let distance_metric = responses
+        .iter()
+        .find_map(|response| response.distance_metric.clone());
+    let mut results: Vec<SearchResult> = responses
+        .into_iter()
+        .flat_map(|response| response.results)
+        .collect();
+    results.sort_by(|a, b| b.similarity_score.total_cmp(&a.similarity_score));
+    results.truncate(top_k);
+    DownstreamSearchResponse {
+        results,
+        search_time_ms,
+        distance_metric,
+    }
+}
+
+/// Run the semantic and keyword searches concurrently and blend them with
+/// SEARCH_HYBRID_SEMANTIC_WEIGHT
+async fn hybrid_search(
+    state: &AppState,
+    request: &SearchRequest,
+) -> Result<DownstreamSearchResponse, ApiError> {
+    let semantic = SearchRequest {
+        mode: SearchMode::Semantic,
+        ..request.clone()
+    };
+    let keyword = SearchRequest {
+        mode: SearchMode::Keyword,
+        ..request.clone()
+    };
+    let (semantic, keyword) = futures::try_join!(
+        downstream_search(state, &semantic),
+        downstream_search(state, &keyword)
+    )?;
+
+    Ok(DownstreamSearchResponse {
+        results: blend(
+            semantic.results,
+            keyword.results,
+            state.config().search_hybrid_semantic_weight,
+            request.top_k as usize,
+        ),
+        search_time_ms: semantic.search_time_ms.max(keyword.search_time_ms),
+        distance_metric: semantic.distance_metric,
+    })
+}
+
+/// Merge the two result lists, scoring each section as
+/// `weight * semantic + (1 - weight) * keyword` where a list it is missing
+/// from counts as 0, and keep the best `top_k`. A section found by both
+/// keeps the semantic result's fields, including its distance.
+fn blend(
+    semantic: Vec<SearchResult>,
+    keyword: Vec<SearchResult>,
+    weight: f64,
+    top_k: usize,
+) -> Vec<SearchResult> {
+    let key = |result: &SearchResult| {
+        let document = result_document_id(result).unwrap_or(&result.case_name);
+        (document.to_string(), result.section_type.clone())
+    };
+
+    let mut merged: Vec<SearchResult> = Vec::with_capacity(semantic.len() + keyword.len());
+    let mut index = HashMap::new();
+    for mut result in semantic {
+        result.similarity_score = SimilarityScore::new(result.similarity_score.get() * weight);
+        index.insert(key(&result), merged.len());
+        merged.push(result);
+    }
+    for mut result in keyword {
+        let score = result.similarity_score.get() * (1.0 - weight);
+        match index.get(&key(&result)) {
+            Some(&i) => {
+                let blended = merged[i].similarity_score.get() + score;
+                merged[i].similarity_score = SimilarityScore::new(blended);
+            }
+            None => {
+                result.similarity_score = SimilarityScore::new(score);
+                result.distance = None;
+                merged.push(result);
+            }
+        }
+    }
+
+    merged.sort_by(|a, b| b.similarity_score.total_cmp(&a.similarity_score));
+    merged.truncate(top_k);
+    merged
+}
+
+/// Semantic search. Returns JSON by default, or a CSV export when the
+/// client sends `Accept: text/csv`.
+pub async fn search(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    headers: HeaderMap,
+    JsonBody(request): JsonBody<SearchRequest>,
+) -> Result<Response, ApiError> {
+    respond(&state, request_id, &headers, &request).await
+}
+
+/// Query-string form of the common search fields, for browsers and curl.
+/// Values are taken as strings so a bad one gets a 400 naming the parameter.
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: Option<String>,
+    pub top_k: Option<String>,
+    pub min_similarity: Option<String>,
+    pub mode: Option<String>,
+}
+
+impl SearchParams {
+    fn into_request(self) -> Result<SearchRequest, ApiError> {
+        let query = self.q.unwrap_or_default();
+        if query.trim().is_empty() {
+            return Err(ApiError::BadRequest(
+                "Query parameter q (the search text) is required".to_string(),
+            ));
+        }
+        let mut request = SearchRequest::new(query);
+        if let Some(top_k) = self.top_k {
+            request = request.with_top_k(parse_param("top_k", "an integer", &top_k)?);
+        }
+        if let Some(min_similarity) = self.min_similarity {
+            request = request.with_min_similarity(parse_param(
+                "min_similarity",
+                "a number",
+                &min_similarity,
+            )?);
+        }
+        if let Some(mode) = self.mode {
+            request = request.with_mode(parse_param("mode", "semantic, keyword or hybrid", &mode)?);
+        }
+        Ok(request)
+    }
+}
+
+/// Range checks are left to [`search_cases`], as for the JSON body
+fn parse_param<T: std::str::FromStr>(name: &str, expected: &str, raw: &str) -> Result<T, ApiError> {
+    raw.trim()
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("{} must be {}, got {:?}", name, expected, raw)))
+}
+
+/// GET form of [`search`] for ad-hoc use; filters beyond these still need
+/// the JSON body
+pub async fn search_query(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    headers: HeaderMap,
+    Query(params): Query<SearchParams>,
+) -> Result<Response, ApiError> {
+    let request = params.into_request()?;
+    respond(&state, request_id, &headers, &request).await
+}
+
+async fn respond(
+    state: &AppState,
+    request_id: RequestId,
+    headers: &HeaderMap,
+    request: &SearchRequest,
+) -> Result<Response, ApiError> {
+    let response = search_cases(state, request).await?;
+
+    let wants_csv = headers
+        .get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/csv"));
+    if wants_csv {
+        return Ok(csv_response(response.results));
+    }
+    Ok(ApiJson::new(state, request_id, response).into_response())
+}
+
+const CSV_COLUMNS: [&str; 7] = [
+    "case_name",
+    "year",
+    "court",
+    "section_type",
+    "similarity_score",
+    "distance",
+    "snippet",
+];
+
+fn csv_response(results: Vec<SearchResult>) -> Response {
+    let header = std::iter::once(csv::record(CSV_COLUMNS));
+    let rows = results.into_iter().map(|result| {
+        csv::record([
+            result.case_name,
+            result.year.to_string(),
+            result.court,
+            result.section_type,
+            result.similarity_score.get().to_string(),
+            result
+                .distance
+                .map(|distance| distance.to_string())
+                .unwrap_or_default(),
+            result.snippet,
+        ])
+    });
+    let body = stream::iter(header.chain(rows).map(Ok::<_, Infallible>));
+
+    (
+        [
+            (CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                CONTENT_DISPOSITION,
+                "attachment; filename=\"search-results.csv\"",
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimilarCasesParams {
+    pub top_k: Option<i32>,
+    pub min_similarity: Option<f64>,
+}
+
+/// Document ids are interpolated into ingestion service paths
+pub fn check_document_id(document_id: &str) -> Result<(), ApiError> {
+    if document_id.is_empty()
+        || !document_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid document_id {:?}",
+            document_id
+        )));
+    }
+    Ok(())
+}
+
+/// A stored case from the ingestion service; unknown ids are a 404
+pub async fn fetch_document(
+    state: &AppState,
+    document_id: &str,
+) -> Result<CaseLawDocument, ApiError> {
+    state
+        .downstream
+        .get_json(
+            &state.config(),
+            Service::Ingestion,
+            &format!("/documents/{}", document_id),
+        )
+        .await
+        .map_err(|e| match e {
+            DownstreamError::Status { status, .. } if status == reqwest::StatusCode::NOT_FOUND => {
+                ApiError::NotFound(format!("No document with id {}", document_id))
+            }
+            e => e.into(),
+        })
+}
+
+/// "More like this": search with the seed document's facts and issue as the
+/// query, leaving the seed itself out of the results
+pub async fn similar_cases(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Path(document_id): Path<String>,
+    Query(params): Query<SimilarCasesParams>,
+) -> Result<ApiJson<SearchResponse>, ApiError> {
+    check_document_id(&document_id)?;
+
+    let requested = params.top_k.unwrap_or(10);
+    let top_k = effective_top_k(&state, requested)?;
+
+    let seed = fetch_document(&state, &document_id).await?;
+
+    // One extra slot, since the seed is usually its own best match
+    let mut request = SearchRequest::new(facts_query(&seed.facts, &seed.issue))
+        .with_top_k((top_k + 1).min(state.config().max_top_k));
+    if let Some(min_similarity) = params.min_similarity {
+        request = request.with_min_similarity(min_similarity);
+    }
+
+    let mut response = search_cases(&state, &request).await?;
+    response
+        .results
+        .retain(|result| result_document_id(result) != Some(document_id.as_str()));
+    response.results.truncate(top_k as usize);
+    response.total_results = response.results.len();
+    response.effective_top_k = top_k;
+    response.requested_top_k = (requested != top_k).then_some(requested);
+    response.query = format!("similar to {}", document_id);
+    Ok(ApiJson::new(&state, request_id, response))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CitationParams {
+    /// The citation as the researcher has it, e.g. `478 A.2d 202 (Vt. 1984)`
+    pub c: Option<String>,
+}
+
+/// Exact lookup by reporter citation, for when the case is already known
+pub async fn case_by_citation(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(params): Query<CitationParams>,
+) -> Result<ApiJson<CaseLawDocument>, ApiError> {
+    let raw = params.c.unwrap_or_default();
+    if raw.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "Query parameter c (the citation) is required".to_string(),
+        ));
+    }
+    let citation: Citation = raw
+        .parse()
+        .map_err(|e| ApiError::BadRequest(format!("Invalid citation: {}", e)))?;
+
+    let query = [
+        ("citation", citation.to_string()),
+        ("citation_key", citation.key()),
+    ];
+    let document = state
+        .downstream
+        .get_json_with_query(
+            &state.config(),
+            Service::Ingestion,
+            "/documents/by-citation",
+            &query,
+        )
+        .await
+        .map_err(|e| match e {
+            DownstreamError::Status { status, .. } if status == reqwest::StatusCode::NOT_FOUND => {
+                ApiError::NotFound(format!("No case with citation {}", citation))
+            }
+            e => e.into(),
+        })?;
+    Ok(ApiJson::new(&state, request_id, document))
+}
+
+/// Full documents for a list of ids in one call, the second phase after
+/// a lightweight search. Ids the ingestion service doesn't know are listed
+/// as missing rather than failing the batch.
+pub async fn cases_batch(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    JsonBody(request): JsonBody<CaseBatchRequest>,
+) -> Result<ApiJson<CaseBatchResponse>, ApiError> {
+    // Capped before deduping so an oversized list is rejected cheaply
+    let max_ids = state.config().case_batch_max_ids;
+    if request.document_ids.is_empty() || request.document_ids.len() > max_ids {
+        return Err(ApiError::BadRequest(format!(
+            "document_ids must list 1-{} ids, got {}",
+            max_ids,
+            request.document_ids.len()
+        )));
+    }
+    let mut seen = HashSet::new();
+    let document_ids: Vec<String> = request
+        .document_ids
+        .into_iter()
+        .filter(|document_id| seen.insert(document_id.clone()))
+        .collect();
+    for document_id in &document_ids {
+        check_document_id(document_id)?;
+    }
+
+    let fetches = document_ids
+        .iter()
+        .map(|document_id| fetch_document(&state, document_id));
+    let mut response = CaseBatchResponse {
+        documents: Vec::new(),
+        missing: Vec::new(),
+    };
+    for (document_id, fetched) in document_ids.iter().zip(future::join_all(fetches).await) {
+        match fetched {
+            Ok(document) => response.documents.push(document),
+            Err(ApiError::NotFound(_)) => response.missing.push(document_id.clone()),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(ApiJson::new(&state, request_id, response))
+}
+
+fn parse_opinion_types(filter: &[String]) -> Result<Vec<OpinionType>, ApiError> {
+    if filter.is_empty() {
+        return Err(ApiError::BadRequest(
+            "opinion_type_filter must not be empty".to_string(),
+        ));
+    }
+    filter
+        .iter()
+        .map(|value| {
+            value.trim().parse().map_err(|_| {
+                let allowed: Vec<_> = OpinionType::ALL.iter().map(|t| t.as_str()).collect();
+                ApiError::BadRequest(format!(
+                    "opinion_type_filter values must be one of {:?}, got {:?}",
+                    allowed, value
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Results carry the opinion type on the full document when one is
+/// attached, otherwise in the indexed payload; results with neither are
+/// treated as not matching
+fn result_opinion_type(result: &SearchResult) -> Option<OpinionType> {
+    let raw = match &result.full_document {
+        Some(document) => document.opinion_type.as_str(),
+        None => result.metadata.get("opinion_type")?.as_str()?,
+    };
+    raw.parse().ok()
+}
+
+/// Build a search query from a case's facts and issue, within the search
+/// service's length limit
+pub fn facts_query(facts: &str, issue: &str) -> String {
+    format!("{}\n{}", facts.trim(), issue.trim())
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .collect()
+}
+
+/// Trim the query, optionally collapsing internal whitespace runs to one
+/// space; an effectively empty query would only waste a downstream call
+fn normalize_query(raw: &str, collapse_whitespace: bool) -> Result<String, ApiError> {
+    let query = if collapse_whitespace {
+        raw.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        raw.trim().to_string()
+    };
+    if query.is_empty() {
+        return Err(ApiError::BadRequest(
+            "query must contain search text, not only whitespace".to_string(),
+        ));
+    }
+    Ok(query)
+}
+
+/// Multiply each score by its court's weight, rescale so the best is at
+/// most 1, and re-sort. Returns whether any score changed.
+fn boost_by_court(results: &mut [SearchResult], weights: &[(String, f64)]) -> bool {
+    // Boosted scores may pass 1 until rescaled, so they're kept as f64s
+    let mut scores: Vec<f64> = results
+        .iter()
+        .map(|result| result.similarity_score.get())
+        .collect();
+    let mut boosted = false;
+    for (result, score) in results.iter_mut().zip(&mut scores) {
+        let court = result.court.to_lowercase();
+        let Some(&(_, weight)) = weights
+            .iter()
+            .find(|(name, _)| court.contains(name.as_str()))
+        else {
+            continue;
+        };
+        if weight == 1.0 {
+            continue;
+        }
+        result
+            .metadata
+            .insert("court_weight".to_string(), weight.into());
+        result
+            .metadata
+            .insert("unboosted_score".to_string(), (*score).into());
+        *score *= weight;
+        boosted = true;
+    }
+    if !boosted {
+        return false;
+    }
+
+    let top = scores.iter().copied().fold(0.0, f64::max);
+    let scale = top.max(1.0);
+    for (result, score) in results.iter_mut().zip(scores) {
+        result.similarity_score = SimilarityScore::new(score / scale);
+    }
+    results.sort_by(|a, b| b.similarity_score.total_cmp(&a.similarity_score));
+    true
+}
+
+/// Reported in place of section types outside SEARCH_RESULT_SECTION_TYPES
+const OTHER_SECTION_TYPE: &str = "other";
+
+/// Replace a section_type the client contract doesn't include with
+/// [`OTHER_SECTION_TYPE`], keeping the original in metadata. Returns
+/// whether it was replaced.
+fn normalize_section_type(result: &mut SearchResult, allowed: &[String]) -> bool {
+    if allowed.contains(&result.section_type) {
+        return false;
+    }
+    log::warn!(
+        "Search result for {:?} has unrecognized section_type {:?}; reporting it as {:?}",
+        result.case_name,
+        result.section_type,
+        OTHER_SECTION_TYPE
+    );
+    let original = std::mem::replace(&mut result.section_type, OTHER_SECTION_TYPE.to_string());
+    result
+        .metadata
+        .insert("downstream_section_type".to_string(), original.into());
+    true
+}
+
+/// Offsets from the result itself, else from the indexed payload; an
+/// inverted range is dropped rather than passed on
+fn snippet_context(result: &SearchResult) -> SnippetContext {
+    let from_metadata = |key: &str| {
+        result
+            .metadata
+            .get(key)
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+    };
+    let context = SnippetContext {
+        char_start: result
+            .snippet_context
+            .char_start
+            .or_else(|| from_metadata("char_start")),
+        char_end: result
+            .snippet_context
+            .char_end
+            .or_else(|| from_metadata("char_end")),
+    };
+    match (context.char_start, context.char_end) {
+        (Some(start), Some(end)) if end < start => SnippetContext::default(),
+        _ => context,
+    }
+}
+
+/// Case-insensitive matcher for the query's words of three or more
+/// characters; shorter ones ("of", "a") would match almost anywhere
+fn query_terms(query: &str) -> Option<Regex> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() >= 3)
+        .map(regex::escape)
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Regex::new(&format!(r"(?i)\b(?:{})\b", terms.join("|"))).ok()
+}
+
+/// Give a result with no snippet `window` characters of its attached
+/// document: around the first query term in its own section, else in any
+/// other section, else the opening of its section. Offsets are only set
+/// when the text is from its own section, which is what they refer to.
+fn fallback_snippet(result: &mut SearchResult, terms: Option<&Regex>, window: usize) -> bool {
+    if window == 0 || !result.snippet.trim().is_empty() {
+        return false;
+    }
+    let Some(document) = &result.full_document else {
+        return false;
+    };
+    let sections = document.sections();
+    let own = result.section_type.parse::<SectionType>().ok();
+    let order: Vec<SectionType> = own
+        .into_iter()
+        .chain(SectionType::ALL.into_iter().filter(|t| Some(*t) != own))
+        .collect();
+    let matched = terms.and_then(|terms| {
+        order.iter().find_map(|section| {
+            let text = sections.get(section)?;
+            Some((*section, *text, terms.find(text)?.start()))
+        })
+    });
+    let opening = || {
+        let section = own?;
+        let text = sections
+            .get(&section)
+            .filter(|text| !text.trim().is_empty())?;
+        Some((section, *text, 0))
+    };
+    let Some((section, text, at)) = matched.or_else(opening) else {
+        return false;
+    };
+
+    let (start, end) = window_around(text, at, window);
+    result.snippet = text.chars().skip(start).take(end - start).collect();
+    result.snippet_context = if Some(section) == own {
+        SnippetContext {
+            char_start: Some(start),
+            char_end: Some(end),
+        }
+    } else {
+        SnippetContext::default()
+    };
+    result
+        .metadata
+        .insert("snippet_source".to_string(), "gateway".into());
+    true
+}
+
+/// Character range of up to `window` characters centred on byte offset
+/// `at`, shifted inward at either end of `text`
+fn window_around(text: &str, at: usize, window: usize) -> (usize, usize) {
+    let total = text.chars().count();
+    let centre = text[..at].chars().count();
+    let start = centre
+        .saturating_sub(window / 2)
+        .min(total.saturating_sub(window));
+    (start, (start + window).min(total))
+}
+
+/// Cut the snippet to `max_chars` characters, the last being an ellipsis,
+/// recording the original length and pulling `char_end` in to match
+fn truncate_snippet(result: &mut SearchResult, max_chars: usize) {
+    let original_chars = result.snippet.chars().count();
+    if original_chars <= max_chars {
+        return;
+    }
+    let kept = max_chars - 1;
+    let cut = result
+        .snippet
+        .char_indices()
+        .nth(kept)
+        .map_or(result.snippet.len(), |(i, _)| i);
+    result.snippet.truncate(cut);
+    result.snippet.push('…');
+    result
+        .metadata
+        .insert("snippet_original_chars".to_string(), original_chars.into());
+    if let Some(start) = result.snippet_context.char_start {
+        result.snippet_context.char_end = Some(start + kept);
+    }
+}
+
+/// The search service copies the indexed payload, including document_id,
+/// into each result's metadata
+pub fn result_document_id(result: &SearchResult) -> Option<&str> {
+    result
+        .metadata
+        .get("document_id")
+        .and_then(|id| id.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(document_id: &str, score: f64) -> SearchResult {
+        SearchResult {
+            case_name: format!("{} v. State", document_id),
+            year: 2000,
+            court: "Supreme Court".to_string(),
+            section_type: "holding".to_string(),
+            similarity_score: SimilarityScore::new(score),
+            distance: Some(1.0 - score),
+            snippet: String::new(),
+            snippet_context: SnippetContext::default(),
+            full_document: None,
+            metadata: HashMap::from([("document_id".to_string(), document_id.into())]),
+            index: None,
+        }
+    }
+
+    fn ranking(results: &[SearchResult]) -> Vec<(String, f64)> {
+        results
+            .iter()
+            .map(|r| {
+                let id = result_document_id(r).unwrap().to_string();
+                (id, (r.similarity_score.get() * 100.0).round() / 100.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn queries_are_trimmed_and_collapsed_on_request() {
+        let padded = "  implied\n  warranty\tof habitability ";
+        assert_eq!(
+            normalize_query(padded, false).unwrap(),
+            "implied\n  warranty\tof habitability"
+        );
+        assert_eq!(
+            normalize_query(padded, true).unwrap(),
+            "implied warranty of habitability"
+        );
+        for blank in ["", "   ", "\n\t "] {
+            assert!(normalize_query(blank, false).is_err());
+            assert!(normalize_query(blank, true).is_err());
+        }
+    }
+
+    #[test]
+    fn court_weights_rerank_and_keep_scores_within_one() {
+        let mut results = vec![result("a", 0.9), result("b", 0.8), result("c", 0.5)];
+        results[0].court = "Court of Appeals".to_string();
+        results[1].court = "Vermont Supreme Court".to_string();
+        results[2].court = "Superior Court".to_string();
+        let weights = vec![
+            ("supreme court".to_string(), 1.5),
+            ("superior".to_string(), 1.0),
+        ];
+        assert!(boost_by_court(&mut results, &weights));
+        // b: 0.8 * 1.5 = 1.2, rescaled to 1.0; a: 0.9 / 1.2 = 0.75
+        assert_eq!(
+            ranking(&results),
+            [
+                ("b".to_string(), 1.0),
+                ("a".to_string(), 0.75),
+                ("c".to_string(), 0.42)
+            ]
+        );
+        assert_eq!(results[0].metadata["court_weight"], 1.5);
+        assert_eq!(results[0].metadata["unboosted_score"], 0.8);
+        assert!(!results[2].metadata.contains_key("court_weight"));
+
+        let mut unmatched = vec![result("a", 0.9)];
+        unmatched[0].court = "Court of Appeals".to_string();
+        assert!(!boost_by_court(&mut unmatched, &weights));
+        assert_eq!(unmatched[0].similarity_score.get(), 0.9);
+    }
+
+    #[test]
+    fn unrecognized_section_types_become_other() {
+        let allowed = vec!["facts".to_string(), "holding".to_string()];
+        let mut known = result("a", 0.9);
+        assert!(!normalize_section_type(&mut known, &allowed));
+        assert_eq!(known.section_type, "holding");
+        assert!(!known.metadata.contains_key("downstream_section_type"));
+
+        let mut drifted = result("b", 0.8);
+        drifted.section_type = "headnote".to_string();
+        assert!(normalize_section_type(&mut drifted, &allowed));
+        assert_eq!(drifted.section_type, "other");
+        assert_eq!(drifted.metadata["downstream_section_type"], "headnote");
+    }
+
+    #[test]
+    fn blend_sums_weighted_scores_of_sections_found_by_both() {
+        let semantic = vec![result("a", 0.9), result("b", 0.8)];
+        let keyword = vec![result("b", 1.0), result("c", 0.9)];
+        let blended = blend(semantic, keyword, 0.5, 10);
+        assert_eq!(
+            ranking(&blended),
+            [
+                ("b".to_string(), 0.9),
+                ("a".to_string(), 0.45),
+                ("c".to_string(), 0.45)
+            ]
+        );
+        // Keyword-only results have no vector distance
+        assert_eq!(blended[2].distance, None);
+    }
+
+    #[test]
+    fn snippet_windows_centre_on_the_match_within_the_text() {
+        let text = "Café lease: the tenant withheld rent after the heating failed.";
+        let at = text.find("rent").unwrap();
+        assert_eq!(window_around(text, at, 10), (27, 37));
+        assert_eq!(window_around(text, 0, 10), (0, 10));
+        assert_eq!(window_around(text, text.len(), 10), (52, 62));
+        assert_eq!(window_around(text, at, 500), (0, 62));
+
+        let terms = query_terms("withheld, of rent").unwrap();
+        assert_eq!(terms.find("RENT of").unwrap().as_str(), "RENT");
+        assert!(query_terms("of a").is_none());
+    }
+
+    #[test]
+    fn truncates_snippets_on_char_boundaries() {
+        let mut long = result("a", 0.9);
+        long.snippet = "Café lease: implied warranty".to_string();
+        long.snippet_context = SnippetContext {
+            char_start: Some(10),
+            char_end: Some(38),
+        };
+        truncate_snippet(&mut long, 5);
+        assert_eq!(long.snippet, "Café…");
+        assert_eq!(long.metadata["snippet_original_chars"], 28);
+        assert_eq!(long.snippet_context.char_end, Some(14));
+
+        let mut short = result("b", 0.9);
+        short.snippet = "Short".to_string();
+        truncate_snippet(&mut short, 5);
+        assert_eq!(short.snippet, "Short");
+        assert!(!short.metadata.contains_key("snippet_original_chars"));
+    }
+
+    #[test]
+    fn blend_keeps_top_k() {
+        let semantic = vec![result("a", 0.9), result("b", 0.8)];
+        let keyword = vec![result("c", 0.7)];
+        assert_eq!(ranking(&blend(semantic, keyword, 1.0, 2)).len(), 2);
+    }
+}
@@ -0,0 +1,137 @@
+use axum::extract::{Query, State};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::downstream::Service;
+use crate::error::ApiError;
+use crate::models::{OutcomeDistribution, OutcomeFilters, OutcomeShare, StatsResponse};
+use crate::request_id::RequestId;
+use crate::response::ApiJson;
+use crate::state::AppState;
+use crate::stats;
+use crate::validation::{MAX_YEAR, MIN_YEAR};
+
+/// Shape returned by the search service's /stats/outcomes
+#[derive(Deserialize)]
+struct OutcomeCounts {
+    counts: BTreeMap<String, u64>,
+}
+
+/// Serve the cached snapshot, fetching on demand until the background
+/// refresher has produced one (or always, when it is disabled)
+pub async fn get_stats(
+    State(state): State<AppState>,
+    request_id: RequestId,
+) -> Result<ApiJson<StatsResponse>, ApiError> {
+    let cached = match state.config().stats_refresh_interval.is_zero() {
+        true => None,
+        false => state.stats.get(),
+    };
+    let snapshot = match cached {
+        Some(snapshot) => snapshot,
+        None => stats::refresh(&state).await?,
+    };
+    Ok(ApiJson::new(&state, request_id, snapshot))
+}
+
+/// Share of indexed cases per final judgment, optionally narrowed to a
+/// court and/or year range
+pub async fn outcome_distribution(
+    State(state): State<AppState>,
+    request_id: RequestId,
+    Query(mut filters): Query<OutcomeFilters>,
+) -> Result<ApiJson<OutcomeDistribution>, ApiError> {
+    filters.court = filters
+        .court
+        .map(|court| court.trim().to_string())
+        .filter(|court| !court.is_empty());
+    check_year_filters(&filters)?;
+
+    let OutcomeCounts { counts } = state
+        .downstream
+        .get_json_with_query(
+            &state.config(),
+            Service::Search,
+            "/stats/outcomes",
+            &filters,
+        )
+        .await?;
+    Ok(ApiJson::new(
+        &state,
+        request_id,
+        distribution(counts, filters),
+    ))
+}
+
+fn check_year_filters(filters: &OutcomeFilters) -> Result<(), ApiError> {
+    for (name, year) in [
+        ("year_from", filters.year_from),
+        ("year_to", filters.year_to),
+    ] {
+        if let Some(year) = year.filter(|y| !(MIN_YEAR..=MAX_YEAR).contains(y)) {
+            return Err(ApiError::BadRequest(format!(
+                "{} must be between {} and {}, got {}",
+                name, MIN_YEAR, MAX_YEAR, year
+            )));
+        }
+    }
+    if let (Some(from), Some(to)) = (filters.year_from, filters.year_to) {
+        if from > to {
+            return Err(ApiError::BadRequest(format!(
+                "year_from ({}) must not be after year_to ({})",
+                from, to
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn distribution(counts: BTreeMap<String, u64>, filters: OutcomeFilters) -> OutcomeDistribution {
+    let total_cases: u64 = counts.values().sum();
+    let outcomes = counts
+        .into_iter()
+        .map(|(outcome, count)| {
+            let proportion = match total_cases {
+                0 => 0.0,
+                total => count as f64 / total as f64,
+            };
+            (outcome, OutcomeShare { count, proportion })
+        })
+        .collect();
+    OutcomeDistribution {
+        total_cases,
+        outcomes,
+        filters,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proportions_sum_to_one() {
+        let counts = BTreeMap::from([("Affirmed".to_string(), 3), ("Reversed".to_string(), 1)]);
+        let dist = distribution(counts, OutcomeFilters::default());
+        assert_eq!(dist.total_cases, 4);
+        assert_eq!(dist.outcomes["Affirmed"].proportion, 0.75);
+        assert_eq!(dist.outcomes["Reversed"].proportion, 0.25);
+    }
+
+    #[test]
+    fn empty_corpus_has_no_outcomes() {
+        let dist = distribution(BTreeMap::new(), OutcomeFilters::default());
+        assert_eq!(dist.total_cases, 0);
+        assert!(dist.outcomes.is_empty());
+    }
+
+    #[test]
+    fn rejects_inverted_year_range() {
+        let filters = OutcomeFilters {
+            court: None,
+            year_from: Some(2010),
+            year_to: Some(2000),
+        };
+        assert!(check_year_filters(&filters).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Bounded in-memory index of recent analyses, listed per token at
+//! GET /api/analyze-brief/history for activity views
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::AnalyzeResponse;
+use crate::timestamp;
+
+/// One analysis, as listed in the history
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisSummary {
+    /// The job_id for ?mode=async analyses, pollable at
+    /// GET /api/analyze-brief/:id; otherwise the request_id
+    pub analysis_id: String,
+    pub asynchronous: bool,
+    pub analyzed_at: String,
+    pub predicted_outcome: String,
+    pub case_count: usize,
+}
+
+impl AnalysisSummary {
+    pub fn new(analysis_id: String, asynchronous: bool, analysis: &AnalyzeResponse) -> Self {
+        Self {
+            analysis_id,
+            asynchronous,
+            analyzed_at: timestamp::now_rfc3339(),
+            predicted_outcome: analysis.predicted_outcome.label.clone(),
+            case_count: analysis.top_cases.len(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    summary: AnalysisSummary,
+    recorded_at: Instant,
+}
+
+/// One ring of entries per token name, so a busy token can't evict
+/// another's history
+#[derive(Debug, Default)]
+pub struct AnalysisHistory {
+    entries: Mutex<HashMap<String, VecDeque<Entry>>>,
+}
+
+impl AnalysisHistory {
+    /// Append a summary to `owner`'s ring, evicting its oldest past
+    /// `capacity` (0 keeps none) and any entry older than `retention`
+    pub fn record(
+        &self,
+        owner: &str,
+        summary: AnalysisSummary,
+        capacity: usize,
+        retention: Duration,
+    ) {
+        let mut entries = self.entries.lock().unwrap();
+        for ring in entries.values_mut() {
+            ring.retain(|entry| entry.recorded_at.elapsed() < retention);
+        }
+        entries.retain(|_, ring| !ring.is_empty());
+        if capacity == 0 {
+            return;
+        }
+        let ring = entries.entry(owner.to_string()).or_default();
+        while ring.len() >= capacity {
+            ring.pop_front();
+        }
+        ring.push_back(Entry {
+            summary,
+            recorded_at: Instant::now(),
+        });
+    }
+
+    /// `owner`'s analyses within `retention`, newest first
+    pub fn recent(&self, owner: &str, retention: Duration) -> Vec<AnalysisSummary> {
+        let entries = self.entries.lock().unwrap();
+        let Some(ring) = entries.get(owner) else {
+            return Vec::new();
+        };
+        ring.iter()
+            .rev()
+            .filter(|entry| entry.recorded_at.elapsed() < retention)
+            .map(|entry| entry.summary.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str) -> AnalysisSummary {
+        AnalysisSummary {
+            analysis_id: id.to_string(),
+            asynchronous: false,
+            analyzed_at: String::new(),
+            predicted_outcome: "affirmed".to_string(),
+            case_count: 0,
+        }
+    }
+
+    #[test]
+    fn lists_only_the_owners_entries_newest_first_within_capacity() {
+        let history = AnalysisHistory::default();
+        let retention = Duration::from_secs(60);
+        for (owner, id) in [("a", "1"), ("b", "2"), ("a", "3"), ("a", "4"), ("a", "5")] {
+            history.record(owner, summary(id), 3, retention);
+        }
+        let ids = |owner: &str| -> Vec<String> {
+            history
+                .recent(owner, retention)
+                .into_iter()
+                .map(|summary| summary.analysis_id)
+                .collect()
+        };
+        assert_eq!(ids("a"), ["5", "4", "3"]);
+        assert_eq!(ids("b"), ["2"]);
+        assert!(ids("c").is_empty());
+        assert!(history.recent("a", Duration::ZERO).is_empty());
+
+        history.record("a", summary("6"), 0, retention);
+        assert_eq!(ids("a"), ["5", "4", "3"]);
+    }
+
+    #[test]
+    fn caps_each_owner_separately() {
+        let history = AnalysisHistory::default();
+        let retention = Duration::from_secs(60);
+        for id in 1..=5 {
+            history.record("a", summary(&format!("a{}", id)), 2, retention);
+            history.record("b", summary(&format!("b{}", id)), 2, retention);
+        }
+        let ids = |owner: &str| -> Vec<String> {
+            history
+                .recent(owner, retention)
+                .into_iter()
+                .map(|summary| summary.analysis_id)
+                .collect()
+        };
+        assert_eq!(ids("a"), ["a5", "a4"]);
+        assert_eq!(ids("b"), ["b5", "b4"]);
+    }
+}
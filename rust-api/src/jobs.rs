@@ -0,0 +1,167 @@
+//! In-memory store of async /api/analyze-brief jobs, polled at
+//! GET /api/analyze-brief/:id and reported to webhook callbacks
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::models::{AnalyzeResponse, ErrorResponse};
+use crate::timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    /// Stopped by DELETE /api/analyze-brief/:id before it finished
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// What clients see when polling, and the body POSTed to a callback_url
+#[derive(Debug, Clone, Serialize)]
+pub struct JobView {
+    pub job_id: String,
+    pub status: JobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<AnalyzeResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+}
+
+#[derive(Debug)]
+struct Job {
+    view: JobView,
+    finished_at: Option<Instant>,
+    /// Fired by [`JobStore::cancel`]; the job's task stops on it
+    cancel: CancellationToken,
+    /// The principal that submitted the job, if it was authenticated
+    owner: Option<String>,
+}
+
+impl Job {
+    /// Owned jobs are only visible to their owner; to anyone else they
+    /// look unknown, so job ids can't be probed across tenants
+    fn visible_to(&self, caller: Option<&str>) -> bool {
+        match &self.owner {
+            Some(owner) => caller == Some(owner.as_str()),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct JobStore {
+    jobs: Mutex<HashMap<String, Job>>,
+}
+
+impl JobStore {
+    /// Register a pending job, first dropping finished jobs older than
+    /// `retention`. The task running it should stop when the token fires.
+    pub fn create(
+        &self,
+        retention: Duration,
+        owner: Option<String>,
+    ) -> (JobView, CancellationToken) {
+        let view = JobView {
+            job_id: uuid::Uuid::new_v4().to_string(),
+            status: JobStatus::Pending,
+            result: None,
+            error: None,
+            created_at: timestamp::now_rfc3339(),
+            completed_at: None,
+        };
+
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.retain(
+            |_, job| !matches!(job.finished_at, Some(finished) if finished.elapsed() >= retention),
+        );
+        let cancel = CancellationToken::new();
+        jobs.insert(
+            view.job_id.clone(),
+            Job {
+                view: view.clone(),
+                finished_at: None,
+                cancel: cancel.clone(),
+                owner,
+            },
+        );
+        (view, cancel)
+    }
+
+    /// The job as `caller` may see it; None if unknown or someone else's
+    pub fn get(&self, job_id: &str, caller: Option<&str>) -> Option<JobView> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .get(job_id)
+            .filter(|job| job.visible_to(caller))
+            .map(|job| job.view.clone())
+    }
+
+    pub fn mark_running(&self, job_id: &str) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(job_id) {
+            if job.view.status == JobStatus::Pending {
+                job.view.status = JobStatus::Running;
+            }
+        }
+    }
+
+    /// Record the outcome and return the final view; None if the job is
+    /// gone or was cancelled meanwhile
+    pub fn finish(
+        &self,
+        job_id: &str,
+        outcome: Result<AnalyzeResponse, ErrorResponse>,
+    ) -> Option<JobView> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.get_mut(job_id)?;
+        if job.view.status == JobStatus::Cancelled {
+            return None;
+        }
+        match outcome {
+            Ok(result) => {
+                job.view.status = JobStatus::Completed;
+                job.view.result = Some(result);
+            }
+            Err(error) => {
+                job.view.status = JobStatus::Failed;
+                job.view.error = Some(error);
+            }
+        }
+        job.view.completed_at = Some(timestamp::now_rfc3339());
+        job.finished_at = Some(Instant::now());
+        Some(job.view.clone())
+    }
+
+    /// Cancel an unfinished job, returning its view and whether this call
+    /// cancelled it; finished jobs are left as they are. None if unknown
+    /// or someone else's.
+    pub fn cancel(&self, job_id: &str, caller: Option<&str>) -> Option<(JobView, bool)> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.get_mut(job_id).filter(|job| job.visible_to(caller))?;
+        if job.view.status.is_finished() {
+            return Some((job.view.clone(), false));
+        }
+        job.cancel.cancel();
+        job.view.status = JobStatus::Cancelled;
+        job.view.completed_at = Some(timestamp::now_rfc3339());
+        job.finished_at = Some(Instant::now());
+        Some((job.view.clone(), true))
+    }
+}
@@ -0,0 +1,46 @@
+//! JSON request bodies whose size-limit rejection is a 413 ErrorResponse
+
+use axum::{
+    async_trait,
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+
+use crate::body_timeout;
+use crate::error::ApiError;
+
+/// Drop-in for `axum::Json` on request bodies. Bodies over the route's
+/// DefaultBodyLimit become ApiError::PayloadTooLarge and bodies cut off by
+/// REQUEST_BODY_TIMEOUT_SECONDS ApiError::RequestTimeout; other rejections
+/// keep axum's default response.
+#[derive(Debug, Clone)]
+pub struct JsonBody<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(request, state).await {
+            Ok(Json(value)) => Ok(JsonBody(value)),
+            Err(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                Err(ApiError::PayloadTooLarge(body_text(&rejection)).into_response())
+            }
+            Err(rejection) => match body_timeout::cause(&rejection) {
+                Some(timeout) => Err(ApiError::RequestTimeout(timeout.to_string()).into_response()),
+                None => Err(rejection.into_response()),
+            },
+        }
+    }
+}
+
+fn body_text(rejection: &JsonRejection) -> String {
+    format!("JSON body too large: {}", rejection.body_text())
+}
@@ -0,0 +1,161 @@
+//! Lightweight stopword-based language detection for OCR samples
+//! Returns Tesseract language codes so the result can be passed straight to OCR
+
+/// Each word is listed under one language only, so a hit is evidence for
+/// that language alone; articles and conjunctions several languages share
+/// (`de`, `la`, `que`, `e`, ...) are left out for that reason
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "eng",
+        &[
+            "the", "and", "of", "to", "that", "is", "was", "for", "with", "on", "by", "this",
+            "which", "are", "be", "it",
+        ],
+    ),
+    (
+        "spa",
+        &[
+            "el", "y", "los", "las", "del", "por", "una", "es", "fue", "pero", "sus", "cuando",
+            "donde", "según", "muy",
+        ],
+    ),
+    (
+        "fra",
+        &[
+            "le", "les", "et", "des", "du", "est", "une", "dans", "pour", "au", "aux", "ce",
+            "sont", "pas", "ont", "sur", "été", "cette",
+        ],
+    ),
+    (
+        "deu",
+        &[
+            "der", "die", "und", "das", "ist", "den", "von", "mit", "nicht", "ein", "zu", "dem",
+            "eine", "sich", "auf", "wird", "auch", "nach",
+        ],
+    ),
+    (
+        "ita",
+        &[
+            "il", "di", "che", "della", "non", "sono", "gli", "nel", "alla", "dei", "delle",
+            "questo", "anche", "è", "per",
+        ],
+    ),
+    (
+        "por",
+        &[
+            "o", "do", "dos", "em", "os", "com", "uma", "não", "ao", "aos", "pelo", "pela", "foi",
+            "é", "são", "sua", "seu",
+        ],
+    ),
+];
+
+/// Fewer stopword hits than this is too little text to judge
+const MIN_HITS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    pub language: &'static str,
+    /// Share of stopword hits belonging to the winning language
+    pub confidence: f64,
+}
+
+pub fn detect(text: &str) -> Option<Detection> {
+    let mut hits = vec![0usize; STOPWORDS.len()];
+    for word in text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+    {
+        let word = word.to_lowercase();
+        for (i, (_, words)) in STOPWORDS.iter().enumerate() {
+            if words.contains(&word.as_str()) {
+                hits[i] += 1;
+            }
+        }
+    }
+
+    let total: usize = hits.iter().sum();
+    if total < MIN_HITS {
+        return None;
+    }
+    // On a tie the earlier language wins; confidence is then at most 0.5
+    let (best, best_hits) =
+        hits.iter()
+            .enumerate()
+            .fold((0, &0), |best, hit| if hit.1 > best.1 { hit } else { best });
+    Some(Detection {
+        language: STOPWORDS[best].0,
+        confidence: *best_hits as f64 / total as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn detected(text: &str) -> Detection {
+        detect(text).expect("enough stopwords to judge")
+    }
+
+    #[test]
+    fn each_stopword_belongs_to_one_language() {
+        let mut seen = HashSet::new();
+        for (language, words) in STOPWORDS {
+            for word in *words {
+                assert!(
+                    seen.insert(*word),
+                    "{:?} is listed twice ({})",
+                    word,
+                    language
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn detects_legal_prose_above_the_default_threshold() {
+        let samples = [
+            (
+                "eng",
+                "The tenant withheld rent because the landlord failed to repair the heating, \
+                 and the court held that the lease was breached by the landlord.",
+            ),
+            (
+                "spa",
+                "El tribunal de apelación confirmó la sentencia del juzgado, pero los \
+                 demandantes alegaron que las pruebas presentadas por el fiscal no fueron \
+                 suficientes y que la decisión fue contraria a la ley.",
+            ),
+            (
+                "fra",
+                "La cour d'appel a confirmé le jugement du tribunal, mais les requérants \
+                 soutiennent que les preuves présentées dans cette affaire ne sont pas \
+                 suffisantes pour établir la faute.",
+            ),
+            (
+                "por",
+                "O tribunal de recurso confirmou a decisão do juiz, mas os autores \
+                 alegaram que as provas apresentadas pelo réu não foram suficientes e que \
+                 a sentença foi contrária à lei.",
+            ),
+        ];
+        for (language, text) in samples {
+            let detection = detected(text);
+            assert_eq!(detection.language, language, "{}", text);
+            assert!(detection.confidence >= 0.6, "{}: {:?}", language, detection);
+        }
+    }
+
+    #[test]
+    fn short_text_is_too_little_to_judge() {
+        assert_eq!(detect("Smith v. Jones, No. 22-104"), None);
+        assert_eq!(detect("the court and the lease"), None);
+    }
+
+    #[test]
+    fn ties_go_to_the_earlier_language_below_the_threshold() {
+        let detection = detected("the and of el y los");
+        assert_eq!(detection.language, "eng");
+        assert_eq!(detection.confidence, 0.5);
+    }
+}
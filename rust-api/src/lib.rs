@@ -0,0 +1,176 @@
+//! Legal Judge API gateway
+//! Fronts the Python OCR / search / prediction / opinion services behind one HTTP API
+
+pub mod access_log;
+pub mod allowlist;
+pub mod auth;
+pub mod body_timeout;
+pub mod callback;
+pub mod citation;
+pub mod compare;
+pub mod concurrency;
+pub mod config;
+pub mod court;
+pub mod csv;
+pub mod deadline;
+pub mod disk;
+pub mod downstream;
+pub mod error;
+pub mod fields;
+pub mod forwarded;
+pub mod handlers;
+pub mod history;
+pub mod injection;
+pub mod jobs;
+pub mod json;
+pub mod language;
+pub mod metrics;
+pub mod mime;
+pub mod models;
+pub mod normalize;
+pub mod ocr;
+pub mod pdf;
+pub mod pipeline;
+pub mod range;
+pub mod remote;
+pub mod report;
+pub mod request_id;
+pub mod response;
+pub mod retry;
+pub mod signing;
+pub mod state;
+pub mod stats;
+pub mod store;
+pub mod timestamp;
+pub mod upload;
+pub mod validation;
+pub mod warmup;
+
+use axum::{
+    extract::{DefaultBodyLimit, Extension},
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use tower_http::cors::CorsLayer;
+
+use crate::handlers::fallback::ApiPrefix;
+use crate::state::AppState;
+
+pub fn app(state: AppState) -> Router {
+    let config = state.config();
+    let upload_limit = DefaultBodyLimit::max(config.max_upload_bytes);
+    let json_limit = DefaultBodyLimit::max(config.max_json_body_bytes);
+
+    let routes = Router::new()
+        .route("/admin/reload", post(handlers::admin::reload_config))
+        .route("/health", get(handlers::health::health_check))
+        .route("/metrics", get(handlers::health::metrics))
+        .route("/api/version", get(handlers::health::version))
+        .route("/api/schema/:model", get(handlers::schema::model_schema))
+        .route(
+            "/api/analyze-brief",
+            post(handlers::analyze::analyze_brief).layer(upload_limit),
+        )
+        .route(
+            "/api/analyze-url",
+            post(handlers::analyze::analyze_url).layer(json_limit),
+        )
+        .route(
+            "/api/analyze-brief/history",
+            get(handlers::analyze::analysis_history),
+        )
+        .route(
+            "/api/analyze-brief/:id",
+            get(handlers::analyze::analysis_job)
+                .delete(handlers::analyze::cancel_analysis_job),
+        )
+        .route(
+            "/api/analyze-brief/:id/report.pdf",
+            get(handlers::analyze::analysis_report),
+        )
+        .route("/api/ocr-text/:id", get(handlers::analyze::ocr_text))
+        .route(
+            "/api/ingest",
+            post(handlers::ingest::ingest_document).layer(json_limit),
+        )
+        .route(
+            "/api/validate",
+            post(handlers::ingest::validate_document).layer(json_limit),
+        )
+        .route("/api/stats", get(handlers::stats::get_stats))
+        .route(
+            "/api/stats/outcomes",
+            get(handlers::stats::outcome_distribution),
+        )
+        .route(
+            "/api/search",
+            get(handlers::search::search_query)
+                .post(handlers::search::search)
+                .layer(json_limit),
+        )
+        .route(
+            "/api/similar-cases/:document_id",
+            get(handlers::search::similar_cases),
+        )
+        .route(
+            "/api/case/by-citation",
+            get(handlers::search::case_by_citation),
+        )
+        .route(
+            "/api/cases/batch",
+            post(handlers::search::cases_batch).layer(json_limit),
+        )
+        .route("/api/batch", post(handlers::batch::batch).layer(json_limit))
+        .route(
+            "/api/predict",
+            post(handlers::predict::predict).layer(json_limit),
+        )
+        .route(
+            "/api/predict/explain",
+            post(handlers::predict::explain).layer(json_limit),
+        )
+        .route(
+            "/api/generate-opinion",
+            post(handlers::opinion::generate_opinion).layer(json_limit),
+        )
+        .route(
+            "/api/generate-opinion/stream",
+            post(handlers::opinion::generate_opinion_stream).layer(json_limit),
+        )
+        .route("/api/opinion/:id", get(handlers::opinion::get_opinion));
+
+    // Probes keep working at the root whatever the prefix
+    let prefix = config.api_prefix.clone();
+    let router = match prefix.as_str() {
+        "" => routes,
+        prefix => Router::new()
+            .nest(prefix, routes)
+            .route("/health", get(handlers::health::health_check)),
+    };
+
+    router
+        .fallback(handlers::fallback::not_found)
+        .method_not_allowed_fallback(handlers::fallback::method_not_allowed)
+        .layer(Extension(ApiPrefix(prefix)))
+        .layer(middleware::from_fn(deadline::apply_deadline))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            forwarded::capture_headers,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            body_timeout::limit_body_time,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            access_log::log_request,
+        ))
+        .layer(middleware::from_fn(request_id::assign_request_id))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            signing::sign_response,
+        ))
+        .layer(CorsLayer::permissive())
+        .with_state(state)
+}
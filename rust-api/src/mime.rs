@@ -0,0 +1,113 @@
+//! Upload type checks against UPLOAD_ALLOWED_MIME_TYPES, by declared
+//! Content-Type and by magic bytes
+
+use crate::error::ApiError;
+
+/// Types the gateway can recognise, with the leading bytes that identify
+/// them. Supporting a new format means adding it here (and teaching the OCR
+/// path to handle it) before it can be listed in UPLOAD_ALLOWED_MIME_TYPES.
+pub const SIGNATURES: &[(&str, &[u8])] = &[("application/pdf", b"%PDF-")];
+
+/// Enough of the file to match any signature
+pub const SNIFF_LEN: usize = 8;
+
+/// Clients that don't know the type send this, or nothing
+const UNSPECIFIED: &str = "application/octet-stream";
+
+pub fn is_known(mime: &str) -> bool {
+    SIGNATURES.iter().any(|(known, _)| *known == mime)
+}
+
+/// The declared type, lower-cased and without parameters; `None` when the
+/// client didn't say
+pub fn declared_type(content_type: Option<&str>) -> Option<String> {
+    let mime = content_type?
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    (!mime.is_empty() && mime != UNSPECIFIED).then_some(mime)
+}
+
+/// Reject a declared type outside the allowlist before reading the upload
+pub fn check_declared(declared: Option<&str>, allowed: &[String]) -> Result<(), ApiError> {
+    match declared {
+        Some(mime) if !allowed.iter().any(|a| a == mime) => Err(unsupported(mime, allowed)),
+        _ => Ok(()),
+    }
+}
+
+/// Identify the upload by its first bytes, requiring an allowed type that
+/// agrees with the declared one, if any
+pub fn check_content(
+    prefix: &[u8],
+    declared: Option<&str>,
+    allowed: &[String],
+) -> Result<&'static str, ApiError> {
+    let sniffed = SIGNATURES
+        .iter()
+        .find(|(_, magic)| prefix.starts_with(magic))
+        .map(|(mime, _)| *mime);
+    match sniffed {
+        Some(mime) if allowed.iter().any(|a| a == mime) && declared.unwrap_or(mime) == mime => {
+            Ok(mime)
+        }
+        Some(mime) => Err(unsupported(mime, allowed)),
+        None => {
+            let found = match declared {
+                Some(mime) => format!("{} (content does not match)", mime),
+                None => "(unrecognised content)".to_string(),
+            };
+            Err(unsupported(&found, allowed))
+        }
+    }
+}
+
+fn unsupported(found: &str, allowed: &[String]) -> ApiError {
+    ApiError::UnsupportedMediaType {
+        found: found.to_string(),
+        allowed: allowed.join(", "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pdf_only() -> Vec<String> {
+        vec!["application/pdf".to_string()]
+    }
+
+    #[test]
+    fn declared_type_ignores_parameters_and_octet_stream() {
+        assert_eq!(
+            declared_type(Some("Application/PDF; name=brief.pdf")).as_deref(),
+            Some("application/pdf")
+        );
+        assert_eq!(declared_type(Some("application/octet-stream")), None);
+        assert_eq!(declared_type(None), None);
+    }
+
+    #[test]
+    fn rejects_declared_types_outside_the_allowlist() {
+        assert!(check_declared(Some("application/pdf"), &pdf_only()).is_ok());
+        assert!(check_declared(None, &pdf_only()).is_ok());
+        assert!(matches!(
+            check_declared(Some("image/png"), &pdf_only()),
+            Err(ApiError::UnsupportedMediaType { .. })
+        ));
+    }
+
+    #[test]
+    fn magic_bytes_must_match() {
+        let allowed = pdf_only();
+        assert_eq!(
+            check_content(b"%PDF-1.7\n", None, &allowed).unwrap(),
+            "application/pdf"
+        );
+        assert!(check_content(b"%PDF-1.7\n", Some("application/pdf"), &allowed).is_ok());
+        assert!(check_content(b"PK\x03\x04", Some("application/pdf"), &allowed).is_err());
+        assert!(check_content(b"", None, &allowed).is_err());
+    }
+}
@@ -1,10 +1,14 @@
-/// Rust data models matching Python Pydantic schemas
-/// These models ensure type-safe communication between Rust API gateway and Python services
+//! Rust data models matching Python Pydantic schemas
+//! These models ensure type-safe communication between Rust API gateway and Python services
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::court::CourtLevel;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CaseLawDocument {
     pub case_name: String,
     pub year: i32,
@@ -26,13 +30,160 @@ pub struct CaseLawDocument {
     pub lower_court: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub procedural_history: Option<String>,
+    /// Reporter citation, e.g. `478 A.2d 202`; what GET /api/case/by-citation matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub citation: Option<String>,
     
     pub document_id: String,
     pub ingestion_timestamp: String,
     pub validation_status: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl CaseLawDocument {
+    /// The text sections keyed by type, for code that treats them uniformly
+    pub fn sections(&self) -> HashMap<SectionType, &str> {
+        HashMap::from([
+            (SectionType::Facts, self.facts.as_str()),
+            (SectionType::Issue, self.issue.as_str()),
+            (SectionType::Reasoning, self.reasoning.as_str()),
+            (SectionType::Holding, self.holding.as_str()),
+        ])
+    }
+
+    /// [`CourtLevel::classify`] applied to `court`
+    pub fn court_level(&self) -> CourtLevel {
+        CourtLevel::classify(&self.court)
+    }
+}
+
+/// Section a search result or filter refers to; mirrors the section_type
+/// values the Python services index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionType {
+    Facts,
+    Issue,
+    Reasoning,
+    Holding,
+    /// Indexed as its own vector, but not a CaseLawDocument text section
+    Judgment,
+}
+
+impl SectionType {
+    pub const ALL: [SectionType; 5] = [
+        SectionType::Facts,
+        SectionType::Issue,
+        SectionType::Reasoning,
+        SectionType::Holding,
+        SectionType::Judgment,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SectionType::Facts => "facts",
+            SectionType::Issue => "issue",
+            SectionType::Reasoning => "reasoning",
+            SectionType::Holding => "holding",
+            SectionType::Judgment => "judgment",
+        }
+    }
+}
+
+impl std::str::FromStr for SectionType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SectionType::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| format!("unknown section type {:?}", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OpinionType {
+    PerCuriam,
+    Majority,
+    Concurring,
+    Dissenting,
+}
+
+impl OpinionType {
+    pub const ALL: [OpinionType; 4] = [
+        OpinionType::PerCuriam,
+        OpinionType::Majority,
+        OpinionType::Concurring,
+        OpinionType::Dissenting,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OpinionType::PerCuriam => "per_curiam",
+            OpinionType::Majority => "majority",
+            OpinionType::Concurring => "concurring",
+            OpinionType::Dissenting => "dissenting",
+        }
+    }
+}
+
+impl std::str::FromStr for OpinionType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OpinionType::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| format!("unknown opinion type {:?}", s))
+    }
+}
+
+/// The gateway's outcome vocabulary. Prediction labels are translated into
+/// it through OUTCOME_LABEL_MAP, so clients see the same labels whichever
+/// backend predicted them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum Outcome {
+    Affirmed,
+    Reversed,
+    Remanded,
+    Mixed,
+    /// A label with no mapping
+    Other,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 5] = [
+        Outcome::Affirmed,
+        Outcome::Reversed,
+        Outcome::Remanded,
+        Outcome::Mixed,
+        Outcome::Other,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Affirmed => "Affirmed",
+            Outcome::Reversed => "Reversed",
+            Outcome::Remanded => "Remanded",
+            Outcome::Mixed => "Mixed",
+            Outcome::Other => "Other",
+        }
+    }
+}
+
+impl std::str::FromStr for Outcome {
+    type Err = String;
+
+    /// Case-insensitive, so the prediction service's `AFFIRMED` is canonical too
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Outcome::ALL
+            .into_iter()
+            .find(|outcome| outcome.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown outcome {:?}", s))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SearchRequest {
     pub query: String,
     #[serde(default = "default_top_k")]
@@ -43,79 +194,349 @@ pub struct SearchRequest {
     pub year_range: Option<Vec<i32>>,
     #[serde(default = "default_min_similarity")]
     pub min_similarity: f64,
+    /// Keep only results whose opinion_type is one of these OpinionType values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opinion_type_filter: Option<Vec<String>>,
+    #[serde(default)]
+    pub mode: SearchMode,
+    /// Truncate snippets to this many characters; defaults to SEARCH_MAX_SNIPPET_CHARS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_snippet_chars: Option<usize>,
+    /// Keep only results from these documents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_ids: Option<Vec<String>>,
+    /// SEARCH_INDICES names to query instead of SEARCH_SERVICE_URL; the
+    /// gateway clears it before forwarding
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indices: Vec<String>,
+}
+
+/// `hybrid` runs both downstream modes and blends them in the gateway
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    #[default]
+    Semantic,
+    Keyword,
+    Hybrid,
+}
+
+impl std::str::FromStr for SearchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "semantic" => Ok(SearchMode::Semantic),
+            "keyword" => Ok(SearchMode::Keyword),
+            "hybrid" => Ok(SearchMode::Hybrid),
+            _ => Err(format!("unknown search mode {:?}", s)),
+        }
+    }
+}
+
+impl SearchRequest {
+    /// A semantic search for `query` with the same defaults a JSON body gets
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            top_k: default_top_k(),
+            section_filter: None,
+            year_range: None,
+            min_similarity: default_min_similarity(),
+            opinion_type_filter: None,
+            mode: SearchMode::default(),
+            max_snippet_chars: None,
+            document_ids: None,
+            indices: Vec::new(),
+        }
+    }
+
+    pub fn with_top_k(mut self, top_k: i32) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    pub fn with_min_similarity(mut self, min_similarity: f64) -> Self {
+        self.min_similarity = min_similarity;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_document_ids(mut self, document_ids: Vec<String>) -> Self {
+        self.document_ids = Some(document_ids);
+        self
+    }
 }
 
 fn default_top_k() -> i32 { 10 }
 fn default_min_similarity() -> f64 { 0.6 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A similarity or relevance score, always within [0, 1]; serialized as a
+/// plain number. Values outside the range (from a downstream service, or
+/// NaN) are clamped with a warning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, JsonSchema)]
+#[serde(transparent)]
+pub struct SimilarityScore(#[schemars(range(min = 0, max = 1))] f64);
+
+impl SimilarityScore {
+    pub fn new(value: f64) -> Self {
+        if (0.0..=1.0).contains(&value) {
+            return Self(value);
+        }
+        let clamped = if value > 1.0 { 1.0 } else { 0.0 };
+        log::warn!(
+            "Similarity score {} is outside [0, 1]; clamping to {}",
+            value,
+            clamped
+        );
+        Self(clamped)
+    }
+
+    pub fn get(self) -> f64 {
+        self.0
+    }
+
+    /// For sorting; scores are never NaN
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for SimilarityScore {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SearchResult {
     pub case_name: String,
     pub year: i32,
     pub court: String,
     pub section_type: String,
-    pub similarity_score: f64,
+    /// Normalized 0-1 score after re-ranking
+    pub similarity_score: SimilarityScore,
+    /// Raw vector distance under `SearchResponse::distance_metric`, for
+    /// clients that re-rank themselves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f64>,
     pub snippet: String,
+    #[serde(flatten)]
+    pub snippet_context: SnippetContext,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub full_document: Option<CaseLawDocument>,
     pub metadata: HashMap<String, serde_json::Value>,
+    /// The SEARCH_INDICES index this came from, when the request named some
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+}
+
+impl SearchResult {
+    /// [`CourtLevel::classify`] applied to `court`
+    pub fn court_level(&self) -> CourtLevel {
+        CourtLevel::classify(&self.court)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where a snippet sits in its source section, as character offsets
+/// (`char_end` exclusive); omitted when the search service doesn't say
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SnippetContext {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub char_start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub char_end: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SearchResponse {
     pub status: String,
     pub query: String,
     pub results: Vec<SearchResult>,
     pub total_results: usize,
     pub search_time_ms: u64,
+    /// e.g. "cosine"; omitted when the search service doesn't report it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_metric: Option<String>,
+    /// top_k the search ran with, after clamping to MAX_TOP_K
+    #[serde(default)]
+    pub effective_top_k: i32,
+    /// Set to the requested top_k when MAX_TOP_K lowered it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requested_top_k: Option<i32>,
+    /// Set when GLOBAL_MIN_SIMILARITY raised the requested min_similarity to this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_similarity_floor: Option<f64>,
+    /// SEARCH_COURT_WEIGHTS changed at least one score; boosted results
+    /// carry `court_weight` and `unboosted_score` in their metadata
+    #[serde(default)]
+    pub court_boost_applied: bool,
+    /// What to do next when status is "no_corpus"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guidance: Option<String>,
+}
+
+/// Body of POST /api/cases/batch, for hydrating lightweight search results
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CaseBatchRequest {
+    pub document_ids: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CaseBatchResponse {
+    /// In the order requested, repeats dropped
+    pub documents: Vec<CaseLawDocument>,
+    /// Requested ids the ingestion service has no document for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PredictionRequest {
     pub facts: String,
     pub issue: String,
+    /// Defaults to the gateway's DEFAULT_JURISDICTION when omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jurisdiction: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Body of POST /api/predict/explain: the facts being predicted on and the
+/// precedent to relate them to
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExplainRequest {
+    #[serde(flatten)]
+    pub prediction: PredictionRequest,
+    pub document_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PrecedentExplanation {
+    pub status: String,
+    pub document_id: String,
+    pub case_name: String,
+    pub year: i32,
+    pub precedent_outcome: String,
+    /// Semantic similarity of the facts to each indexed section of the
+    /// precedent; sections the search didn't surface are absent
+    pub section_similarity: BTreeMap<String, f64>,
+    /// Terms the facts and issue share with the precedent's
+    pub matching_facts: Vec<String>,
+    pub distinguishing_factors: DistinguishingFactors,
+    pub explanation: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DistinguishingFactors {
+    pub only_in_request: Vec<String>,
+    pub only_in_precedent: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OutcomePrediction {
     pub outcome: String,
-    pub probabilities: HashMap<String, f64>,
+    pub probabilities: BTreeMap<String, f64>,
     pub confidence: f64,
     pub supporting_cases: Vec<String>,
     pub explanation: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PredictionResponse {
     pub status: String,
     pub predicted_outcome: String,
-    pub probabilities: HashMap<String, f64>,
+    /// By outcome; POST /api/predict?probabilities=list returns an array of
+    /// OutcomeProbability instead
+    pub probabilities: BTreeMap<String, f64>,
     pub confidence: f64,
     pub supporting_cases: Vec<SupportingCase>,
+    /// Prose summary; `rationale` is the same reasoning broken down
     pub explanation: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<Vec<RationaleFactor>>,
+    /// Caveats the client should surface, e.g. a low-confidence prediction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
+}
+
+/// One entry of `probabilities` under `?probabilities=list`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OutcomeProbability {
+    pub outcome: String,
+    pub probability: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// One weighted factor behind a prediction
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RationaleFactor {
+    pub factor: String,
+    /// Share of the decision attributed to this factor, 0..=1
+    pub weight: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cited_case: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SupportingCase {
     pub case_name: String,
     pub year: i32,
-    pub similarity_score: f64,
+    pub similarity_score: SimilarityScore,
     pub outcome: String,
+    /// Indexed document this case came from, when the prediction service reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_id: Option<String>,
+    /// Passages that supported the outcome, for highlighting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence_spans: Vec<EvidenceSpan>,
+}
+
+/// A passage of an indexed document, by character offsets into its text
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EvidenceSpan {
+    /// Filled from the supporting case when the service leaves it out
+    #[serde(default)]
+    pub document_id: Option<String>,
+    pub char_start: usize,
+    pub char_end: usize,
+    pub text: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OpinionRequest {
     pub case_context: CaseContext,
     #[serde(default = "default_opinion_type")]
     pub opinion_type: String,
     #[serde(default = "default_max_precedents")]
     pub max_precedents: i32,
+    /// Only callers with the `internal` scope may set this to false
+    #[serde(default = "default_include_disclaimer", skip_serializing)]
+    pub include_disclaimer: bool,
+}
+
+impl OpinionRequest {
+    /// A request with the same defaults a JSON body gets
+    pub fn new(case_context: CaseContext) -> Self {
+        Self {
+            case_context,
+            opinion_type: default_opinion_type(),
+            max_precedents: default_max_precedents(),
+            include_disclaimer: default_include_disclaimer(),
+        }
+    }
 }
 
 fn default_opinion_type() -> String { "per_curiam".to_string() }
 fn default_max_precedents() -> i32 { 5 }
+fn default_include_disclaimer() -> bool { true }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CaseContext {
     pub case_number: String,
     pub petitioner: String,
@@ -127,22 +548,55 @@ pub struct CaseContext {
     pub procedural_history: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GeneratedOpinion {
     pub full_text: String,
     pub sections: HashMap<String, String>,
     pub cited_precedents: Vec<String>,
-    pub generation_metadata: HashMap<String, serde_json::Value>,
-    pub disclaimer: String,
+    pub generation_metadata: GenerationMetadata,
+    /// Absent only when an `internal` caller asked for it to be omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disclaimer: Option<String>,
+}
+
+/// The known generation_metadata fields, typed; anything else the opinion
+/// service reports is kept in `extra` and serialized back alongside them
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GenerationMetadata {
+    #[serde(default, alias = "model", skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_used: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation_time_ms: Option<f64>,
+    /// The LLM was unavailable and canned text was used
+    #[serde(default)]
+    pub mock: bool,
+    /// Set by the gateway after clamping to MAX_PRECEDENTS_LIMIT
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_precedents: Option<i32>,
+    /// Set by the gateway: citations rewritten to OPINION_CITATION_STYLE
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normalized_citations: Option<usize>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OpinionResponse {
     pub status: String,
+    /// Fetch this opinion again at GET /api/opinion/:id
+    pub opinion_id: String,
+    pub generated_at: String,
     pub opinion: GeneratedOpinion,
+    /// The opinion service fell back to canned text instead of the LLM
+    #[serde(default)]
+    pub mock: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IngestionResult {
     pub document_id: String,
     pub case_name: String,
@@ -153,7 +607,40 @@ pub struct IngestionResult {
     pub vector_ids: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Outcome of POST /api/validate: what /api/ingest would reject, without ingesting
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub validation_errors: Vec<FieldViolation>,
+}
+
+/// How cases in the corpus were decided, for baseline context
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OutcomeDistribution {
+    pub total_cases: u64,
+    /// Keyed by final_judgment
+    pub outcomes: BTreeMap<String, OutcomeShare>,
+    pub filters: OutcomeFilters,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OutcomeShare {
+    pub count: u64,
+    /// count / total_cases; 0 when the filtered corpus is empty
+    pub proportion: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct OutcomeFilters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub court: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year_from: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year_to: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub service: String,
@@ -161,7 +648,7 @@ pub struct HealthResponse {
     pub components: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StatsResponse {
     pub total_cases_indexed: i64,
     pub vector_index_size_mb: i64,
@@ -169,11 +656,130 @@ pub struct StatsResponse {
     pub total_opinions_generated: i64,
     pub average_search_time_ms: f64,
     pub average_opinion_generation_time_ms: f64,
+    /// When the gateway last refreshed this snapshot (RFC 3339, UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ErrorResponse {
     pub status: String,
     pub error: String,
     pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<FieldViolation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FieldViolation {
+    pub field: String,
+    pub message: String,
+}
+
+/// Body of POST /api/analyze-url; the optional fields mirror the
+/// analyze-brief form fields
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnalyzeUrlRequest {
+    pub url: String,
+    #[serde(default)]
+    pub lang: Option<String>,
+    #[serde(default)]
+    pub callback_url: Option<String>,
+    /// Leave out of top_cases any case the document itself cites
+    #[serde(default)]
+    pub exclude_cited: bool,
+    /// Rank only these documents for top_cases instead of the whole corpus
+    #[serde(default)]
+    pub candidate_document_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnalyzeResponse {
+    /// The first 500 characters; the full text is at GET /api/ocr-text/:ocr_text_id
+    pub ocr_text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr_text_id: Option<String>,
+    /// The full text page by page, when the OCR service paginates it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<PageText>,
+    /// Pages OCR couldn't read; the analysis is based on the others
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_pages: Vec<u32>,
+    pub predicted_outcome: AnalyzeOutcome,
+    pub top_cases: Vec<CaseResult>,
+    /// Empty when opinion generation failed and ANALYZE_OPINION_FAIL_OPEN let
+    /// the analysis through; `warnings` then says why
+    pub judge_opinion: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, serde_json::Value>,
+    /// Some or all of this response is placeholder data, not real analysis
+    #[serde(default)]
+    pub mock: bool,
+}
+
+impl AnalyzeResponse {
+    /// Top-level fields a `fields` query parameter may select
+    pub const FIELDS: [&'static str; 10] = [
+        "ocr_text",
+        "ocr_text_id",
+        "pages",
+        "failed_pages",
+        "predicted_outcome",
+        "top_cases",
+        "judge_opinion",
+        "warnings",
+        "metadata",
+        "mock",
+    ];
+}
+
+/// One page of OCR text, numbered from 1 within the whole document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PageText {
+    pub page_number: u32,
+    pub text: String,
+}
+
+/// Extraction quality reported under `metadata.ocr`; fields the OCR service
+/// doesn't send default to unknown (None) or false
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct OcrMetadata {
+    pub page_count: Option<i32>,
+    /// Mean recognition confidence in [0, 1]
+    pub ocr_confidence: Option<f64>,
+    pub has_images: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnalyzeOutcome {
+    pub label: String,
+    pub probabilities: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CaseResult {
+    pub case_name: String,
+    pub citation: String,
+    pub relevance_score: SimilarityScore,
+    pub snippet: String,
+}
+
+/// Common wrapper for successful /api responses
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApiEnvelope<T> {
+    pub status: String,
+    pub data: T,
+    pub request_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VersionResponse {
+    pub version: String,
+    pub git_commit: String,
+    pub build_timestamp: String,
+    pub rustc_version: String,
+    /// Configured service URLs with any credentials removed
+    pub downstream_services: BTreeMap<String, String>,
 }
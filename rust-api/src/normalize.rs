@@ -0,0 +1,120 @@
+//! Cleanup of raw OCR output before it is used for search and prediction
+
+/// Collapse runs of whitespace, rejoin words hyphenated across a line
+/// break and drop control characters. Line breaks survive, and form feeds
+/// become paragraph breaks; runs of blank lines collapse to one.
+pub fn normalize_ocr_text(raw: &str) -> String {
+    let mut cleaned = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                cleaned.push('\n');
+            }
+            '\n' => cleaned.push('\n'),
+            // Page break
+            '\u{000C}' => cleaned.push_str("\n\n"),
+            c if c.is_whitespace() => cleaned.push(' '),
+            c if c.is_control() => {}
+            c => cleaned.push(c),
+        }
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in cleaned.split('\n') {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if let Some(previous) = lines.last_mut() {
+            if is_hyphenated_break(previous, &line) {
+                previous.pop();
+                previous.push_str(&line);
+                continue;
+            }
+        }
+        lines.push(line);
+    }
+
+    let mut text = String::with_capacity(cleaned.len());
+    let mut blank_run = false;
+    for line in lines {
+        if line.is_empty() {
+            blank_run = !text.is_empty();
+            continue;
+        }
+        if !text.is_empty() {
+            text.push_str(if blank_run { "\n\n" } else { "\n" });
+        }
+        text.push_str(&line);
+        blank_run = false;
+    }
+    text
+}
+
+/// `previous` ends in `letter-` and `next` continues the word in lower
+/// case; "--" and lines starting with a capital are left alone
+fn is_hyphenated_break(previous: &str, next: &str) -> bool {
+    let Some(stem) = previous.strip_suffix('-') else {
+        return false;
+    };
+    stem.chars().next_back().is_some_and(char::is_alphabetic)
+        && next.chars().next().is_some_and(char::is_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapses_whitespace_within_lines() {
+        assert_eq!(
+            normalize_ocr_text("  The   court\tfinds \u{00A0} that  \n the  landlord  "),
+            "The court finds that\nthe landlord"
+        );
+    }
+
+    #[test]
+    fn joins_words_hyphenated_across_lines() {
+        let raw =
+            "the implied warranty of habit-\nability applies to every resi-  \r\n  dential lease";
+        assert_eq!(
+            normalize_ocr_text(raw),
+            "the implied warranty of habitability applies to every residential lease"
+        );
+    }
+
+    #[test]
+    fn keeps_hyphens_that_are_not_line_breaks() {
+        assert_eq!(
+            normalize_ocr_text("a self-executing clause\nsee 28 U.S.C. --\nand North-\nEast"),
+            "a self-executing clause\nsee 28 U.S.C. --\nand North-\nEast"
+        );
+    }
+
+    #[test]
+    fn turns_form_feeds_into_paragraph_breaks() {
+        assert_eq!(
+            normalize_ocr_text("end of page one\u{000C}Page 2\n\n\n\n\nmore text\n\n"),
+            "end of page one\n\nPage 2\n\nmore text"
+        );
+    }
+
+    #[test]
+    fn strips_control_characters() {
+        assert_eq!(
+            normalize_ocr_text("\u{0000}Plain\u{0007}tiff\u{001B} v. Defendant\u{007F}"),
+            "Plaintiff v. Defendant"
+        );
+    }
+
+    #[test]
+    fn representative_scanned_page() {
+        let raw = "\u{000C}  UNITED STATES COURT OF APPEALS  \r\n\r\n\r\n\
+                   The tenant with-\r\nheld rent after the land-\r\nlord failed\t\tto repair\u{0008}\r\n\
+                   \u{000C}\u{000C}  2  \r\n";
+        assert_eq!(
+            normalize_ocr_text(raw),
+            "UNITED STATES COURT OF APPEALS\n\n\
+             The tenant withheld rent after the landlord failed to repair\n\n2"
+        );
+    }
+}
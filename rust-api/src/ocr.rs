@@ -0,0 +1,433 @@
+//! Client for the OCR service's /ocr/pdf endpoint
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::downstream::{DownstreamError, Service};
+use crate::error::ApiError;
+use crate::language;
+use crate::models::{OcrMetadata, PageText};
+use crate::normalize::normalize_ocr_text;
+use crate::pdf;
+use crate::state::AppState;
+use crate::upload::Upload;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OcrResponse {
+    #[serde(default)]
+    pub full_text: Option<String>,
+    #[serde(default)]
+    pub page_count: Option<i32>,
+    #[serde(default)]
+    pub ocr_confidence: Option<f64>,
+    #[serde(default)]
+    pub has_images: Option<bool>,
+    /// Per-page text, from OCR services that paginate
+    #[serde(default)]
+    pub pages: Option<Vec<PageText>>,
+    /// U+FFFD characters substituted for invalid UTF-8 (OCR_LOSSY_UTF8)
+    #[serde(skip)]
+    pub replacement_chars: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OcrOptions {
+    pub lang: Option<String>,
+    pub first_page: Option<u32>,
+    pub last_page: Option<u32>,
+    /// Base URL of the OCR engine to call; defaults to OCR_SERVICE_URL
+    pub engine: Option<String>,
+}
+
+pub async fn run(
+    state: &AppState,
+    pdf: &Upload,
+    options: &OcrOptions,
+) -> Result<OcrResponse, ApiError> {
+    let url = match &options.engine {
+        Some(engine) => format!("{}/ocr/pdf", engine),
+        None => format!("{}/ocr/pdf", Service::Ocr.base_url(&state.config())),
+    };
+    let request = ocr_request(state, pdf, options, &url)
+        .await
+        .map_err(ApiError::TempFile)?;
+    // The multipart body is streamed, so each retry needs a fresh one
+    let rebuild = || async {
+        ocr_request(state, pdf, options, &url)
+            .await
+            .map_err(|e| log::warn!("Could not re-read upload to retry OCR: {}", e))
+            .ok()
+    };
+    let mut response = state
+        .downstream
+        .execute_retrying(Service::Ocr, request, rebuild)
+        .await?;
+    let replacement_chars = response.ensure_utf8(Service::Ocr, state.config().ocr_lossy_utf8)?;
+    if replacement_chars > 0 {
+        log::warn!(
+            "OCR response contained invalid UTF-8; replaced {} sequences",
+            replacement_chars
+        );
+    }
+    let mut ocr: OcrResponse = response.decode(Service::Ocr).map_err(document_rejection)?;
+    ocr.replacement_chars = replacement_chars;
+    Ok(ocr)
+}
+
+async fn ocr_request(
+    state: &AppState,
+    pdf: &Upload,
+    options: &OcrOptions,
+    url: &str,
+) -> std::io::Result<reqwest::RequestBuilder> {
+    let body = pdf.to_body().await?;
+    // Never the client's filename, which is only ever logged
+    let part = reqwest::multipart::Part::stream_with_length(body, pdf.len())
+        .file_name("brief.pdf")
+        .mime_str("application/pdf")
+        .expect("static MIME type is valid");
+
+    let mut form = reqwest::multipart::Form::new().part("file", part);
+    if let Some(lang) = &options.lang {
+        form = form.text("lang", lang.clone());
+    }
+    if let Some(first_page) = options.first_page {
+        form = form.text("first_page", first_page.to_string());
+    }
+    if let Some(last_page) = options.last_page {
+        form = form.text("last_page", last_page.to_string());
+    }
+    Ok(state.downstream.client().post(url).multipart(form))
+}
+
+/// The OCR service answers 400/415/422 for PDFs it cannot read (encrypted,
+/// corrupt, not a PDF) and 413 for ones over its size limit. Those are the
+/// client's to fix, so they are reported as such rather than as a 502.
+fn document_rejection(e: DownstreamError) -> ApiError {
+    match e {
+        DownstreamError::Status { status, body, .. }
+            if matches!(status.as_u16(), 400 | 415 | 422) =>
+        {
+            ApiError::UnprocessableDocument {
+                detail: downstream_detail(&body),
+            }
+        }
+        DownstreamError::Status { status, body, .. } if status.as_u16() == 413 => {
+            ApiError::PayloadTooLarge(format!(
+                "document is too large for the OCR service: {}",
+                downstream_detail(&body)
+            ))
+        }
+        e => e.into(),
+    }
+}
+
+/// FastAPI's `{"detail": "..."}` message, else the body as sent
+fn downstream_detail(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| value.get("detail")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageSource {
+    Client,
+    Detected,
+    Default,
+}
+
+#[derive(Debug, Clone)]
+pub struct LanguageChoice {
+    pub language: String,
+    pub source: LanguageSource,
+    pub confidence: Option<f64>,
+}
+
+/// Pick the OCR language: the client's choice, else (when OCR_AUTODETECT_LANG
+/// is on) a detection over a first-page OCR sample, else the configured default
+pub async fn choose_language(
+    state: &AppState,
+    pdf: &Upload,
+    requested: Option<String>,
+) -> LanguageChoice {
+    let config = state.config();
+    let default = |confidence| LanguageChoice {
+        language: config.ocr_default_lang.clone(),
+        source: LanguageSource::Default,
+        confidence,
+    };
+
+    if let Some(language) = requested {
+        return LanguageChoice {
+            language,
+            source: LanguageSource::Client,
+            confidence: None,
+        };
+    }
+    if !config.ocr_autodetect_lang {
+        return default(None);
+    }
+
+    let sample_options = OcrOptions {
+        lang: Some(config.ocr_default_lang.clone()),
+        first_page: Some(1),
+        last_page: Some(1),
+        engine: None,
+    };
+    let sample = match run(state, pdf, &sample_options).await {
+        Ok(response) => response.full_text.unwrap_or_default(),
+        Err(e) => {
+            log::warn!("Language detection sample failed, using default: {}", e);
+            return default(None);
+        }
+    };
+
+    match language::detect(&sample) {
+        Some(detection) if detection.confidence >= config.ocr_autodetect_min_confidence => {
+            LanguageChoice {
+                language: detection.language.to_string(),
+                source: LanguageSource::Detected,
+                confidence: Some(detection.confidence),
+            }
+        }
+        detection => default(detection.map(|d| d.confidence)),
+    }
+}
+
+/// Tesseract language specs look like `eng` or `eng+fra`
+pub fn is_valid_language(lang: &str) -> bool {
+    !lang.is_empty()
+        && lang.len() <= 32
+        && lang
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+')
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    Single,
+    PageRanges {
+        pages_per_chunk: u32,
+        chunks: usize,
+        concurrency: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct OcrExtraction {
+    pub full_text: Option<String>,
+    /// `full_text` by page; empty unless every OCR call returned pages
+    pub pages: Vec<PageText>,
+    /// The engine's text before OCR_NORMALIZE_TEXT cleanup; `None` when
+    /// normalization is off and `full_text` is already raw
+    pub raw_text: Option<String>,
+    pub page_count: Option<i32>,
+    /// Pages whose chunk failed; their text is missing from `full_text`
+    pub failed_pages: Vec<u32>,
+    pub replacement_chars: usize,
+    pub ocr_confidence: Option<f64>,
+    pub has_images: bool,
+    pub strategy: ChunkStrategy,
+    /// The OCR_SERVICE_URLS entry that produced this result
+    pub engine: String,
+}
+
+impl OcrExtraction {
+    pub fn quality(&self) -> OcrMetadata {
+        OcrMetadata {
+            page_count: self.page_count,
+            ocr_confidence: self.ocr_confidence,
+            has_images: self.has_images,
+        }
+    }
+}
+
+/// OCR the whole document with each OCR_SERVICE_URLS engine in turn,
+/// stopping at the first that returns non-empty text. If none does, the
+/// last engine's empty result or error is returned.
+pub async fn extract(
+    state: &AppState,
+    pdf: &Upload,
+    lang: Option<String>,
+) -> Result<OcrExtraction, ApiError> {
+    let config = state.config();
+    let pages_per_chunk = config.ocr_chunk_pages;
+    let total_pages = match pages_per_chunk {
+        0 => 0,
+        _ => pdf::count_pages(pdf).await.unwrap_or_else(|e| {
+            log::warn!("Could not count PDF pages, skipping chunking: {}", e);
+            0
+        }) as u32,
+    };
+
+    let mut outcome = None;
+    for (attempt, engine) in config.ocr_service_urls.iter().enumerate() {
+        if attempt > 0 {
+            state.metrics.incr("ocr_engine_fallbacks_total");
+        }
+        let result = extract_with(state, pdf, lang.clone(), engine, total_pages).await;
+        match &result {
+            Ok(extraction) if has_text(extraction) => {
+                return result.map(|extraction| apply_normalization(&config, extraction))
+            }
+            Ok(_) => log::warn!("OCR engine {} returned no text", engine),
+            // Neither a local spooling failure nor a PDF the engine
+            // rejected as unreadable will go better on another engine
+            Err(ApiError::TempFile(_))
+            | Err(ApiError::UnprocessableDocument { .. })
+            | Err(ApiError::PayloadTooLarge(_)) => return result,
+            Err(e) => log::warn!("OCR engine {} failed: {}", engine, e),
+        }
+        outcome = Some(result);
+    }
+    outcome.expect("OCR_SERVICE_URLS is never empty")
+}
+
+fn apply_normalization(config: &Config, mut extraction: OcrExtraction) -> OcrExtraction {
+    if config.ocr_normalize_text {
+        let normalized = extraction.full_text.as_deref().map(normalize_ocr_text);
+        extraction.raw_text = std::mem::replace(&mut extraction.full_text, normalized);
+        for page in &mut extraction.pages {
+            page.text = normalize_ocr_text(&page.text);
+        }
+    }
+    extraction
+}
+
+fn sorted_pages(mut pages: Vec<PageText>) -> Vec<PageText> {
+    pages.sort_by_key(|page| page.page_number);
+    pages
+}
+
+fn has_text(extraction: &OcrExtraction) -> bool {
+    extraction
+        .full_text
+        .as_deref()
+        .is_some_and(|text| !text.trim().is_empty())
+}
+
+/// OCR with one engine, splitting the document into page ranges OCR'd
+/// concurrently when it is longer than OCR_CHUNK_PAGES. A failed chunk only
+/// loses its own pages; the call fails only if every chunk does.
+async fn extract_with(
+    state: &AppState,
+    pdf: &Upload,
+    lang: Option<String>,
+    engine: &str,
+    total_pages: u32,
+) -> Result<OcrExtraction, ApiError> {
+    let pages_per_chunk = state.config().ocr_chunk_pages;
+
+    if pages_per_chunk == 0 || total_pages <= pages_per_chunk {
+        let options = OcrOptions {
+            lang,
+            engine: Some(engine.to_string()),
+            ..Default::default()
+        };
+        let response = run(state, pdf, &options).await?;
+        return Ok(OcrExtraction {
+            full_text: response.full_text,
+            pages: response.pages.map(sorted_pages).unwrap_or_default(),
+            raw_text: None,
+            page_count: response.page_count,
+            failed_pages: Vec::new(),
+            replacement_chars: response.replacement_chars,
+            ocr_confidence: response.ocr_confidence,
+            has_images: response.has_images.unwrap_or(false),
+            strategy: ChunkStrategy::Single,
+            engine: engine.to_string(),
+        });
+    }
+
+    let ranges: Vec<(u32, u32)> = (1..=total_pages)
+        .step_by(pages_per_chunk as usize)
+        .map(|first| (first, (first + pages_per_chunk - 1).min(total_pages)))
+        .collect();
+    let concurrency = state.config().ocr_chunk_concurrency.max(1);
+    log::info!(
+        "OCR'ing {} pages in {} chunks of {}",
+        total_pages,
+        ranges.len(),
+        pages_per_chunk
+    );
+
+    // Collect the futures first: a lazy `map` inside the stream trips the
+    // compiler's Send inference for the handler future
+    let chunks: Vec<_> = ranges
+        .iter()
+        .map(|&(first, last)| {
+            let options = OcrOptions {
+                lang: lang.clone(),
+                first_page: Some(first),
+                last_page: Some(last),
+                engine: Some(engine.to_string()),
+            };
+            async move { (first, last, run(state, pdf, &options).await) }
+        })
+        .collect();
+    let results: Vec<_> = stream::iter(chunks).buffered(concurrency).collect().await;
+
+    let mut texts = Vec::new();
+    // None once a chunk comes back unpaginated
+    let mut paginated = Some(Vec::new());
+    let mut failed_pages = Vec::new();
+    let mut page_count = 0;
+    let mut replacement_chars = 0;
+    let mut has_images = false;
+    // Page-weighted sum of the confidences chunks reported, and their pages
+    let mut confidence_sum = 0.0;
+    let mut confidence_pages = 0;
+    let mut last_error = None;
+    for (first, last, result) in results {
+        match result {
+            Ok(response) => {
+                let pages = response.page_count.unwrap_or((last - first + 1) as i32);
+                replacement_chars += response.replacement_chars;
+                has_images |= response.has_images.unwrap_or(false);
+                if let Some(confidence) = response.ocr_confidence {
+                    confidence_sum += confidence * f64::from(pages);
+                    confidence_pages += pages;
+                }
+                texts.extend(response.full_text);
+                paginated = paginated.zip(response.pages).map(|(mut all, chunk)| {
+                    all.extend(chunk);
+                    all
+                });
+                page_count += pages;
+            }
+            Err(e) => {
+                log::warn!("OCR failed for pages {}-{}: {}", first, last, e);
+                failed_pages.extend(first..=last);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    if !failed_pages.is_empty() && state.config().ocr_fail_on_partial {
+        return Err(ApiError::IncompleteOcr { failed_pages });
+    }
+    if failed_pages.len() == total_pages as usize {
+        return Err(last_error.expect("at least one chunk failed"));
+    }
+    Ok(OcrExtraction {
+        full_text: Some(texts.join("\n\n")),
+        pages: paginated.map(sorted_pages).unwrap_or_default(),
+        raw_text: None,
+        page_count: Some(page_count),
+        failed_pages,
+        replacement_chars,
+        ocr_confidence: (confidence_pages > 0)
+            .then(|| confidence_sum / f64::from(confidence_pages)),
+        has_images,
+        strategy: ChunkStrategy::PageRanges {
+            pages_per_chunk,
+            chunks: ranges.len(),
+            concurrency,
+        },
+        engine: engine.to_string(),
+    })
+}
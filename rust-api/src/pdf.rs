@@ -0,0 +1,77 @@
+//! Lightweight PDF inspection that scans raw bytes instead of parsing
+
+use regex::bytes::Regex;
+use std::sync::OnceLock;
+
+use crate::upload::Upload;
+
+/// Bytes carried between chunks so markers split across a boundary still match
+const CARRY: usize = 64;
+
+fn page_object() -> &'static Regex {
+    static PAGE_OBJECT: OnceLock<Regex> = OnceLock::new();
+    // `/Type /Page` but not `/Type /Pages`
+    PAGE_OBJECT.get_or_init(|| Regex::new(r"/Type\s*/Page[^s]").unwrap())
+}
+
+fn encrypt_entry() -> &'static Regex {
+    static ENCRYPT_ENTRY: OnceLock<Regex> = OnceLock::new();
+    // The trailer's `/Encrypt` key, not a longer name that starts with it
+    ENCRYPT_ENTRY.get_or_init(|| Regex::new(r"/Encrypt[^A-Za-z0-9]").unwrap())
+}
+
+/// Count page objects. Returns 0 when pages live in compressed object
+/// streams, in which case callers should not rely on the count.
+pub async fn count_pages(upload: &Upload) -> std::io::Result<usize> {
+    count_matches(upload, page_object()).await
+}
+
+/// Whether the document has an encryption dictionary, meaning OCR would
+/// need a password to read it
+pub async fn is_encrypted(upload: &Upload) -> std::io::Result<bool> {
+    Ok(count_matches(upload, encrypt_entry()).await? > 0)
+}
+
+async fn count_matches(upload: &Upload, pattern: &Regex) -> std::io::Result<usize> {
+    let mut matches = 0;
+    let mut carry: Vec<u8> = Vec::new();
+    upload
+        .for_each_chunk(|chunk| {
+            let carried = carry.len();
+            carry.extend_from_slice(chunk);
+            matches += pattern
+                .find_iter(&carry)
+                .filter(|m| m.end() > carried)
+                .count();
+            let keep = carry.len().min(CARRY);
+            carry.drain(..carry.len() - keep);
+        })
+        .await?;
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+
+    fn upload(contents: &'static [u8]) -> Upload {
+        Upload::Memory(Bytes::from_static(contents))
+    }
+
+    #[tokio::test]
+    async fn detects_encryption_dictionary_in_trailer() {
+        let encrypted = upload(
+            b"%PDF-1.6\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+              5 0 obj\n<< /Filter /Standard /V 4 /R 4 /Length 128 /P -1028 >>\nendobj\n\
+              trailer\n<< /Size 6 /Root 1 0 R /Encrypt 5 0 R >>\n%%EOF\n",
+        );
+        assert!(is_encrypted(&encrypted).await.unwrap());
+
+        let plain = upload(
+            b"%PDF-1.6\n1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n\
+              trailer\n<< /Size 2 /Root 1 0 R >>\n%%EOF\n",
+        );
+        assert!(!is_encrypted(&plain).await.unwrap());
+    }
+}
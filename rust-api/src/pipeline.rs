@@ -0,0 +1,39 @@
+//! Stage bookkeeping for the multi-step analyze pipeline
+
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Stage {
+    #[default]
+    Upload,
+    LanguageDetection,
+    Ocr,
+    Opinion,
+}
+
+impl Stage {
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Upload => "upload",
+            Stage::LanguageDetection => "language_detection",
+            Stage::Ocr => "ocr",
+            Stage::Opinion => "opinion",
+        }
+    }
+}
+
+/// Shared record of the active stage, readable after the pipeline future
+/// has been dropped by a timeout
+#[derive(Debug, Clone, Default)]
+pub struct StageTracker(Arc<Mutex<Stage>>);
+
+impl StageTracker {
+    pub fn enter(&self, stage: Stage) {
+        log::debug!("Analyze stage: {}", stage.name());
+        *self.0.lock().unwrap() = stage;
+    }
+
+    pub fn current(&self) -> Stage {
+        *self.0.lock().unwrap()
+    }
+}
@@ -0,0 +1,95 @@
+//! Single byte-range `Range` headers (RFC 9110 §14), for paging through
+//! large stored text
+
+use std::ops::Range;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable Range header; serve the whole body with 200
+    Full,
+    /// Serve these bytes with 206
+    Partial(Range<usize>),
+    /// Syntactically valid but outside the body; 416
+    Unsatisfiable,
+}
+
+/// Interpret `header` against a body of `len` bytes. Multiple ranges and
+/// malformed headers are ignored, which the RFC permits, so such clients
+/// get the full body.
+pub fn parse(header: Option<&str>, len: usize) -> RangeRequest {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return RangeRequest::Full;
+    };
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return RangeRequest::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    let range = if start.is_empty() {
+        // bytes=-N: the last N bytes
+        match end.parse::<usize>() {
+            Ok(0) => return RangeRequest::Unsatisfiable,
+            Ok(suffix) => len.saturating_sub(suffix)..len,
+            Err(_) => return RangeRequest::Full,
+        }
+    } else {
+        let Ok(start) = start.parse::<usize>() else {
+            return RangeRequest::Full;
+        };
+        let end = match end {
+            "" => len,
+            end => match end.parse::<usize>() {
+                Ok(end) if end >= start => end.saturating_add(1).min(len),
+                _ => return RangeRequest::Full,
+            },
+        };
+        start..end
+    };
+    if range.start >= len {
+        return RangeRequest::Unsatisfiable;
+    }
+    RangeRequest::Partial(range)
+}
+
+/// `Content-Range` value for a served range
+pub fn content_range(range: &Range<usize>, len: usize) -> String {
+    format!("bytes {}-{}/{}", range.start, range.end - 1, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bounded_open_and_suffix_ranges() {
+        assert_eq!(parse(Some("bytes=0-9"), 100), RangeRequest::Partial(0..10));
+        assert_eq!(
+            parse(Some("bytes=90-"), 100),
+            RangeRequest::Partial(90..100)
+        );
+        assert_eq!(parse(Some("bytes=-5"), 100), RangeRequest::Partial(95..100));
+        // An end past the body is clamped
+        assert_eq!(
+            parse(Some("bytes=50-500"), 100),
+            RangeRequest::Partial(50..100)
+        );
+    }
+
+    #[test]
+    fn out_of_bounds_is_unsatisfiable_and_junk_is_ignored() {
+        assert_eq!(parse(Some("bytes=100-"), 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse(Some("bytes=-0"), 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse(None, 100), RangeRequest::Full);
+        assert_eq!(parse(Some("bytes=0-1,5-6"), 100), RangeRequest::Full);
+        assert_eq!(parse(Some("items=0-1"), 100), RangeRequest::Full);
+        assert_eq!(parse(Some("bytes=9-3"), 100), RangeRequest::Full);
+    }
+
+    #[test]
+    fn formats_inclusive_content_range() {
+        assert_eq!(content_range(&(0..10), 100), "bytes 0-9/100");
+    }
+}
@@ -0,0 +1,119 @@
+//! Fetching documents clients host elsewhere, for /api/analyze-url
+//!
+//! The URL is client-supplied, so it is held to the same allowlist as every
+//! other outbound connection, redirects are not followed, and the body is
+//! subject to the upload size and content-type checks.
+
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, USER_AGENT};
+use reqwest::Url;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::downstream;
+use crate::error::ApiError;
+use crate::mime;
+use crate::upload::{Upload, UploadWriter};
+
+/// Separate from the downstream client so redirects can't steer a fetch
+/// past the allowlist
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("remote document HTTP client builds")
+    })
+}
+
+/// Check a client-supplied document URL before connecting to it
+pub fn validate_url(config: &Config, raw: &str) -> Result<Url, ApiError> {
+    let url = Url::parse(raw).map_err(|e| ApiError::BadRequest(format!("Invalid url: {}", e)))?;
+    if !config.downstream_allowlist.permits(&url) {
+        return Err(ApiError::BadRequest(format!(
+            "url host {:?} is not in the allowlist",
+            url.host_str().unwrap_or_default()
+        )));
+    }
+    Ok(url)
+}
+
+/// Download the document at `url`, enforcing MAX_UPLOAD_BYTES and
+/// UPLOAD_ALLOWED_MIME_TYPES as for a multipart upload
+pub async fn fetch(config: &Config, url: Url) -> Result<Upload, ApiError> {
+    let timeout = config.remote_document_timeout;
+    tokio::time::timeout(timeout, download(config, url))
+        .await
+        .map_err(|_| ApiError::Timeout {
+            operation: "document fetch",
+            seconds: timeout.as_secs(),
+            stage: None,
+        })?
+}
+
+async fn download(config: &Config, url: Url) -> Result<Upload, ApiError> {
+    let unreachable = |e: reqwest::Error| {
+        ApiError::BadRequest(format!("Could not fetch document from url: {}", e))
+    };
+    let mut response = client()
+        .get(url.clone())
+        .header(USER_AGENT, &config.downstream_user_agent)
+        .send()
+        .await
+        .map_err(unreachable)?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ApiError::BadRequest(format!(
+            "Could not fetch document from url: remote returned {}",
+            status
+        )));
+    }
+
+    let limit = config.max_upload_bytes as u64;
+    let too_large = || {
+        ApiError::PayloadTooLarge(format!(
+            "Remote document exceeds the {} byte upload limit",
+            limit
+        ))
+    };
+    let declared_len = response
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared_len.is_some_and(|len| len > limit) {
+        return Err(too_large());
+    }
+
+    let allowed = &config.upload_allowed_mime_types;
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    let declared = mime::declared_type(content_type);
+    mime::check_declared(declared.as_deref(), allowed)?;
+
+    // Content-Length can be absent or wrong, so count what actually arrives
+    let mut writer = UploadWriter::new(config.upload_spool_threshold_bytes);
+    while let Some(chunk) = response.chunk().await.map_err(unreachable)? {
+        if writer.len() + chunk.len() as u64 > limit {
+            return Err(too_large());
+        }
+        writer.write(&chunk).await?;
+    }
+    let upload = writer.finish().await?;
+    if upload.is_empty() {
+        return Err(ApiError::BadRequest("Remote document is empty".to_string()));
+    }
+    let prefix = upload
+        .prefix(mime::SNIFF_LEN)
+        .await
+        .map_err(ApiError::TempFile)?;
+    mime::check_content(&prefix, declared.as_deref(), allowed)?;
+    log::info!(
+        "Fetched {} byte document from {}",
+        upload.len(),
+        downstream::redact_credentials(url.as_str())
+    );
+    Ok(upload)
+}
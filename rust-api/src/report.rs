@@ -0,0 +1,464 @@
+//! Renders a finished analysis as a printable PDF report
+//!
+//! Written directly against the PDF format rather than through a layout
+//! crate: the report is plain wrapped text and rules in the standard
+//! Helvetica fonts, which every reader has, so nothing is embedded.
+
+use std::fmt::Write as _;
+
+use crate::models::AnalyzeResponse;
+
+/// Printed at the top of the report
+pub const DISCLAIMER: &str =
+    "AI-generated research output, not legal advice. The predicted outcome, \
+     precedents and draft opinion were produced automatically from the uploaded brief and must be \
+     reviewed by a qualified attorney before they are relied on.";
+
+/// Short form of [`DISCLAIMER`] for every page footer
+const FOOTER: &str = "AI-generated research output - not legal advice";
+
+// US Letter, in points
+const PAGE_WIDTH: f32 = 612.0;
+const PAGE_HEIGHT: f32 = 792.0;
+const MARGIN: f32 = 54.0;
+const BODY_TOP: f32 = PAGE_HEIGHT - MARGIN;
+/// Leaves room below the body for the footer
+const BODY_BOTTOM: f32 = 72.0;
+const FOOTER_Y: f32 = 40.0;
+const LINE_SPACING: f32 = 1.3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    Text {
+        font: Font,
+        size: f32,
+        indent: f32,
+        text: String,
+    },
+    Space(f32),
+    Rule,
+}
+
+#[derive(Default)]
+struct Layout {
+    items: Vec<Item>,
+}
+
+impl Layout {
+    fn heading(&mut self, text: &str) {
+        self.items.push(Item::Space(10.0));
+        self.paragraph(text, Font::Bold, 13.0, 0.0);
+        self.items.push(Item::Space(2.0));
+    }
+
+    /// Word-wrapped to the body width less `indent`
+    fn paragraph(&mut self, text: &str, font: Font, size: f32, indent: f32) {
+        for line in wrap(text, font, size, PAGE_WIDTH - 2.0 * MARGIN - indent) {
+            self.items.push(Item::Text {
+                font,
+                size,
+                indent,
+                text: line,
+            });
+        }
+    }
+
+    fn space(&mut self, points: f32) {
+        self.items.push(Item::Space(points));
+    }
+
+    fn rule(&mut self) {
+        self.items.push(Item::Rule);
+    }
+
+    /// Content streams, one per page
+    fn paginate(&self) -> Vec<Vec<u8>> {
+        let mut pages = vec![Vec::new()];
+        let mut y = BODY_TOP;
+        for item in &self.items {
+            let height = match item {
+                Item::Text { size, .. } => size * LINE_SPACING,
+                Item::Space(points) => *points,
+                Item::Rule => 8.0,
+            };
+            if y - height < BODY_BOTTOM {
+                pages.push(Vec::new());
+                y = BODY_TOP;
+                if !matches!(item, Item::Text { .. }) {
+                    continue;
+                }
+            }
+            y -= height;
+            let page = pages.last_mut().expect("at least one page");
+            match item {
+                Item::Text {
+                    font,
+                    size,
+                    indent,
+                    text,
+                } => text_op(page, *font, *size, MARGIN + indent, y, text),
+                Item::Space(_) => {}
+                Item::Rule => {
+                    let y = y + height / 2.0;
+                    let line = format!(
+                        "0.5 w {} {:.1} m {} {:.1} l S\n",
+                        MARGIN,
+                        y,
+                        PAGE_WIDTH - MARGIN,
+                        y
+                    );
+                    page.extend_from_slice(line.as_bytes());
+                }
+            }
+        }
+
+        let total = pages.len();
+        for (i, page) in pages.iter_mut().enumerate() {
+            text_op(page, Font::Regular, 8.0, MARGIN, FOOTER_Y, FOOTER);
+            let number = format!("Page {} of {}", i + 1, total);
+            let x = PAGE_WIDTH - MARGIN - text_width(&number, Font::Regular, 8.0);
+            text_op(page, Font::Regular, 8.0, x, FOOTER_Y, &number);
+        }
+        pages
+    }
+}
+
+/// The report for a completed analysis job
+pub fn render(job_id: &str, generated_at: &str, analysis: &AnalyzeResponse) -> Vec<u8> {
+    let mut layout = Layout::default();
+    layout.paragraph("Brief Analysis Report", Font::Bold, 18.0, 0.0);
+    layout.paragraph(
+        &format!("Analysis {} - generated {}", job_id, generated_at),
+        Font::Regular,
+        9.0,
+        0.0,
+    );
+    layout.space(6.0);
+    layout.rule();
+    layout.paragraph(
+        &format!("DISCLAIMER: {}", DISCLAIMER),
+        Font::Bold,
+        11.0,
+        0.0,
+    );
+    layout.rule();
+    if analysis.mock {
+        layout.paragraph(
+            "PLACEHOLDER DATA: some or all of this analysis is stand-in output from an \
+             unavailable service, not a real analysis of the brief.",
+            Font::Bold,
+            10.0,
+            0.0,
+        );
+    }
+
+    layout.heading("Predicted outcome");
+    layout.paragraph(&analysis.predicted_outcome.label, Font::Bold, 11.0, 0.0);
+    let mut probabilities: Vec<_> = analysis.predicted_outcome.probabilities.iter().collect();
+    probabilities.sort_by(|a, b| b.1.total_cmp(a.1));
+    for (label, probability) in probabilities {
+        layout.paragraph(
+            &format!("{}: {:.1}%", label, probability * 100.0),
+            Font::Regular,
+            10.0,
+            12.0,
+        );
+    }
+
+    if !analysis.warnings.is_empty() {
+        layout.heading("Warnings");
+        for warning in &analysis.warnings {
+            layout.paragraph(&format!("- {}", warning), Font::Regular, 10.0, 0.0);
+        }
+    }
+
+    layout.heading("Most relevant precedents");
+    if analysis.top_cases.is_empty() {
+        layout.paragraph("No similar cases were found.", Font::Regular, 10.0, 0.0);
+    }
+    for (i, case) in analysis.top_cases.iter().enumerate() {
+        layout.space(4.0);
+        layout.paragraph(
+            &format!("{}. {}", i + 1, case.case_name),
+            Font::Bold,
+            10.0,
+            0.0,
+        );
+        layout.paragraph(
+            &format!(
+                "{}; relevance {:.2}",
+                case.citation,
+                case.relevance_score.get()
+            ),
+            Font::Regular,
+            9.0,
+            12.0,
+        );
+        if !case.snippet.trim().is_empty() {
+            layout.paragraph(case.snippet.trim(), Font::Regular, 9.0, 12.0);
+        }
+    }
+
+    layout.heading("Draft opinion");
+    if analysis.judge_opinion.trim().is_empty() {
+        layout.paragraph(
+            "No opinion was generated for this analysis.",
+            Font::Regular,
+            10.0,
+            0.0,
+        );
+    }
+    for line in analysis.judge_opinion.trim().lines() {
+        if line.trim().is_empty() {
+            layout.space(6.0);
+        } else {
+            layout.paragraph(line.trim(), Font::Regular, 10.0, 0.0);
+        }
+    }
+
+    document(&layout.paginate())
+}
+
+/// Assemble the file: catalog, page tree, fonts and info, then a page and
+/// content stream per page, and the cross-reference table
+fn document(pages: &[Vec<u8>]) -> Vec<u8> {
+    const FIRST_PAGE: usize = 6;
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", FIRST_PAGE + 2 * i))
+        .collect();
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    objects.push(
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        )
+        .into_bytes(),
+    );
+    for base_font in ["Helvetica", "Helvetica-Bold"] {
+        objects.push(
+            format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                base_font
+            )
+            .into_bytes(),
+        );
+    }
+    objects.push(b"<< /Title (Brief analysis report) /Producer (legal-judge-api) >>".to_vec());
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                FIRST_PAGE + 2 * i + 1
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content);
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        out.extend_from_slice(object);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = out.len();
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(table, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        table,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    out.extend_from_slice(table.as_bytes());
+    out
+}
+
+fn text_op(page: &mut Vec<u8>, font: Font, size: f32, x: f32, y: f32, text: &str) {
+    page.extend_from_slice(
+        format!("BT /{} {} Tf {:.1} {:.1} Td (", font.resource(), size, x, y).as_bytes(),
+    );
+    page.extend_from_slice(&pdf_string(text));
+    page.extend_from_slice(b") Tj ET\n");
+}
+
+/// The body of a literal string in WinAnsiEncoding. Typographic
+/// punctuation is folded to ASCII; other characters outside Latin-1
+/// can't be shown in the standard fonts and become `?`.
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => out.extend_from_slice(&[b'\\', c as u8]),
+            '\u{2018}' | '\u{2019}' => out.push(b'\''),
+            '\u{201c}' | '\u{201d}' => out.push(b'"'),
+            '\u{2013}' | '\u{2014}' => out.push(b'-'),
+            '\u{2026}' => out.extend_from_slice(b"..."),
+            '\t' => out.push(b' '),
+            ' '..='~' | '\u{a0}'..='\u{ff}' => out.push(c as u32 as u8),
+            _ => out.push(b'?'),
+        }
+    }
+    out
+}
+
+/// Approximate advance width of `text` in points. Helvetica's metrics,
+/// coarsened to a few classes of character; erring wide keeps wrapped
+/// lines inside the margin.
+fn text_width(text: &str, font: Font, size: f32) -> f32 {
+    let em: f32 = text
+        .chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '!' | '|' => 0.28,
+            ' ' | 'f' | 't' | 'r' | 'I' | '(' | ')' | '-' | '/' => 0.34,
+            'm' | 'w' | 'M' | 'W' | '%' | '@' => 0.89,
+            'A'..='Z' => 0.72,
+            _ => 0.56,
+        })
+        .sum();
+    let weight = match font {
+        Font::Regular => 1.0,
+        Font::Bold => 1.06,
+    };
+    em * size * weight
+}
+
+/// Greedy word wrap; words wider than a whole line are split
+fn wrap(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if text_width(&candidate, font, size) <= width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            line.push(c);
+            if text_width(&line, font, size) > width {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, c.to_string()));
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AnalyzeOutcome, CaseResult, SimilarityScore};
+    use std::collections::{BTreeMap, HashMap};
+
+    fn analysis(opinion: &str) -> AnalyzeResponse {
+        AnalyzeResponse {
+            ocr_text: String::new(),
+            ocr_text_id: None,
+            pages: Vec::new(),
+            failed_pages: Vec::new(),
+            predicted_outcome: AnalyzeOutcome {
+                label: "Affirmed".to_string(),
+                probabilities: BTreeMap::from([("Affirmed".to_string(), 0.8)]),
+            },
+            top_cases: vec![CaseResult {
+                case_name: "Hilder v. St. Peter (quoted)".to_string(),
+                citation: "478 A.2d 202".to_string(),
+                relevance_score: SimilarityScore::new(0.91),
+                snippet: "Implied warranty of habitability".to_string(),
+            }],
+            judge_opinion: opinion.to_string(),
+            warnings: Vec::new(),
+            metadata: HashMap::new(),
+            mock: false,
+        }
+    }
+
+    #[test]
+    fn wraps_within_the_width_and_splits_overlong_words() {
+        let lines = wrap(&"habitability ".repeat(40), Font::Regular, 10.0, 200.0);
+        assert!(lines.len() > 1);
+        assert!(lines
+            .iter()
+            .all(|line| text_width(line, Font::Regular, 10.0) <= 200.0));
+
+        let lines = wrap(&"x".repeat(100), Font::Regular, 10.0, 50.0);
+        assert_eq!(lines.concat(), "x".repeat(100));
+    }
+
+    #[test]
+    fn escapes_delimiters_and_folds_unsupported_characters() {
+        assert_eq!(pdf_string("a (b) \\ c"), b"a \\(b\\) \\\\ c");
+        assert_eq!(
+            pdf_string("\u{201c}caf\u{e9}\u{201d} \u{2014} \u{4e2d}"),
+            b"\"caf\xe9\" - ?"
+        );
+    }
+
+    #[test]
+    fn renders_a_well_formed_multi_page_document() {
+        let pdf = render(
+            "job-1",
+            "2024-01-01T00:00:00Z",
+            &analysis(&"The judgment is affirmed.\n".repeat(200)),
+        );
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+
+        let text = String::from_utf8_lossy(&pdf);
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|offset| offset.parse().ok())
+            .expect("startxref offset");
+        assert!(pdf[startxref..].starts_with(b"xref"));
+        let pages: usize = text
+            .split("/Count ")
+            .nth(1)
+            .and_then(|tail| tail.split(' ').next())
+            .and_then(|count| count.parse().ok())
+            .expect("page count");
+        assert!(pages > 1, "200 opinion lines should span pages");
+        assert_eq!(text.matches("/Type /Page ").count(), pages);
+        assert!(text.contains("(DISCLAIMER: AI-generated research output"));
+        assert!(text.contains("(1. Hilder v. St. Peter \\(quoted\\))"));
+        assert!(text.contains(&format!("(Page {} of {})", pages, pages)));
+    }
+}
@@ -0,0 +1,70 @@
+//! Per-request correlation ID, taken from X-Request-Id or generated
+//!
+//! Besides the extension handlers extract, the middleware scopes the ID to
+//! the request's task so downstream call logs can be tagged with it.
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::convert::Infallible;
+use std::future::Future;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    fn generate() -> Self {
+        RequestId(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
+/// The ID of the request whose task is running, if any
+pub fn current() -> Option<RequestId> {
+    CURRENT.try_with(RequestId::clone).ok()
+}
+
+/// Run `future` with `request_id` as [`current`], for work spawned off a request
+pub async fn scope<F: Future>(request_id: RequestId, future: F) -> F::Output {
+    CURRENT.scope(request_id, future).await
+}
+
+/// Middleware that attaches a RequestId extension and echoes it back as a header
+pub async fn assign_request_id(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(|value| RequestId(value.to_string()))
+        .unwrap_or_else(RequestId::generate);
+
+    request.extensions_mut().insert(request_id.clone());
+    let mut response = CURRENT.scope(request_id.clone(), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id.0) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<RequestId>()
+            .cloned()
+            .unwrap_or_else(RequestId::generate))
+    }
+}
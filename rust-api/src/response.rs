@@ -0,0 +1,61 @@
+//! Success responses wrapped in the common ApiEnvelope
+
+use axum::{
+    http::HeaderValue,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::models::ApiEnvelope;
+use crate::request_id::RequestId;
+use crate::state::AppState;
+
+/// Set to `true` on responses carrying placeholder rather than real data
+pub const MOCK_HEADER: &str = "x-mock";
+
+/// JSON success body, enveloped unless the legacy unwrapped shape is configured
+pub struct ApiJson<T> {
+    data: T,
+    request_id: RequestId,
+    unwrapped: bool,
+    mock: bool,
+}
+
+impl<T> ApiJson<T> {
+    pub fn new(state: &AppState, request_id: RequestId, data: T) -> Self {
+        Self {
+            data,
+            request_id,
+            unwrapped: state.config().legacy_unwrapped_responses,
+            mock: false,
+        }
+    }
+
+    /// Flag the body as mock data with the X-Mock header
+    pub fn mock(mut self, mock: bool) -> Self {
+        self.mock = mock;
+        self
+    }
+}
+
+impl<T: Serialize> IntoResponse for ApiJson<T> {
+    fn into_response(self) -> Response {
+        let mut response = if self.unwrapped {
+            Json(self.data).into_response()
+        } else {
+            Json(ApiEnvelope {
+                status: "success".to_string(),
+                data: self.data,
+                request_id: self.request_id.0,
+            })
+            .into_response()
+        };
+        if self.mock {
+            response
+                .headers_mut()
+                .insert(MOCK_HEADER, HeaderValue::from_static("true"));
+        }
+        response
+    }
+}
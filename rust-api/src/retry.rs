@@ -0,0 +1,138 @@
+//! Retry budget shared by every downstream call in one analyze run
+//!
+//! Each call may retry up to MAX_RETRIES times on its own, which across OCR
+//! chunks, engines and later stages can add up to far more waiting than any
+//! one stage suggests. The pipeline scopes a [`RetryBudget`] to its task so
+//! [`crate::downstream::Downstream`] draws every retry from the same pool;
+//! once it is spent, failures surface on the first attempt.
+
+use serde::Serialize;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::Config;
+
+tokio::task_local! {
+    static BUDGET: Arc<RetryBudget>;
+}
+
+#[derive(Debug)]
+pub struct RetryBudget {
+    max_retries: u32,
+    max_time: Duration,
+    spent: Mutex<Spent>,
+}
+
+#[derive(Debug, Default)]
+struct Spent {
+    retries: u32,
+    time: Duration,
+    exhausted: bool,
+}
+
+/// Reported under `metadata.retry_budget`
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryUsage {
+    pub retries_used: u32,
+    pub retries_allowed: u32,
+    pub retry_time_ms: u64,
+    pub retry_time_allowed_ms: u64,
+    /// A retry was refused because the budget had run out
+    pub exhausted: bool,
+}
+
+impl RetryBudget {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            max_retries: config.pipeline_retry_budget,
+            max_time: config.pipeline_retry_budget_time,
+            spent: Mutex::default(),
+        }
+    }
+
+    /// Claim one retry waiting `backoff` first, refusing if that would
+    /// overrun the count or time allowance
+    fn try_acquire(&self, backoff: Duration) -> bool {
+        let mut spent = self.spent.lock().unwrap();
+        if spent.retries >= self.max_retries || spent.time + backoff >= self.max_time {
+            spent.exhausted = true;
+            return false;
+        }
+        spent.retries += 1;
+        true
+    }
+
+    /// Charge the backoff and attempt time of a claimed retry
+    fn record(&self, elapsed: Duration) {
+        self.spent.lock().unwrap().time += elapsed;
+    }
+
+    pub fn usage(&self) -> RetryUsage {
+        let spent = self.spent.lock().unwrap();
+        RetryUsage {
+            retries_used: spent.retries,
+            retries_allowed: self.max_retries,
+            retry_time_ms: spent.time.as_millis() as u64,
+            retry_time_allowed_ms: self.max_time.as_millis() as u64,
+            exhausted: spent.exhausted,
+        }
+    }
+}
+
+/// Run `future` with `budget` governing its downstream retries
+pub async fn scope<F: Future>(budget: Arc<RetryBudget>, future: F) -> F::Output {
+    BUDGET.scope(budget, future).await
+}
+
+/// Permission for one retry with `backoff` before it. Outside a pipeline
+/// only the per-call limit applies.
+pub fn acquire(backoff: Duration) -> bool {
+    BUDGET
+        .try_with(|budget| budget.try_acquire(backoff))
+        .unwrap_or(true)
+}
+
+/// Charge a granted retry's elapsed time to the current budget, if any
+pub fn record(elapsed: Duration) {
+    let _ = BUDGET.try_with(|budget| budget.record(elapsed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(max_retries: u32, max_time: Duration) -> RetryBudget {
+        RetryBudget {
+            max_retries,
+            max_time,
+            spent: Mutex::default(),
+        }
+    }
+
+    #[test]
+    fn refuses_once_count_or_time_is_spent() {
+        let by_count = budget(2, Duration::from_secs(60));
+        assert!(by_count.try_acquire(Duration::ZERO));
+        assert!(by_count.try_acquire(Duration::ZERO));
+        assert!(!by_count.try_acquire(Duration::ZERO));
+        let usage = by_count.usage();
+        assert_eq!(usage.retries_used, 2);
+        assert!(usage.exhausted);
+
+        let by_time = budget(10, Duration::from_secs(1));
+        assert!(!by_time.try_acquire(Duration::from_secs(2)));
+        assert!(by_time.try_acquire(Duration::ZERO));
+        by_time.record(Duration::from_secs(1));
+        assert!(!by_time.try_acquire(Duration::ZERO));
+        assert_eq!(by_time.usage().retry_time_ms, 1000);
+    }
+
+    #[tokio::test]
+    async fn acquire_is_unbounded_outside_a_scope() {
+        assert!(acquire(Duration::ZERO));
+        let scoped = Arc::new(budget(0, Duration::from_secs(60)));
+        assert!(!scope(scoped.clone(), async { acquire(Duration::ZERO) }).await);
+        assert!(scoped.usage().exhausted);
+    }
+}
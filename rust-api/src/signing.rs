@@ -0,0 +1,73 @@
+//! Optional tamper-evidence for JSON responses
+//!
+//! With SIGN_RESPONSES on, every `application/json` response carries
+//! `X-Signature: sha256=<hex>`, an HMAC-SHA256 keyed with
+//! RESPONSE_SIGNING_KEY over the body bytes exactly as sent: no
+//! re-serialization, key sorting or whitespace normalization, so clients
+//! must verify the raw body before parsing it. Streamed exports such as
+//! the search CSV are not signed.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header::CONTENT_TYPE, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ring::hmac;
+
+use crate::state::AppState;
+
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// `sha256=<hex>` HMAC-SHA256 of the concatenated `parts`
+pub fn hmac_sha256_hex(key: &str, parts: &[&[u8]]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes());
+    let mut context = hmac::Context::with_key(&key);
+    for part in parts {
+        context.update(part);
+    }
+    let hex: String = context
+        .sign()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+/// Middleware buffering JSON responses to attach X-Signature
+pub async fn sign_response(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    let config = state.config();
+    if !config.sign_responses || !is_json(&response) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("Failed to buffer response for signing: {}", e);
+            return axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let signature = hmac_sha256_hex(&config.response_signing_key, &[&bytes]);
+    parts.headers.insert(
+        SIGNATURE_HEADER,
+        HeaderValue::from_str(&signature).expect("hex is a valid header value"),
+    );
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
@@ -0,0 +1,54 @@
+use axum::body::Bytes;
+use std::sync::Arc;
+
+use crate::concurrency::{PipelineQueue, TenantLimiter};
+use crate::config::{Config, SharedConfig};
+use crate::downstream::Downstream;
+use crate::history::AnalysisHistory;
+use crate::jobs::JobStore;
+use crate::metrics::Metrics;
+use crate::stats::StatsCache;
+use crate::store::{StoredOpinion, TtlStore};
+
+/// Shared state handed to every axum handler
+#[derive(Clone)]
+pub struct AppState {
+    pub config: SharedConfig,
+    pub downstream: Arc<Downstream>,
+    pub metrics: Arc<Metrics>,
+    pub stats: Arc<StatsCache>,
+    pub jobs: Arc<JobStore>,
+    /// Recent analyses per token, for GET /api/analyze-brief/history
+    pub history: Arc<AnalysisHistory>,
+    pub tenants: Arc<TenantLimiter>,
+    pub pipeline_queue: Arc<PipelineQueue>,
+    /// Generated opinions by opinion_id, for GET /api/opinion/:id
+    pub opinions: Arc<TtlStore<StoredOpinion>>,
+    /// Full OCR text by ocr_text_id, for GET /api/ocr-text/:id
+    pub ocr_texts: Arc<TtlStore<Bytes>>,
+}
+
+impl AppState {
+    pub fn new(config: Config) -> reqwest::Result<Self> {
+        let metrics = Arc::new(Metrics::default());
+        let config = SharedConfig::new(config);
+        let downstream = Arc::new(Downstream::new(&config, metrics.clone())?);
+        Ok(Self {
+            config,
+            downstream,
+            metrics,
+            stats: Arc::default(),
+            jobs: Arc::default(),
+            history: Arc::default(),
+            tenants: Arc::default(),
+            pipeline_queue: Arc::default(),
+            opinions: Arc::default(),
+            ocr_texts: Arc::default(),
+        })
+    }
+
+    /// Snapshot of the current configuration; see [`SharedConfig`]
+    pub fn config(&self) -> Arc<Config> {
+        self.config.load()
+    }
+}
@@ -0,0 +1,109 @@
+//! Aggregated index statistics for /api/stats, refreshed in the background
+//! so the endpoint doesn't fan out to the Python services on every call
+
+use serde::Deserialize;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::downstream::{DownstreamError, Service};
+use crate::models::StatsResponse;
+use crate::state::AppState;
+use crate::timestamp;
+
+/// Shape returned by the search service's /stats
+#[derive(Deserialize)]
+struct SearchStats {
+    total_searches: i64,
+    total_documents_indexed: i64,
+    average_search_time_ms: f64,
+}
+
+/// Shape returned by the opinion service's /stats
+#[derive(Deserialize)]
+struct OpinionStats {
+    total_opinions_generated: i64,
+    average_generation_time_ms: f64,
+}
+
+/// The most recent successful snapshot, if any
+#[derive(Debug, Default)]
+pub struct StatsCache(RwLock<Option<StatsResponse>>);
+
+impl StatsCache {
+    pub fn get(&self) -> Option<StatsResponse> {
+        self.0.read().unwrap().clone()
+    }
+
+    fn set(&self, stats: StatsResponse) {
+        *self.0.write().unwrap() = Some(stats);
+    }
+}
+
+/// Query the search and opinion services and store the combined snapshot
+pub async fn refresh(state: &AppState) -> Result<StatsResponse, DownstreamError> {
+    let config = state.config();
+    let (search, opinion) = tokio::join!(
+        state
+            .downstream
+            .get_json::<SearchStats>(&config, Service::Search, "/stats"),
+        state
+            .downstream
+            .get_json::<OpinionStats>(&config, Service::Opinion, "/stats"),
+    );
+    let (search, opinion) = (search?, opinion?);
+
+    let stats = StatsResponse {
+        total_cases_indexed: search.total_documents_indexed,
+        // Not reported by any service yet
+        vector_index_size_mb: 0,
+        total_searches_performed: search.total_searches,
+        total_opinions_generated: opinion.total_opinions_generated,
+        average_search_time_ms: search.average_search_time_ms,
+        average_opinion_generation_time_ms: opinion.average_generation_time_ms,
+        last_updated: Some(timestamp::now_rfc3339()),
+    };
+    state.stats.set(stats.clone());
+    Ok(stats)
+}
+
+/// Whether the search index holds no documents. A cached snapshot showing
+/// documents is trusted; otherwise the search service is asked, since the
+/// corpus may have been ingested since. Unknown counts as not empty.
+pub async fn corpus_is_empty(state: &AppState) -> bool {
+    if state
+        .stats
+        .get()
+        .is_some_and(|stats| stats.total_cases_indexed > 0)
+    {
+        return false;
+    }
+    match state
+        .downstream
+        .get_json::<SearchStats>(&state.config(), Service::Search, "/stats")
+        .await
+    {
+        Ok(search) => search.total_documents_indexed == 0,
+        Err(e) => {
+            log::debug!("Could not check whether the corpus is empty: {}", e);
+            false
+        }
+    }
+}
+
+/// Refresh every STATS_REFRESH_INTERVAL_SECONDS (re-read each round so a
+/// config reload applies); a failed round keeps the previous snapshot.
+/// While the interval is 0 rounds are skipped rather than the loop never
+/// starting, so a reload that enables caching doesn't serve a stale snapshot.
+pub fn spawn_refresher(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            let interval = state.config().stats_refresh_interval;
+            if !interval.is_zero() {
+                if let Err(e) = refresh(&state).await {
+                    log::warn!("Background stats refresh failed: {}", e);
+                }
+            }
+            tokio::time::sleep(interval.max(Duration::from_secs(1))).await;
+        }
+    });
+}
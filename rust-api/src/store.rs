@@ -0,0 +1,71 @@
+//! In-memory stores of results clients fetch again later by ID, such as
+//! generated opinions and full OCR text, each expiring after a retention
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::OpinionResponse;
+
+/// A generated opinion as kept for GET /api/opinion/:id
+#[derive(Debug, Clone)]
+pub struct StoredOpinion {
+    pub response: OpinionResponse,
+    /// The principal that generated it, if it was authenticated
+    pub owner: Option<String>,
+}
+
+impl StoredOpinion {
+    /// Owned opinions are only visible to their owner, as with analysis
+    /// jobs, since an internal caller's may have had its disclaimer dropped
+    pub fn visible_to(&self, caller: Option<&str>) -> bool {
+        match &self.owner {
+            Some(owner) => caller == Some(owner.as_str()),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Stored<V> {
+    value: V,
+    stored_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct TtlStore<V> {
+    entries: Mutex<HashMap<String, Stored<V>>>,
+}
+
+impl<V> Default for TtlStore<V> {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::default(),
+        }
+    }
+}
+
+impl<V: Clone> TtlStore<V> {
+    /// Keep `value` under `id`, first dropping entries older than `retention`
+    pub fn insert(&self, id: String, value: V, retention: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, stored| stored.stored_at.elapsed() < retention);
+        entries.insert(
+            id,
+            Stored {
+                value,
+                stored_at: Instant::now(),
+            },
+        );
+    }
+
+    /// The stored value, unless it is unknown or older than `retention`
+    pub fn get(&self, id: &str, retention: Duration) -> Option<V> {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(id)
+            .filter(|stored| stored.stored_at.elapsed() < retention)
+            .map(|stored| stored.value.clone())
+    }
+}
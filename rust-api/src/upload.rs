@@ -0,0 +1,214 @@
+//! Uploaded documents, kept in memory when small and spooled to a temp file
+//! when large so concurrent big filings don't multiply peak memory
+
+use axum::body::Bytes;
+use axum::extract::multipart::{Field, MultipartError};
+use axum::http::StatusCode;
+use std::sync::Arc;
+use tempfile::TempPath;
+use tokio::io::AsyncWriteExt;
+
+use crate::body_timeout;
+use crate::error::ApiError;
+
+#[derive(Debug, Clone)]
+pub enum Upload {
+    Memory(Bytes),
+    /// The file is deleted when the last clone is dropped, so it is cleaned
+    /// up on success and on every error path alike
+    Spooled {
+        path: Arc<TempPath>,
+        len: u64,
+    },
+}
+
+impl Upload {
+    pub fn len(&self) -> u64 {
+        match self {
+            Upload::Memory(bytes) => bytes.len() as u64,
+            Upload::Spooled { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Build a fresh request body each call so the upload can be resent
+    pub async fn to_body(&self) -> std::io::Result<reqwest::Body> {
+        match self {
+            Upload::Memory(bytes) => Ok(reqwest::Body::from(bytes.clone())),
+            Upload::Spooled { path, .. } => {
+                let file = tokio::fs::File::open(path.as_ref()).await?;
+                Ok(reqwest::Body::wrap_stream(
+                    tokio_util::io::ReaderStream::new(file),
+                ))
+            }
+        }
+    }
+
+    /// Up to the first `len` bytes
+    pub async fn prefix(&self, len: usize) -> std::io::Result<Vec<u8>> {
+        match self {
+            Upload::Memory(bytes) => Ok(bytes[..bytes.len().min(len)].to_vec()),
+            Upload::Spooled { path, .. } => {
+                use tokio::io::AsyncReadExt;
+                let file = tokio::fs::File::open(path.as_ref()).await?;
+                let mut prefix = Vec::with_capacity(len);
+                file.take(len as u64).read_to_end(&mut prefix).await?;
+                Ok(prefix)
+            }
+        }
+    }
+
+    /// Feed the upload's contents to `f` in order, a chunk at a time
+    pub async fn for_each_chunk<F: FnMut(&[u8])>(&self, mut f: F) -> std::io::Result<()> {
+        match self {
+            Upload::Memory(bytes) => f(bytes),
+            Upload::Spooled { path, .. } => {
+                use tokio::io::AsyncReadExt;
+                let mut file = tokio::fs::File::open(path.as_ref()).await?;
+                let mut buffer = vec![0u8; 64 * 1024];
+                loop {
+                    let read = file.read(&mut buffer).await?;
+                    if read == 0 {
+                        break;
+                    }
+                    f(&buffer[..read]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Longest client filename kept for logging
+const MAX_FILE_NAME_CHARS: usize = 100;
+
+/// A client-supplied filename made safe to log: directories are dropped and
+/// anything but letters, digits, spaces and `._-` becomes `_`, so escape
+/// sequences and bidi overrides can't reach the logs. None if nothing is
+/// left. Names that aren't UTF-8 never get here; the multipart parser
+/// reports them as absent.
+pub fn safe_file_name(raw: &str) -> Option<String> {
+    let base = raw.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = base
+        .chars()
+        .take(MAX_FILE_NAME_CHARS)
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '.' | '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim();
+    (!name.is_empty() && !name.chars().all(|c| c == '.')).then(|| name.to_string())
+}
+
+/// Over-limit bodies surface as 413 and timed-out ones as 408; anything
+/// else is a malformed request
+pub fn multipart_error(e: MultipartError) -> ApiError {
+    if let Some(timeout) = body_timeout::cause(&e) {
+        ApiError::RequestTimeout(timeout.to_string())
+    } else if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        ApiError::PayloadTooLarge(format!("Upload too large: {}", e.body_text()))
+    } else {
+        ApiError::BadRequest(format!("Failed to read upload: {}", e.body_text()))
+    }
+}
+
+/// Read a multipart field, switching from memory to a temp file once it grows
+/// past `spool_threshold` bytes
+pub async fn read_field(mut field: Field<'_>, spool_threshold: usize) -> Result<Upload, ApiError> {
+    let mut writer = UploadWriter::new(spool_threshold);
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        writer.write(&chunk).await?;
+    }
+    writer.finish().await
+}
+
+/// Accumulates a document arriving in chunks, spooling it to a temp file
+/// once it grows past the threshold
+pub struct UploadWriter {
+    spool_threshold: usize,
+    buffer: Vec<u8>,
+    spool: Option<(tokio::fs::File, TempPath)>,
+    len: u64,
+}
+
+impl UploadWriter {
+    pub fn new(spool_threshold: usize) -> Self {
+        Self {
+            spool_threshold,
+            buffer: Vec::new(),
+            spool: None,
+            len: 0,
+        }
+    }
+
+    /// Bytes written so far
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub async fn write(&mut self, chunk: &[u8]) -> Result<(), ApiError> {
+        self.len += chunk.len() as u64;
+        if self.spool.is_none() && self.buffer.len() + chunk.len() > self.spool_threshold {
+            let (file, path) = tempfile::NamedTempFile::new()
+                .map_err(ApiError::TempFile)?
+                .into_parts();
+            let mut file = tokio::fs::File::from_std(file);
+            file.write_all(&self.buffer)
+                .await
+                .map_err(ApiError::TempFile)?;
+            self.buffer = Vec::new();
+            self.spool = Some((file, path));
+        }
+        match &mut self.spool {
+            Some((file, _)) => file.write_all(chunk).await.map_err(ApiError::TempFile)?,
+            None => self.buffer.extend_from_slice(chunk),
+        }
+        Ok(())
+    }
+
+    pub async fn finish(self) -> Result<Upload, ApiError> {
+        match self.spool {
+            Some((mut file, path)) => {
+                file.flush().await.map_err(ApiError::TempFile)?;
+                log::info!("Spooled {} byte upload to {}", self.len, path.display());
+                Ok(Upload::Spooled {
+                    path: Arc::new(path),
+                    len: self.len,
+                })
+            }
+            None => Ok(Upload::Memory(Bytes::from(self.buffer))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_lose_paths_and_unsafe_characters() {
+        assert_eq!(safe_file_name("brief.pdf").as_deref(), Some("brief.pdf"));
+        assert_eq!(
+            safe_file_name("..\\..\\C:\\filings/Mot\u{e9}on 12.pdf").as_deref(),
+            Some("Mot\u{e9}on 12.pdf")
+        );
+        assert_eq!(
+            safe_file_name("\u{1b}[31mred\u{202e}fdp.exe").as_deref(),
+            Some("__31mred_fdp.exe")
+        );
+        assert_eq!(safe_file_name("../"), None);
+        assert_eq!(safe_file_name(".."), None);
+        assert_eq!(safe_file_name(&"a".repeat(500)).unwrap().len(), 100);
+    }
+}
@@ -0,0 +1,77 @@
+//! Semantic validation of CaseLawDocument, mirroring the Pydantic model in
+//! python-services/shared/models.py so both entry points report the same problems
+
+use crate::models::{CaseLawDocument, FieldViolation, OpinionType, SectionType};
+
+pub const MIN_YEAR: i32 = 1900;
+pub const MAX_YEAR: i32 = 2100;
+pub const FINAL_JUDGMENTS: [&str; 3] = ["Affirmed", "Reversed", "Remanded"];
+
+/// Return every violation rather than stopping at the first
+pub fn validate_case_law_document(doc: &CaseLawDocument) -> Vec<FieldViolation> {
+    let mut violations = Vec::new();
+    let mut violation = |field: &str, message: String| {
+        violations.push(FieldViolation {
+            field: field.to_string(),
+            message,
+        })
+    };
+
+    let case_name_len = doc.case_name.trim().chars().count();
+    if !(5..=500).contains(&case_name_len) {
+        violation(
+            "case_name",
+            format!("must be 5-500 characters, got {}", case_name_len),
+        );
+    }
+    if !doc.case_name.contains(" v. ") && !doc.case_name.contains(" v ") {
+        violation("case_name", "must contain \" v. \" or \" v \"".to_string());
+    }
+
+    if !(MIN_YEAR..=MAX_YEAR).contains(&doc.year) {
+        violation(
+            "year",
+            format!(
+                "must be between {} and {}, got {}",
+                MIN_YEAR, MAX_YEAR, doc.year
+            ),
+        );
+    }
+
+    if doc.opinion_type.parse::<OpinionType>().is_err() {
+        let allowed: Vec<_> = OpinionType::ALL.iter().map(|t| t.as_str()).collect();
+        violation(
+            "opinion_type",
+            format!("must be one of {:?}, got {:?}", allowed, doc.opinion_type),
+        );
+    }
+
+    let sections = doc.sections();
+    let min_lengths = [
+        (SectionType::Facts, 50),
+        (SectionType::Issue, 20),
+        (SectionType::Reasoning, 100),
+        (SectionType::Holding, 20),
+    ];
+    for (section, min_chars) in min_lengths {
+        let len = sections[&section].trim().chars().count();
+        if len < min_chars {
+            violation(
+                section.as_str(),
+                format!("must be at least {} characters, got {}", min_chars, len),
+            );
+        }
+    }
+
+    if !FINAL_JUDGMENTS.contains(&doc.final_judgment.as_str()) {
+        violation(
+            "final_judgment",
+            format!(
+                "must be one of {:?}, got {:?}",
+                FINAL_JUDGMENTS, doc.final_judgment
+            ),
+        );
+    }
+
+    violations
+}
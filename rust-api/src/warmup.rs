@@ -0,0 +1,72 @@
+//! Opens connections to every downstream before the gateway accepts
+//! traffic, so the first real requests find them idle in the pool instead
+//! of paying for TCP and TLS setup
+
+use futures::future;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::downstream::{self, Service};
+use crate::state::AppState;
+
+/// A downstream URL that couldn't be reached during warm-up
+#[derive(Debug)]
+pub struct WarmupFailure {
+    pub url: String,
+    pub error: String,
+}
+
+/// Open WARMUP_CONNECTIONS connections to each downstream URL and return
+/// the URLs that couldn't be reached
+pub async fn warm_up(state: &AppState) -> Vec<WarmupFailure> {
+    let config = state.config();
+    // More than the pool keeps idle would be closed as soon as they finish
+    let connections = config
+        .warmup_connections
+        .min(config.downstream_pool_max_idle_per_host);
+    if connections == 0 {
+        return Vec::new();
+    }
+    let urls: BTreeSet<&str> = Service::ALL
+        .into_iter()
+        .map(|service| service.base_url(&config))
+        .chain(config.ocr_service_urls.iter().map(String::as_str))
+        .collect();
+
+    let client = state.downstream.client();
+    let warming = urls
+        .iter()
+        .map(|base| warm_url(client, base, connections, config.warmup_timeout));
+    let failures: Vec<WarmupFailure> = future::join_all(warming)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    log::info!(
+        "Warmed {} connection(s) to each of {} downstream URL(s); {} unreachable",
+        connections,
+        urls.len(),
+        failures.len()
+    );
+    failures
+}
+
+/// Requests sent at once each take their own connection. Any HTTP
+/// response counts: the connection is what's being warmed.
+async fn warm_url(
+    client: &reqwest::Client,
+    base: &str,
+    connections: usize,
+    timeout: Duration,
+) -> Option<WarmupFailure> {
+    let url = format!("{}/health", base.trim_end_matches('/'));
+    let attempts = (0..connections).map(|_| client.get(&url).timeout(timeout).send());
+    future::join_all(attempts)
+        .await
+        .into_iter()
+        .find_map(Result::err)
+        .map(|e| WarmupFailure {
+            url: downstream::redact_credentials(base),
+            error: e.without_url().to_string(),
+        })
+}
@@ -0,0 +1,133 @@
+//! The JSON body limit and the multipart upload limit apply independently,
+//! and bodies sent too slowly are cut off by the body timeout
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use futures::{stream, StreamExt};
+use legal_judge_api::{config::Config, models::ErrorResponse, state::AppState};
+use std::time::Duration;
+use tower::ServiceExt;
+
+const JSON_LIMIT: usize = 256;
+const UPLOAD_LIMIT: usize = 2048;
+const BOUNDARY: &str = "limit-test-boundary";
+
+fn app() -> Router {
+    app_with(|_| {})
+}
+
+fn app_with(customize: impl FnOnce(&mut Config)) -> Router {
+    let mut config = Config::from_env().expect("default config is valid");
+    config.max_json_body_bytes = JSON_LIMIT;
+    config.max_upload_bytes = UPLOAD_LIMIT;
+    // Nothing listens here, so an accepted upload fails fast at OCR
+    config.ocr_service_url = "http://127.0.0.1:9".to_string();
+    config.ocr_service_urls = vec![config.ocr_service_url.clone()];
+    config.ocr_chunk_pages = 0;
+    config.max_retries = 0;
+    customize(&mut config);
+    legal_judge_api::app(AppState::new(config).expect("client builds"))
+}
+
+fn json_request(path: &str, len: usize) -> Request<Body> {
+    let body = format!(r#"{{"facts":"{}","issue":"x"}}"#, "a".repeat(len));
+    Request::post(path)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn multipart_request(file_len: usize) -> Request<Body> {
+    let body = format!(
+        "--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"brief.pdf\"\r\n\
+         Content-Type: application/pdf\r\n\r\n%PDF-{}\r\n--{b}--\r\n",
+        // Past the magic-bytes check, so only the size decides
+        "%".repeat(file_len.saturating_sub(5)),
+        b = BOUNDARY
+    );
+    Request::post("/api/analyze-brief")
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn error_body(response: Response) -> ErrorResponse {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).expect("error responses are ErrorResponse JSON")
+}
+
+#[tokio::test]
+async fn oversized_json_body_is_413_error_response() {
+    let response = app()
+        .oneshot(json_request("/api/predict", JSON_LIMIT * 2))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body = error_body(response).await;
+    assert_eq!(body.status, "error");
+    assert!(body.error.contains("too large"), "{}", body.error);
+}
+
+#[tokio::test]
+async fn oversized_multipart_upload_is_413_error_response() {
+    let response = app()
+        .oneshot(multipart_request(UPLOAD_LIMIT * 2))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body = error_body(response).await;
+    assert_eq!(body.status, "error");
+    assert!(body.error.contains("too large"), "{}", body.error);
+}
+
+#[tokio::test]
+async fn upload_above_json_limit_is_not_rejected() {
+    let response = app()
+        .oneshot(multipart_request(JSON_LIMIT * 4))
+        .await
+        .unwrap();
+
+    assert_ne!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+/// `first` followed by a body that never finishes arriving
+fn stalled_body(first: &'static str) -> Body {
+    let chunks = stream::once(async move { Ok::<_, std::io::Error>(Bytes::from(first)) })
+        .chain(stream::pending());
+    Body::from_stream(chunks)
+}
+
+#[tokio::test]
+async fn stalled_bodies_are_408_error_responses() {
+    let app = app_with(|config| config.request_body_timeout = Duration::from_millis(100));
+    let json = Request::post("/api/predict")
+        .header(CONTENT_TYPE, "application/json")
+        .body(stalled_body(r#"{"facts":"The landlord"#))
+        .unwrap();
+    let upload = Request::post("/api/analyze-brief")
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        )
+        .body(stalled_body(
+            "--limit-test-boundary\r\nContent-Disposition: form-data; name=\"file\"; \
+             filename=\"brief.pdf\"\r\nContent-Type: application/pdf\r\n\r\n%PDF-1.4",
+        ))
+        .unwrap();
+
+    for request in [json, upload] {
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+        let body = error_body(response).await;
+        assert!(body.error.contains("not received within"), "{}", body.error);
+    }
+}
@@ -0,0 +1,133 @@
+//! Unknown routes and wrong methods get ErrorResponse bodies
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::ALLOW, Request, StatusCode},
+    response::Response,
+    Router,
+};
+use legal_judge_api::{
+    config::Config, handlers::fallback::ENDPOINTS, models::ErrorResponse, state::AppState,
+};
+use tower::ServiceExt;
+
+fn app() -> Router {
+    app_with_prefix("")
+}
+
+fn app_with_prefix(prefix: &str) -> Router {
+    let mut config = Config::from_env().expect("default config is valid");
+    // Nothing listens here, so routed requests fail fast downstream
+    for url in [
+        &mut config.ocr_service_url,
+        &mut config.search_service_url,
+        &mut config.prediction_service_url,
+        &mut config.opinion_service_url,
+        &mut config.ingestion_service_url,
+    ] {
+        *url = "http://127.0.0.1:9".to_string();
+    }
+    config.ocr_service_urls = vec![config.ocr_service_url.clone()];
+    config.max_retries = 0;
+    config.api_prefix = prefix.to_string();
+    legal_judge_api::app(AppState::new(config).expect("client builds"))
+}
+
+async fn send(method: &str, path: &str) -> Response {
+    send_to(app(), method, path).await
+}
+
+async fn send_to(app: Router, method: &str, path: &str) -> Response {
+    let request = Request::builder()
+        .method(method)
+        .uri(path)
+        .body(Body::empty())
+        .unwrap();
+    app.oneshot(request).await.unwrap()
+}
+
+async fn error_body(response: Response) -> ErrorResponse {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).expect("ErrorResponse body")
+}
+
+#[tokio::test]
+async fn unknown_route_lists_endpoints() {
+    let response = send("GET", "/api/nope").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = error_body(response).await;
+    assert_eq!(body.error, "no route for GET /api/nope");
+    let details = body.details.expect("endpoint list");
+    assert!(details.contains("POST /api/search"), "{}", details);
+}
+
+#[tokio::test]
+async fn wrong_method_reports_allowed_methods() {
+    let response = send("DELETE", "/api/search").await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers()[ALLOW], "GET,HEAD,POST");
+
+    let body = error_body(response).await;
+    assert_eq!(body.error, "method DELETE is not allowed for /api/search");
+    assert_eq!(body.details.as_deref(), Some("allowed methods: GET, POST"));
+}
+
+#[tokio::test]
+async fn wrong_method_on_parameterised_route() {
+    let response = send("POST", "/api/similar-cases/doc-1").await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    let body = error_body(response).await;
+    assert_eq!(body.details.as_deref(), Some("allowed methods: GET"));
+}
+
+#[tokio::test]
+async fn every_listed_endpoint_is_routed() {
+    for (method, pattern) in ENDPOINTS {
+        let path = pattern.replace(":id", "x").replace(":document_id", "x");
+        let response = send(method, &path).await;
+        let status = response.status();
+        assert_ne!(
+            status,
+            StatusCode::METHOD_NOT_ALLOWED,
+            "{} {}",
+            method,
+            path
+        );
+        if status == StatusCode::NOT_FOUND {
+            let body = error_body(response).await;
+            assert!(
+                !body.error.starts_with("no route"),
+                "{} {} is listed but not routed",
+                method,
+                path
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn prefix_nests_routes_but_keeps_root_health() {
+    let app = app_with_prefix("/legal-judge");
+    let response = send_to(app.clone(), "GET", "/legal-judge/api/version").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    for path in ["/health", "/legal-judge/health"] {
+        let response = send_to(app.clone(), "GET", path).await;
+        assert_eq!(response.status(), StatusCode::OK, "{}", path);
+    }
+
+    let response = send_to(app.clone(), "GET", "/api/version").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let details = error_body(response).await.details.expect("endpoint list");
+    assert!(
+        details.contains("POST /legal-judge/api/search"),
+        "{}",
+        details
+    );
+
+    let response = send_to(app, "DELETE", "/legal-judge/api/search").await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    let body = error_body(response).await;
+    assert_eq!(body.details.as_deref(), Some("allowed methods: GET, POST"));
+}
@@ -0,0 +1,305 @@
+//! In-process stand-ins for the Python services, for hermetic integration
+//! tests. Each mock is a small axum server on an ephemeral port that
+//! records every request and answers from a per-path table of canned
+//! responses, seeded with bodies shaped like the real services'.
+
+#![allow(dead_code)] // each test binary uses a different subset
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::CONTENT_TYPE, HeaderMap, Method, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use legal_judge_api::config::Config;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A request a mock received
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: Method,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl Recorded {
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).expect("request body is JSON")
+    }
+
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+#[derive(Clone)]
+struct Canned {
+    status: StatusCode,
+    content_type: String,
+    body: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Inner {
+    responses: HashMap<String, Canned>,
+    /// Responses for requests whose body contains a marker, checked first
+    matching: Vec<(String, Vec<u8>, Canned)>,
+    /// How long to wait before answering requests to a path
+    delays: HashMap<String, Duration>,
+    requests: Vec<Recorded>,
+}
+
+/// One mock service; clones share its state
+#[derive(Clone)]
+pub struct MockService {
+    pub url: String,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl MockService {
+    pub async fn start() -> Self {
+        let inner = Arc::new(Mutex::new(Inner::default()));
+        let handler_state = inner.clone();
+        let app = Router::new().fallback(move |request: Request<Body>| {
+            let inner = handler_state.clone();
+            async move { respond(&inner, request).await }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock service");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        MockService { url, inner }
+    }
+
+    /// Answer requests to `path` with `status` and `body` from now on
+    pub fn respond(&self, path: &str, status: StatusCode, body: Value) -> &Self {
+        self.respond_bytes(path, status, "application/json", body.to_string())
+    }
+
+    /// Like [`Self::respond`], for bodies that aren't JSON
+    pub fn respond_bytes(
+        &self,
+        path: &str,
+        status: StatusCode,
+        content_type: &str,
+        body: impl Into<Vec<u8>>,
+    ) -> &Self {
+        let canned = Canned {
+            status,
+            content_type: content_type.to_string(),
+            body: body.into(),
+        };
+        self.inner
+            .lock()
+            .unwrap()
+            .responses
+            .insert(path.to_string(), canned);
+        self
+    }
+
+    /// Answer requests to `path` whose body contains `marker` with `status`
+    /// and `body`, ahead of the path's usual response
+    pub fn respond_matching(
+        &self,
+        path: &str,
+        marker: &str,
+        status: StatusCode,
+        body: Value,
+    ) -> &Self {
+        let canned = Canned {
+            status,
+            content_type: "application/json".to_string(),
+            body: body.to_string().into_bytes(),
+        };
+        self.inner.lock().unwrap().matching.push((
+            path.to_string(),
+            marker.as_bytes().to_vec(),
+            canned,
+        ));
+        self
+    }
+
+    /// Hold each answer to `path` back by `delay`
+    pub fn delay(&self, path: &str, delay: Duration) -> &Self {
+        self.inner
+            .lock()
+            .unwrap()
+            .delays
+            .insert(path.to_string(), delay);
+        self
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.inner.lock().unwrap().requests.clone()
+    }
+}
+
+async fn respond(inner: &Mutex<Inner>, request: Request<Body>) -> Response {
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, usize::MAX).await.unwrap_or_default();
+    let path = parts.uri.path().to_string();
+    let delay = inner.lock().unwrap().delays.get(&path).copied();
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+    }
+    let mut inner = inner.lock().unwrap();
+    inner.requests.push(Recorded {
+        method: parts.method,
+        path: path.clone(),
+        query: parts.uri.query().map(str::to_string),
+        headers: parts.headers,
+        body: body.to_vec(),
+    });
+    let matching = inner.matching.iter().find(|(matched, marker, _)| {
+        *matched == path
+            && body
+                .windows(marker.len())
+                .any(|window| window == &marker[..])
+    });
+    match matching
+        .map(|(_, _, canned)| canned)
+        .or(inner.responses.get(&path))
+    {
+        Some(canned) => (
+            canned.status,
+            [(CONTENT_TYPE, canned.content_type.clone())],
+            canned.body.clone(),
+        )
+            .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "detail": format!("mock has no response for {}", path) })),
+        )
+            .into_response(),
+    }
+}
+
+/// Every downstream the gateway calls, answering like a healthy deployment
+pub struct MockServices {
+    pub ocr: MockService,
+    pub ingestion: MockService,
+    pub search: MockService,
+    pub prediction: MockService,
+    pub opinion: MockService,
+}
+
+pub const OCR_TEXT: &str =
+    "The tenant withheld rent after the landlord failed to repair the heating.";
+
+impl MockServices {
+    pub async fn start() -> Self {
+        let mocks = MockServices {
+            ocr: MockService::start().await,
+            ingestion: MockService::start().await,
+            search: MockService::start().await,
+            prediction: MockService::start().await,
+            opinion: MockService::start().await,
+        };
+        mocks.ocr.respond(
+            "/ocr/pdf",
+            StatusCode::OK,
+            json!({
+                "full_text": OCR_TEXT,
+                "page_count": 1,
+                "ocr_confidence": 0.93,
+                "has_images": false
+            }),
+        );
+        mocks.search.respond(
+            "/search",
+            StatusCode::OK,
+            json!({
+                "results": [search_result("doc-1", "Hilder v. St. Peter", 0.91)],
+                "total_results": 1,
+                "search_time_ms": 12.5,
+                "query": "warranty of habitability",
+                "distance_metric": "cosine"
+            }),
+        );
+        mocks.prediction.respond(
+            "/predict/outcome",
+            StatusCode::OK,
+            json!({
+                "prediction": {
+                    "outcome": "Affirmed",
+                    "probabilities": { "Affirmed": 0.8, "Reversed": 0.15, "Remanded": 0.05 },
+                    "confidence": 0.8,
+                    "supporting_cases": ["Hilder v. St. Peter"],
+                    "explanation": "Similar habitability cases were affirmed.",
+                    "rationale": [{
+                        "factor": "Similar case (92% match) was affirmed",
+                        "weight": 0.6,
+                        "cited_case": "Hilder v. St. Peter (1984)"
+                    }]
+                }
+            }),
+        );
+        mocks.opinion.respond(
+            "/generate/opinion",
+            StatusCode::OK,
+            json!({
+                "opinion": {
+                    "full_text": "PER CURIAM\nThe judgment is affirmed.\n---\nDISCLAIMER: research only",
+                    "sections": { "holding": "The judgment is affirmed." },
+                    "cited_precedents": ["Hilder v. St. Peter"],
+                    "generation_metadata": { "model": "mock", "mock": false },
+                    "disclaimer": "research only"
+                }
+            }),
+        );
+        mocks
+    }
+
+    /// Default configuration with every service URL pointed at the mocks
+    pub fn config(&self) -> Config {
+        let mut config = Config::from_env().expect("default config is valid");
+        config.ocr_service_url = self.ocr.url.clone();
+        config.ocr_service_urls = vec![self.ocr.url.clone()];
+        config.ingestion_service_url = self.ingestion.url.clone();
+        config.search_service_url = self.search.url.clone();
+        config.prediction_service_url = self.prediction.url.clone();
+        config.opinion_service_url = self.opinion.url.clone();
+        config
+    }
+}
+
+pub fn search_result(document_id: &str, case_name: &str, score: f64) -> Value {
+    json!({
+        "case_name": case_name,
+        "year": 1984,
+        "court": "Vermont Supreme Court",
+        "section_type": "holding",
+        "similarity_score": score,
+        "distance": 1.0 - score,
+        "snippet": "Implied warranty of habitability exists in every residential lease",
+        "char_start": 0,
+        "char_end": 66,
+        "metadata": { "document_id": document_id, "opinion_type": "majority" }
+    })
+}
+
+/// A stored CaseLawDocument, as GET /documents/:id returns it
+pub fn case_document(document_id: &str, case_name: &str) -> Value {
+    json!({
+        "case_name": case_name,
+        "year": 1984,
+        "court": "Vermont Supreme Court",
+        "opinion_type": "majority",
+        "facts": "The tenant withheld rent.",
+        "issue": "Whether the lease was breached.",
+        "reasoning": "The landlord ignored the sewage leak.",
+        "holding": "Every residential lease carries an implied warranty of habitability.",
+        "final_judgment": "Affirmed.",
+        "document_id": document_id,
+        "ingestion_timestamp": "2024-01-01T00:00:00Z",
+        "validation_status": "valid"
+    })
+}
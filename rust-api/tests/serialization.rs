@@ -0,0 +1,118 @@
+//! Response bodies serialize deterministically and round-trip what the
+//! services send
+
+use legal_judge_api::models::{
+    AnalyzeOutcome, AnalyzeResponse, CaseResult, GenerationMetadata, PageText, PredictionResponse,
+    SimilarityScore,
+};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+
+fn prediction(probabilities: &[(&str, f64)]) -> PredictionResponse {
+    let probabilities = probabilities
+        .iter()
+        .map(|&(label, p)| (label.to_string(), p))
+        .collect();
+    PredictionResponse {
+        status: "success".to_string(),
+        predicted_outcome: "AFFIRMED".to_string(),
+        probabilities,
+        confidence: 0.7,
+        supporting_cases: Vec::new(),
+        explanation: String::new(),
+        rationale: None,
+        warnings: Vec::new(),
+        metadata: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn identical_predictions_serialize_byte_identically() {
+    // Same contents built in different insertion orders
+    let first = prediction(&[("REVERSED", 0.2), ("AFFIRMED", 0.7), ("REMANDED", 0.1)]);
+    let second = prediction(&[("REMANDED", 0.1), ("REVERSED", 0.2), ("AFFIRMED", 0.7)]);
+
+    let first = serde_json::to_vec(&first).unwrap();
+    let second = serde_json::to_vec(&second).unwrap();
+    assert_eq!(first, second);
+
+    let text = String::from_utf8(first).unwrap();
+    let affirmed = text.find("AFFIRMED\":").unwrap();
+    let remanded = text.find("REMANDED").unwrap();
+    let reversed = text.find("REVERSED").unwrap();
+    assert!(affirmed < remanded && remanded < reversed, "{}", text);
+}
+
+#[test]
+fn generation_metadata_types_known_fields_and_keeps_the_rest() {
+    let raw = json!({
+        "model": "gpt-4",
+        "temperature": 0.3,
+        "generation_time_ms": 812.5,
+        "mock": false,
+        "precedents_used": 4,
+        "opinion_type": "majority"
+    });
+    let metadata: GenerationMetadata = serde_json::from_value(raw).unwrap();
+    assert_eq!(metadata.model_name.as_deref(), Some("gpt-4"));
+    assert_eq!(metadata.temperature, Some(0.3));
+    assert_eq!(metadata.generation_time_ms, Some(812.5));
+    assert_eq!(metadata.tokens_used, None);
+    assert_eq!(metadata.extra["precedents_used"], 4);
+    assert!(!metadata.extra.contains_key("model"));
+
+    let value = serde_json::to_value(&metadata).unwrap();
+    assert_eq!(value["model_name"], "gpt-4");
+    assert_eq!(value["opinion_type"], "majority");
+    assert!(value.get("tokens_used").is_none());
+}
+
+#[test]
+fn analyze_response_fields_list_every_serialized_field() {
+    let response = AnalyzeResponse {
+        ocr_text: "text".to_string(),
+        ocr_text_id: Some("ocr-1".to_string()),
+        pages: vec![PageText {
+            page_number: 1,
+            text: "text".to_string(),
+        }],
+        failed_pages: vec![2],
+        predicted_outcome: AnalyzeOutcome {
+            label: "MIXED".to_string(),
+            probabilities: BTreeMap::new(),
+        },
+        top_cases: vec![CaseResult {
+            case_name: "Hilder v. St. Peter".to_string(),
+            citation: "478 A.2d 202".to_string(),
+            relevance_score: SimilarityScore::new(0.9),
+            snippet: String::new(),
+        }],
+        judge_opinion: "opinion".to_string(),
+        warnings: vec!["warning".to_string()],
+        metadata: HashMap::from([("ocr_language".to_string(), json!("eng"))]),
+        mock: true,
+    };
+    let value = serde_json::to_value(&response).unwrap();
+    let mut serialized: Vec<&str> = value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    let mut known = AnalyzeResponse::FIELDS.to_vec();
+    serialized.sort_unstable();
+    known.sort_unstable();
+    assert_eq!(serialized, known);
+}
+
+#[test]
+fn similarity_scores_are_clamped_and_serialize_as_numbers() {
+    let scores: Vec<SimilarityScore> = serde_json::from_value(json!([0.4, 1.7, -0.2])).unwrap();
+    let values: Vec<f64> = scores.iter().map(|score| score.get()).collect();
+    assert_eq!(values, [0.4, 1.0, 0.0]);
+    assert_eq!(
+        serde_json::to_value(scores).unwrap(),
+        json!([0.4, 1.0, 0.0])
+    );
+    assert_eq!(SimilarityScore::new(f64::NAN).get(), 0.0);
+}